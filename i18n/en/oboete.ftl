@@ -18,6 +18,22 @@ light = Light
 
 <#-- Settings -->
 settings = Settings
+next-day-starts-at = Next day starts at
+next-day-starts-at-description = Reviews done before this hour count towards the previous day
+autoplay-audio = Autoplay audio
+autoplay-audio-description = Play the audio of a flashcard side as soon as it's shown
+duplicate-scope = Look for duplicates in
+duplicate-scope-description = Flashcards with the same front (ignoring case and whitespace) are considered duplicates
+same-folder = The same folder
+same-studyset = The same study set
+ordering = Ordering
+studyset-order = Order study sets by
+studyset-order-description = When ordered manually, study sets are moved from their context menu on the sidebar
+folder-order = Order folders by
+folder-order-description = When ordered manually, folders are moved by dragging their handle onto another folder
+order-manual = Manual
+order-alphabetical = Alphabetical
+order-most-due = Most due first
 
 <#-- Application MenuBar -->
 file = File
@@ -25,7 +41,10 @@ new-studyset = New StudySet
 edit = Edit
 rename-studyset = Rename StudySet
 delete-studyset = Delete StudySet
+move-up = Move Up
+move-down = Move Down
 view = View
+note-types = Note Types
 about = About
 
 <#-- Page Headers -->
@@ -43,15 +62,39 @@ ok = Ok
 
 <#-- Folders Page -->
 empty-folders-page = Create some folders to get started...
+folder-counts = {$due} due · {$total} total
 
 <#-- New Folder Dialog -->
 create-folder = Create Folder
+create-subfolder = Create Subfolder
 folder-name = Folder Name
 
 <#-- Folder Details Context Page -->
 folder-details = Folder Details
+parent-folder = Parent Folder
+no-parent-folder = None (top level)
 current-retention-rate = Current Retention Rate
 retention-rate = Retention Rate
+learning-steps = Learning Steps
+relearning-steps = Relearning Steps
+steps-description = Separate the steps with spaces, like 1m 10m 1h (s, m, h and d units, minutes if none). Leave empty to skip them.
+leeches = Leeches
+leech-threshold = Leech Threshold
+leech-threshold-description = Flashcards forgotten {$lapses} times are tagged as leeches
+leech-detection-disabled = Leech detection is disabled
+leech-suspend = Suspend Leeches
+fsrs-parameters = FSRS Parameters
+default-parameters = Using the default parameters
+optimised-parameters = Using parameters optimised for this folder
+reset-parameters = Reset
+optimise-parameters = Optimise Parameters
+optimise-parameters-description = Fit the parameters to the review history of this folder
+optimise = Optimise
+optimising = Optimising...
+optimisation-result = Optimisation Result
+optimisation-log-loss = Log loss: { $old } → { $new } ({ $reviews } reviews)
+accept = Accept
+discard = Discard
 
 <#-- Folder Deletion Dialog -->
 delete-folder = Delete Folder
@@ -69,17 +112,58 @@ flashcard-front-title = Flashcard Front
 flashcard-back-title = Flashcard Back
 flashcard-front-placeholder = Front Content
 flashcard-back-placeholder = Back Content
+markdown-preview = Preview Markdown
 select-image = Select an Image
+tags = Tags
+new-tag-placeholder = New tag (separate tags with spaces)
+all-tags = All tags
+unsupported-field = This content was created with a newer version of Oboete
 browse = Browse
 create = Create
 front-image-alt = Front Image Alternative Text
 back-image-alt = Back Image Alternative Text
+select-audio = Select an Audio File
+audio-transcript = Transcript (optional)
+code-language = Language
+code-placeholder = Source Code
+block-title = Block {$number}
+block-text-placeholder = Block Text
+block-image-alt = Image Alternative Text
+add-block = Add Block
+suspend = Suspend
+suspended = Suspended
+buried = Buried
+bury-until-tomorrow = Bury until tomorrow
+undo = Undo
+leech-detected = This flashcard has been forgotten {$lapses} times and was tagged as a leech, consider rewriting it
+leech-suspended = This flashcard has been forgotten {$lapses} times and was suspended as a leech, consider rewriting it
+cloze-placeholder = Cloze Text
+cloze-selection-placeholder = Text to hide
+wrap-as-cloze = Wrap as Cloze
+cloze-description = Hide text with {"{{"}c1::answer{"}}"} or {"{{"}c1::answer::hint{"}}"}, every number becomes it's own flashcard
+cloze-preview = Preview
+cloze-back-extra-title = Back Extra (optional)
 reset-flashcard-title = Reset Flashcard Status
 reset-flashcard-button = Reset
+flashcard-history = History
+revert = Revert
+reverted-revision = Reverted to the version of { $date }
+edit-reason-placeholder = Reason of the change (optional)
 current-flashcard-status = Current Status
+note-type = Note Type
+basic-flashcard = Basic Flashcard
 
 <#-- Flashcard Options Context Page -->
 flashcard-options = Flashcard Options
+duplicates = Duplicates
+duplicate-action = When Importing a Flashcard That Already Exists
+duplicate-scope-folder-description = Flashcards with the same front on this folder
+duplicate-scope-studyset-description = Flashcards with the same front on any folder of this study set
+duplicate-skip = Skip It
+duplicate-update = Update It
+duplicate-keep-both = Keep Both
+import-summary = { $added } added, { $updated } updated, { $skipped } skipped
+duplicate-flashcard-warning = There is already a flashcard with this front
 folder-import = Import
 import-between-term-title = Between Term & Definition
 import-between-term-placeholder = Character Between Term & Definition
@@ -90,6 +174,8 @@ import-content-placeholder = Content to Import
 import-button = Import
 reset-folder-flashcards-title = Reset All Flashcard Status
 reset-folder-flashcards-button = Reset
+unsuspend-folder-flashcards-title = Unsuspend All Flashcards
+unsuspend-folder-flashcards-button = Unsuspend
 import-anki-title = Import from Anki File
 import-anki-button = Select Anki File
 export-folder-flashcards-title = Export Folder Flashcards
@@ -99,7 +185,107 @@ export-folder-flashcards-anki-button = Export Anki
 <#-- Flashcard Deletion Dialog -->
 delete-flashcard = Delete Flashcard
 
+<#-- Flashcard Move/Copy Dialog -->
+move-to = Move to…
+copy-to = Copy to…
+move-flashcards = Move { $count ->
+    [one] Flashcard
+   *[other] { $count } Flashcards
+}
+copy-flashcards = Copy { $count ->
+    [one] Flashcard
+   *[other] { $count } Flashcards
+}
+move = Move
+copy = Copy
+destination-folder = Destination folder
+keep-progress = Keep progress
+
+<#-- Flashcard Selection -->
+selected-flashcards = { $count } selected
+select-all = Select All
+clear-selection = Clear Selection
+add-tags = Add Tags
+unsuspend = Unsuspend
+reset-status = Reset Status
+export = Export
+delete = Delete
+delete-flashcards = Delete Selected Flashcards ({ $count })
+reset-flashcards = Reset Selected Flashcards ({ $count })
+suspend-flashcards = Suspend Selected Flashcards ({ $count })
+unsuspend-flashcards = Unsuspend Selected Flashcards ({ $count })
+tag-flashcards = Tag Selected Flashcards ({ $count })
+confirm-bulk-reset = Their progress will be lost, are you sure?
+
+<#-- Flashcards List Sort & Filters -->
+filter-flashcards = Filter flashcards
+no-matching-flashcards = No flashcards match the filters
+clear-filters = Clear Filters
+sort-by = Sort by
+sort-created = Creation order
+sort-alphabetical = Alphabetical
+sort-due-date = Due date
+sort-last-reviewed = Last reviewed
+sort-difficulty = Difficulty
+sort-stability = Stability
+all-statuses = All statuses
+all-flashcards = All flashcards
+due-flashcards = Due
+overdue-flashcards = Overdue
+new-flashcards = New
+all-field-types = All field types
+field-text = Text
+field-image = Image
+field-audio = Audio
+field-cloze = Cloze
+field-code = Code
+field-blocks = Blocks
+
+<#-- Note Types Page -->
+empty-note-types-page = Create some note types to get started...
+note-type-summary = {$fields} fields · {$templates} cards
+
+<#-- Note Type Details Context Page -->
+note-type-details = Note Type Details
+note-type-name = Note Type Name
+note-type-fields = Fields
+field-name = Field Name
+field-front = Front
+field-back = Back
+add-field = Add Field
+card-templates = Card Templates
+card-templates-description = Each note generates one flashcard per template. Available placeholders: {$placeholders}, {"{{"}FrontSide{"}}"} (back only)
+template-name = Template Name
+template-front = Front Template
+template-back = Back Template
+add-template = Add Template
+card-template-default-name = Card {$number}
+
+<#-- Note Type Deletion Dialog -->
+delete-note-type = Delete Note Type
+
 <#-- Study Page -->
 fsrs-mode = FSRS Mode - Due Cards - { $due } of { $total }
 study-mode =  Study Mode - Card { $number } of { $total }
 space-key = Space
+replay-audio-hint = Press R to replay
+session-finished = You have studied every flashcard of this session
+finish-session = Finish
+
+<#-- Trash Page -->
+trash = Trash
+empty-trash = Empty Trash
+empty-trash-page = The trash is empty
+restore = Restore
+deleted-at = Deleted { $date }
+confirm-move-to-trash = It will be moved to the trash, you can restore it from there.
+confirm-purge = This can't be undone, it will be deleted for good.
+delete-permanently = Delete Permanently
+trash-retention = Keep Deleted Items For
+trash-retention-description = Items on the trash are deleted for good after this time
+days = { $days } days
+
+<#-- Search Page -->
+search-placeholder = Search flashcards
+search-results = Results for "{ $query }" ({ $count })
+empty-search-page = No flashcards match your search
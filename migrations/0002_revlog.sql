@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS revlog (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    flashcard_id INTEGER NOT NULL,
+    reviewed_at INTEGER NOT NULL, -- Seconds since epoch
+    rating INTEGER NOT NULL, -- FlashcardStatus id
+    elapsed_days INTEGER NOT NULL,
+    previous_fsrs_state TEXT, -- Serialized MemoryState before the review (NULL for new cards)
+    new_fsrs_state TEXT NOT NULL, -- Serialized MemoryState after the review
+    interval INTEGER NOT NULL, -- Days until the next review
+    FOREIGN KEY (flashcard_id) REFERENCES flashcards(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_revlog_flashcard_id ON revlog(flashcard_id);
@@ -0,0 +1,2 @@
+-- Serialized optimised FSRS parameters of the folder (NULL uses the default parameters)
+ALTER TABLE folders ADD COLUMN fsrs_parameters TEXT;
@@ -0,0 +1,15 @@
+-- Due dates and review times go from days since epoch to seconds since epoch
+UPDATE flashcards SET due_date = due_date * 86400 WHERE due_date IS NOT NULL;
+UPDATE flashcards SET last_reviewed = last_reviewed * 86400 WHERE last_reviewed IS NOT NULL;
+
+-- The revlog interval goes from days to seconds until the next review
+UPDATE revlog SET interval = interval * 86400;
+
+-- Current (re)learning step of the flashcard (NULL when the flashcard is new or in review)
+ALTER TABLE flashcards ADD COLUMN learning_step INTEGER;
+-- 1 if the current step belongs to the relearning steps (the flashcard has been forgotten)
+ALTER TABLE flashcards ADD COLUMN relearning INTEGER NOT NULL DEFAULT 0;
+
+-- Space separated step delays, ej: "1m 10m"
+ALTER TABLE folders ADD COLUMN learning_steps TEXT NOT NULL DEFAULT '1m 10m';
+ALTER TABLE folders ADD COLUMN relearning_steps TEXT NOT NULL DEFAULT '10m';
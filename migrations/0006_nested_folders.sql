@@ -0,0 +1,3 @@
+ALTER TABLE folders ADD COLUMN parent_id INTEGER REFERENCES folders(id) ON DELETE CASCADE; -- NULL for top level folders
+
+CREATE INDEX IF NOT EXISTS idx_folders_parent_id ON folders(parent_id);
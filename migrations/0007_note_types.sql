@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS note_types (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    fields TEXT NOT NULL, -- JSON array with the field names
+    templates TEXT NOT NULL -- JSON array of card templates ({ ord, name, front, back })
+);
+
+CREATE TABLE IF NOT EXISTS notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_type_id INTEGER NOT NULL,
+    folder_id INTEGER NOT NULL,
+    fields TEXT NOT NULL, -- JSON array with the field values (same order as the note type fields)
+    FOREIGN KEY (note_type_id) REFERENCES note_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (folder_id) REFERENCES folders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_notes_folder_id ON notes(folder_id);
+
+-- Note the flashcard was generated from (NULL for plain flashcards) and the ord of the template that generated it
+ALTER TABLE flashcards ADD COLUMN note_id INTEGER REFERENCES notes(id) ON DELETE CASCADE;
+ALTER TABLE flashcards ADD COLUMN template_ord INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_flashcards_note_id ON flashcards(note_id);
+
+INSERT INTO note_types (name, fields, templates) VALUES
+(
+    'Basic (and reversed card)',
+    '["Front","Back"]',
+    '[{"ord":0,"name":"Card 1","front":"{{Front}}","back":"{{Back}}"},{"ord":1,"name":"Card 2","front":"{{Back}}","back":"{{Front}}"}]'
+),
+(
+    'Vocabulary',
+    '["Word","Meaning","Example"]',
+    '[{"ord":0,"name":"Recognition","front":"{{Word}}","back":"{{Meaning}}\n\n{{Example}}"},{"ord":1,"name":"Recall","front":"{{Meaning}}","back":"{{Word}}\n\n{{Example}}"}]'
+);
@@ -0,0 +1,4 @@
+-- Suspended flashcards are never studied until they are unsuspended
+ALTER TABLE flashcards ADD COLUMN suspended INTEGER NOT NULL DEFAULT 0;
+-- Study day (days since epoch) buried flashcards come back on, NULL if not buried
+ALTER TABLE flashcards ADD COLUMN buried_until INTEGER;
@@ -0,0 +1,6 @@
+-- Times a flashcard has been forgotten after being learned (rated Bad while in review)
+ALTER TABLE flashcards ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+-- Lapses after which a flashcard is tagged as a leech, 0 disables leech detection
+ALTER TABLE folders ADD COLUMN leech_threshold INTEGER NOT NULL DEFAULT 8;
+-- Leeches are also suspended if enabled
+ALTER TABLE folders ADD COLUMN leech_suspend INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Seconds since epoch the row was moved to the trash, NULL if it's not on the trash.
+-- Trashing a folder also trashes it's subfolders (with the same time) so they can be restored together
+ALTER TABLE studysets ADD COLUMN deleted_at INTEGER;
+ALTER TABLE folders ADD COLUMN deleted_at INTEGER;
+ALTER TABLE flashcards ADD COLUMN deleted_at INTEGER;
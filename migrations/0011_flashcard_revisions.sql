@@ -0,0 +1,12 @@
+-- Previous versions of the flashcards, a row is added every time the front or back of a flashcard is edited
+CREATE TABLE IF NOT EXISTS flashcard_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    flashcard_id INTEGER NOT NULL,
+    front TEXT NOT NULL, -- Front the flashcard had before the edit
+    back TEXT NOT NULL, -- Back the flashcard had before the edit
+    edited_at INTEGER NOT NULL, -- Seconds since epoch
+    reason TEXT, -- Why the flashcard was edited (optional)
+    FOREIGN KEY (flashcard_id) REFERENCES flashcards(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_flashcard_revisions_flashcard_id ON flashcard_revisions(flashcard_id);
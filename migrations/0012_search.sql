@@ -0,0 +1,45 @@
+-- Full text search over the text of the flashcards (text, alt text of images, transcripts, cloze and code).
+-- The sides are saved as json, so the searchable text is every string of it that is not a path or a code language
+-- (values saved before the json schema are indexed as they are). Kept in sync with the flashcards by the triggers below
+CREATE VIRTUAL TABLE IF NOT EXISTS flashcards_fts USING fts5(
+    front,
+    back,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO flashcards_fts (rowid, front, back)
+SELECT
+    id,
+    (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(front) THEN front ELSE json_quote(front) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language'))),
+    (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(back) THEN back ELSE json_quote(back) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language')))
+FROM flashcards;
+
+CREATE TRIGGER IF NOT EXISTS flashcards_fts_insert AFTER INSERT ON flashcards
+BEGIN
+    INSERT INTO flashcards_fts (rowid, front, back) VALUES (
+        new.id,
+        (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(new.front) THEN new.front ELSE json_quote(new.front) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language'))),
+        (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(new.back) THEN new.back ELSE json_quote(new.back) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language')))
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS flashcards_fts_update AFTER UPDATE OF front, back ON flashcards
+BEGIN
+    DELETE FROM flashcards_fts WHERE rowid = old.id;
+    INSERT INTO flashcards_fts (rowid, front, back) VALUES (
+        new.id,
+        (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(new.front) THEN new.front ELSE json_quote(new.front) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language'))),
+        (SELECT group_concat(value, ' ') FROM json_tree(CASE WHEN json_valid(new.back) THEN new.back ELSE json_quote(new.back) END)
+         WHERE type = 'text' AND (key IS NULL OR key NOT IN ('path', 'language')))
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS flashcards_fts_delete AFTER DELETE ON flashcards
+BEGIN
+    DELETE FROM flashcards_fts WHERE rowid = old.id;
+END;
@@ -0,0 +1,19 @@
+-- Manual ordering of study sets and folders, existing rows keep their creation order
+ALTER TABLE studysets ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE folders ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE studysets SET position = id;
+UPDATE folders SET position = id;
+
+-- Rows inserted without a position are placed last
+CREATE TRIGGER IF NOT EXISTS studysets_position_insert AFTER INSERT ON studysets
+WHEN new.position = 0
+BEGIN
+    UPDATE studysets SET position = (SELECT MAX(position) + 1 FROM studysets) WHERE id = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS folders_position_insert AFTER INSERT ON folders
+WHEN new.position = 0
+BEGIN
+    UPDATE folders SET position = (SELECT MAX(position) + 1 FROM folders) WHERE id = new.id;
+END;
@@ -1,12 +1,15 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use crate::app::app_menu::MenuAction;
+use crate::app::app_menu::{MenuAction, NavMenuAction};
 use crate::app::context_page::ContextPage;
+use crate::app::core::models::duplicates::DuplicateScope;
+use crate::app::core::models::ordering::OrderMode;
 use crate::app::core::models::studyset::StudySet;
-use crate::app::core::utils::OboeteToast;
+use crate::app::core::models::trash::TrashItem;
+use crate::app::core::utils::{Clock, OboeteToast, SystemClock};
 use crate::app::core::{init_database, utils};
 use crate::app::dialogs::{DialogPage, DialogState};
-use crate::app::screen::{Screen, flashcards, folders, study};
+use crate::app::screen::{Screen, flashcards, folders, note_types, search, study, trash};
 use crate::config::{AppTheme, OboeteConfig};
 use crate::key_binds::key_binds;
 use crate::{fl, icons};
@@ -34,6 +37,8 @@ mod screen;
 mod widgets;
 
 const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
+/// Days the trash can keep the deleted items for
+const TRASH_RETENTION_DAYS: &[u32] = &[7, 14, 30, 60, 90, 365];
 
 /// The application model stores app-specific state used to describe its interface and
 /// drive its logic.
@@ -62,6 +67,18 @@ pub struct AppModel {
     config: OboeteConfig,
     // Application Themes
     app_themes: Vec<String>,
+    // Hours a study day can start at
+    day_start_hours: Vec<String>,
+    // Options of the days the trash keeps the deleted items
+    trash_retention_options: Vec<String>,
+    // Options of where flashcards are considered duplicates
+    duplicate_scopes: Vec<String>,
+    // Options of how the study sets and folders are ordered
+    order_modes: Vec<String>,
+    /// Clock used to know when flashcards are due
+    clock: Arc<SystemClock>,
+    /// Text typed on the search entry of the header
+    search_query: String,
     /// Application State
     state: State,
 }
@@ -92,8 +109,24 @@ pub enum Message {
     UpdateConfig(OboeteConfig),
     /// Update the application theme
     UpdateTheme(usize),
+    /// Update the hour at which a new study day starts
+    UpdateNextDayStartsAt(usize),
+    /// Update if the audio of the flashcards is played as soon as it's shown
+    UpdateAutoplayAudio(bool),
+    /// Update the days the trash keeps the deleted items (by it's index on the options)
+    UpdateTrashRetentionDays(usize),
+    /// Update where flashcards with the same front are considered duplicates
+    UpdateDuplicateScope(usize),
+    /// Update how the study sets of the nav bar are ordered (by it's index on the options)
+    UpdateStudySetOrder(usize),
+    /// Update how the folders of a study set are ordered (by it's index on the options)
+    UpdateFolderOrder(usize),
     /// Callback after clicking something in the app menu
     MenuAction(app_menu::MenuAction),
+    /// Callback after clicking something in the context menu of a nav bar item
+    NavMenuAction(app_menu::NavMenuAction),
+    /// Callback after dropping a dragged [`StudySet`] (if it could be read) onto the given nav bar item
+    StudySetDropped(nav_bar::Id, Option<folders::DraggedStudySet>),
     /// Needed for responsive menu bar
     Surface(surface::Action),
     /// Asks to execute various actions related to the application dialogs
@@ -110,6 +143,8 @@ pub enum Message {
     FetchStudySets,
     /// Callback after fetching the studysets from the database
     FetchedStudySets(Result<Vec<StudySet>, anywho::Error>),
+    /// Callback after purging items from the trash, with the ids of the deleted folders
+    FoldersPurged(Vec<i32>),
 
     /// Folders Screen
     Folders(folders::Message),
@@ -117,13 +152,27 @@ pub enum Message {
     Flashcards(flashcards::Message),
     /// Study Screen
     Study(study::Message),
+    /// Note Types Screen
+    NoteTypes(note_types::Message),
+    /// Trash Screen
+    Trash(trash::Message),
+    /// Search Screen
+    Search(search::Message),
 
     /// Asks to open the folders page given a folder_id
     OpenFolders(i32),
-    /// Asks to open the flashcards page given a folder_id
-    OpenFlashcards(i32),
-    /// Asks to open the flashcards page given a folder_id
-    OpenStudy(i32),
+    /// Asks to open the flashcards page given a folder_id (and the flashcard to open the upsert page of, if any)
+    OpenFlashcards(i32, Option<i32>),
+    /// Asks to open the study page given a folder_id (and optionally a tag to filter the flashcards)
+    OpenStudy(i32, Option<String>),
+    /// Asks to open the note types page
+    OpenNoteTypes,
+    /// Asks to open the trash page
+    OpenTrash,
+    /// Callback after the user types on the search entry of the header
+    SearchQueryInput(String),
+    /// Asks to search the flashcards of every studyset with the current search query
+    OpenSearch,
 
     /// Callback after asking to perform an app-wide backup
     ComleteBackup(String),
@@ -181,9 +230,25 @@ impl cosmic::Application for AppModel {
             nav: nav_bar::Model::default(),
             key_binds: key_binds(),
             modifiers: Modifiers::empty(),
+            clock: Arc::new(SystemClock::new(flags.config.next_day_starts_at)),
             config_handler: flags.config_handler,
             config: flags.config,
             app_themes: vec![fl!("match-desktop"), fl!("dark"), fl!("light")],
+            day_start_hours: (0..24).map(|hour| format!("{hour:02}:00")).collect(),
+            trash_retention_options: TRASH_RETENTION_DAYS
+                .iter()
+                .map(|days| fl!("days", days = days))
+                .collect(),
+            duplicate_scopes: vec![fl!("same-folder"), fl!("same-studyset")],
+            order_modes: OrderMode::ALL
+                .iter()
+                .map(|order| match order {
+                    OrderMode::Manual => fl!("order-manual"),
+                    OrderMode::Alphabetical => fl!("order-alphabetical"),
+                    OrderMode::MostDue => fl!("order-most-due"),
+                })
+                .collect(),
+            search_query: String::new(),
             state: State::Loading,
         };
 
@@ -204,11 +269,69 @@ impl cosmic::Application for AppModel {
         vec![app_menu::menu_bar(&self.core, &self.key_binds)]
     }
 
+    /// Elements to pack at the end of the header bar.
+    fn header_end(&self) -> Vec<Element<'_, Self::Message>> {
+        let State::Ready { .. } = &self.state else {
+            return Vec::new();
+        };
+
+        vec![
+            widget::search_input(fl!("search-placeholder"), &self.search_query)
+                .on_input(Message::SearchQueryInput)
+                .on_submit(|_| Message::OpenSearch)
+                .width(Length::Fixed(240.))
+                .into(),
+        ]
+    }
+
     /// Enables the COSMIC application to create a nav bar with this model.
     fn nav_model(&self) -> Option<&nav_bar::Model> {
         Some(&self.nav)
     }
 
+    /// Nav bar of the study sets, a study set can be dropped on another one to take it's place (only when ordered manually)
+    fn nav_bar(&self) -> Option<Element<'_, cosmic::Action<Self::Message>>> {
+        if !self.core().nav_bar_active() {
+            return None;
+        }
+
+        let mut nav = widget::nav_bar(self.nav_model()?, |id| {
+            cosmic::action::cosmic(cosmic::app::Action::NavBar(id))
+        })
+        .on_context(|id| cosmic::action::cosmic(cosmic::app::Action::NavBarContext(id)))
+        .context_menu(self.nav_context_menu(self.core().nav_bar_context()))
+        .on_dnd_drop(|id, dragged: Option<folders::DraggedStudySet>, _action| {
+            cosmic::action::app(Message::StudySetDropped(id, dragged))
+        })
+        .into_container()
+        .width(Length::Shrink)
+        .height(Length::Shrink);
+
+        if !self.core().is_condensed() {
+            nav = nav.max_width(280);
+        }
+
+        Some(Element::from(nav))
+    }
+
+    /// Context menu of the study sets of the nav bar, they can only be moved when ordered manually
+    fn nav_context_menu(
+        &self,
+        id: nav_bar::Id,
+    ) -> Option<Vec<menu::Tree<cosmic::Action<Self::Message>>>> {
+        if self.config.studyset_order != OrderMode::Manual {
+            return None;
+        }
+
+        Some(menu::items(
+            &HashMap::new(),
+            vec![
+                menu::Item::Button(fl!("move-up"), None, NavMenuAction::MoveUp(id)),
+                menu::Item::Button(fl!("move-down"), None, NavMenuAction::MoveDown(id)),
+            ],
+        ))
+    }
+
     /// Display a context drawer if the context page is requested.
     fn context_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Self::Message>> {
         if !self.core.window.show_context {
@@ -237,6 +360,11 @@ impl cosmic::Application for AppModel {
                     flashcards_screen.view().map(Message::Flashcards)
                 }
                 Screen::Study(study_screen) => study_screen.view().map(Message::Study),
+                Screen::NoteTypes(note_types_screen) => {
+                    note_types_screen.view().map(Message::NoteTypes)
+                }
+                Screen::Trash(trash_screen) => trash_screen.view().map(Message::Trash),
+                Screen::Search(search_screen) => search_screen.view().map(Message::Search),
             },
         };
 
@@ -302,6 +430,15 @@ impl cosmic::Application for AppModel {
             Screen::Study(study_screen) => {
                 subscriptions.push(study_screen.subscription().map(Message::Study))
             }
+            Screen::NoteTypes(note_types_screen) => {
+                subscriptions.push(note_types_screen.subscription().map(Message::NoteTypes))
+            }
+            Screen::Trash(trash_screen) => {
+                subscriptions.push(trash_screen.subscription().map(Message::Trash))
+            }
+            Screen::Search(search_screen) => {
+                subscriptions.push(search_screen.subscription().map(Message::Search))
+            }
         };
 
         Subscription::batch(subscriptions)
@@ -330,6 +467,7 @@ impl cosmic::Application for AppModel {
                 Task::none()
             }
             Message::UpdateConfig(config) => {
+                self.clock.set_next_day_starts_at(config.next_day_starts_at);
                 self.config = config;
                 Task::none()
             }
@@ -353,6 +491,100 @@ impl cosmic::Application for AppModel {
                 }
                 Task::none()
             }
+            Message::UpdateNextDayStartsAt(index) => {
+                let hour = index.min(23) as u8;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_next_day_starts_at(handler, hour)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.next_day_starts_at = hour;
+                }
+
+                self.clock
+                    .set_next_day_starts_at(self.config.next_day_starts_at);
+                Task::none()
+            }
+            Message::UpdateAutoplayAudio(autoplay_audio) => {
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_autoplay_audio(handler, autoplay_audio)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.autoplay_audio = autoplay_audio;
+                }
+                Task::none()
+            }
+            Message::UpdateTrashRetentionDays(index) => {
+                let Some(days) = TRASH_RETENTION_DAYS.get(index).copied() else {
+                    return Task::none();
+                };
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_trash_retention_days(handler, days)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.trash_retention_days = days;
+                }
+                Task::none()
+            }
+            Message::UpdateDuplicateScope(index) => {
+                let scope = match index {
+                    1 => DuplicateScope::StudySet,
+                    _ => DuplicateScope::Folder,
+                };
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_duplicate_scope(handler, scope)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.duplicate_scope = scope;
+                }
+                Task::none()
+            }
+            Message::UpdateStudySetOrder(index) => {
+                let Some(order) = OrderMode::ALL.get(index).copied() else {
+                    return Task::none();
+                };
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_studyset_order(handler, order)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.studyset_order = order;
+                }
+                self.update(Message::FetchStudySets)
+            }
+            Message::UpdateFolderOrder(index) => {
+                let Some(order) = OrderMode::ALL.get(index).copied() else {
+                    return Task::none();
+                };
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(err) = self.config.set_folder_order(handler, order)
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.folder_order = order;
+                }
+
+                // The folders are reloaded with the new order if they are being shown
+                let showing_folders = matches!(
+                    &self.state,
+                    State::Ready {
+                        screen: Screen::Folders(_),
+                        ..
+                    }
+                );
+                match self.nav.active_data::<i32>().copied() {
+                    Some(set_id) if showing_folders => self.update(Message::OpenFolders(set_id)),
+                    _ => Task::none(),
+                }
+            }
             Message::LaunchUrl(url) => {
                 match open::that_detached(&url) {
                     Ok(()) => {}
@@ -362,6 +594,94 @@ impl cosmic::Application for AppModel {
                 }
                 Task::none()
             }
+            Message::NavMenuAction(action) => {
+                let State::Ready { database, .. } = &self.state else {
+                    return Task::none();
+                };
+
+                let (entity, offset) = match action {
+                    NavMenuAction::MoveUp(entity) => (entity, -1),
+                    NavMenuAction::MoveDown(entity) => (entity, 1),
+                };
+
+                let mut set_ids: Vec<i32> = self
+                    .nav
+                    .iter()
+                    .filter_map(|entity| self.nav.data::<i32>(entity).copied())
+                    .collect();
+                let Some(index) = self
+                    .nav
+                    .data::<i32>(entity)
+                    .and_then(|set_id| set_ids.iter().position(|id| id == set_id))
+                else {
+                    return Task::none();
+                };
+                let Some(new_index) = index
+                    .checked_add_signed(offset)
+                    .filter(|new_index| *new_index < set_ids.len())
+                else {
+                    return Task::none();
+                };
+                set_ids.swap(index, new_index);
+
+                Task::perform(
+                    StudySet::reorder(Arc::clone(database), set_ids),
+                    |res| match res {
+                        Ok(_) => cosmic::action::app(Message::FetchStudySets),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                        }
+                    },
+                )
+            }
+            Message::StudySetDropped(entity, dragged) => {
+                let State::Ready { database, .. } = &self.state else {
+                    return Task::none();
+                };
+                let Some(folders::DraggedStudySet(set_id)) = dragged else {
+                    return Task::none();
+                };
+                if self.config.studyset_order != OrderMode::Manual {
+                    return Task::none();
+                }
+
+                let mut set_ids: Vec<i32> = self
+                    .nav
+                    .iter()
+                    .filter_map(|entity| self.nav.data::<i32>(entity).copied())
+                    .collect();
+                let Some(target_id) = self.nav.data::<i32>(entity).copied() else {
+                    return Task::none();
+                };
+                if target_id == set_id {
+                    return Task::none();
+                }
+
+                // The dropped study set takes the place of the target
+                let old_index = set_ids.iter().position(|id| *id == set_id);
+                set_ids.retain(|id| *id != set_id);
+                let target_index = set_ids
+                    .iter()
+                    .position(|id| *id == target_id)
+                    .unwrap_or_default();
+                let new_index = match old_index {
+                    Some(index) if index <= target_index => target_index + 1,
+                    _ => target_index,
+                };
+                set_ids.insert(new_index, set_id);
+
+                Task::perform(
+                    StudySet::reorder(Arc::clone(database), set_ids),
+                    |res| match res {
+                        Ok(_) => cosmic::action::app(Message::FetchStudySets),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                        }
+                    },
+                )
+            }
             Message::MenuAction(action) => {
                 let State::Ready { .. } = &mut self.state else {
                     return Task::none();
@@ -440,6 +760,8 @@ impl cosmic::Application for AppModel {
                     MenuAction::Settings => {
                         self.update(Message::ToggleContextPage(ContextPage::Settings))
                     }
+                    MenuAction::NoteTypes => self.update(Message::OpenNoteTypes),
+                    MenuAction::Trash => self.update(Message::OpenTrash),
                 }
             }
             Message::Surface(a) => {
@@ -455,6 +777,7 @@ impl cosmic::Application for AppModel {
                     &self.dialog_state,
                     database,
                     &self.nav,
+                    &*self.clock,
                 )
             }
             Message::Key(modifiers, key) => {
@@ -471,14 +794,36 @@ impl cosmic::Application for AppModel {
             }
 
             Message::DatabaseLoaded(pool) => {
-                let (folders, _task) = screen::FoldersScreen::new(&Arc::clone(&pool), None);
+                let (folders, _task) = screen::FoldersScreen::new(
+                    &Arc::clone(&pool),
+                    None,
+                    Arc::clone(&self.clock),
+                    self.config.folder_order,
+                    self.config.studyset_order == OrderMode::Manual,
+                );
+
+                // Items that have been on the trash for too long are deleted for good
+                let retention_seconds = self.config.trash_retention_days as i64 * 86400;
+                let purge_task = Task::perform(
+                    TrashItem::purge_all(
+                        Arc::clone(&pool),
+                        Some(self.clock.now() - retention_seconds),
+                    ),
+                    |res| match res {
+                        Ok(folder_ids) => cosmic::action::app(Message::FoldersPurged(folder_ids)),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                        }
+                    },
+                );
 
                 self.state = State::Ready {
                     database: pool,
                     screen: Screen::Folders(folders),
                 };
 
-                self.update(Message::FetchStudySets)
+                Task::batch([purge_task, self.update(Message::FetchStudySets)])
             }
 
             Message::FetchStudySets => {
@@ -486,13 +831,20 @@ impl cosmic::Application for AppModel {
                     return Task::none();
                 };
 
-                Task::perform(StudySet::get_all(Arc::clone(database)), |r| {
-                    cosmic::action::app(Message::FetchedStudySets(r))
-                })
+                Task::perform(
+                    StudySet::get_all(
+                        Arc::clone(database),
+                        self.config.studyset_order,
+                        Arc::clone(&self.clock),
+                    ),
+                    |r| cosmic::action::app(Message::FetchedStudySets(r)),
+                )
             }
             Message::FetchedStudySets(result) => {
                 match result {
                     Ok(sets) => {
+                        let active_set_id = self.nav.active_data::<i32>().copied();
+
                         // Reset the navbar items
                         self.nav = nav_bar::Model::default();
 
@@ -505,7 +857,7 @@ impl cosmic::Application for AppModel {
                         }
 
                         // If there's no study sets on the navbar.
-                        let Some(entity) = self.nav.iter().next() else {
+                        let Some(first_entity) = self.nav.iter().next() else {
                             let State::Ready {
                                 screen, database, ..
                             } = &mut self.state
@@ -513,14 +865,27 @@ impl cosmic::Application for AppModel {
                                 return Task::none();
                             };
 
-                            let (folders, _task) =
-                                screen::FoldersScreen::new(&Arc::clone(database), None);
+                            let (folders, _task) = screen::FoldersScreen::new(
+                                &Arc::clone(database),
+                                None,
+                                Arc::clone(&self.clock),
+                                self.config.folder_order,
+                                self.config.studyset_order == OrderMode::Manual,
+                            );
                             *screen = Screen::Folders(folders);
 
                             return Task::none();
                         };
 
-                        // If there are any items on the navbar...
+                        // If there are any items on the navbar the previously selected study set is kept selected (if it still exists)
+                        let entity = self
+                            .nav
+                            .iter()
+                            .find(|entity| {
+                                active_set_id.is_some()
+                                    && self.nav.data::<i32>(*entity).copied() == active_set_id
+                            })
+                            .unwrap_or(first_entity);
                         self.nav.activate(entity);
                         self.on_nav_select(entity)
                     }
@@ -553,9 +918,11 @@ impl cosmic::Application for AppModel {
                     folders::Action::OpenCreateStudySetDialog => self.update(
                         Message::DialogAction(dialogs::DialogAction::OpenNewStudySetDialog),
                     ),
-                    folders::Action::OpenCreateFolderDialog => self.update(Message::DialogAction(
-                        dialogs::DialogAction::OpenCreateFolderDialog,
-                    )),
+                    folders::Action::OpenCreateFolderDialog(parent_id) => {
+                        self.update(Message::DialogAction(
+                            dialogs::DialogAction::OpenCreateFolderDialog(parent_id),
+                        ))
+                    }
                     folders::Action::OpenDeleteFolderDialog(folder_id) => {
                         self.update(Message::DialogAction(
                             dialogs::DialogAction::OpenDeleteFolderDialog(folder_id),
@@ -565,7 +932,7 @@ impl cosmic::Application for AppModel {
                         self.update(Message::ToggleContextPage(context_page))
                     }
                     folders::Action::OpenFolder(folder_id) => {
-                        self.update(Message::OpenFlashcards(folder_id))
+                        self.update(Message::OpenFlashcards(folder_id, None))
                     }
                 }
             }
@@ -577,7 +944,13 @@ impl cosmic::Application for AppModel {
                     return Task::none();
                 };
 
-                let (folders, task) = screen::FoldersScreen::new(database, Some(studyset_id));
+                let (folders, task) = screen::FoldersScreen::new(
+                    database,
+                    Some(studyset_id),
+                    Arc::clone(&self.clock),
+                    self.config.folder_order,
+                    self.config.studyset_order == OrderMode::Manual,
+                );
                 *screen = Screen::Folders(folders);
                 task.map(|msg| cosmic::action::app(Message::Folders(msg)))
             }
@@ -605,16 +978,47 @@ impl cosmic::Application for AppModel {
                             dialogs::DialogAction::OpenDeleteFlashcardDialog(flashcard),
                         ))
                     }
+                    flashcards::Action::OpenTransferFlashcardsDialog {
+                        flashcards,
+                        copy,
+                        folders,
+                    } => self.update(Message::DialogAction(
+                        dialogs::DialogAction::OpenTransferFlashcardsDialog {
+                            flashcards,
+                            copy,
+                            folders,
+                        },
+                    )),
+                    flashcards::Action::OpenBulkEditDialog(flashcard_ids, edit) => {
+                        self.update(Message::DialogAction(
+                            dialogs::DialogAction::OpenBulkEditDialog(flashcard_ids, edit),
+                        ))
+                    }
                     flashcards::Action::OpenContextPage(context_page) => {
                         self.update(Message::ToggleContextPage(context_page))
                     }
-                    flashcards::Action::StudyFolder(folder_id) => {
+                    flashcards::Action::SaveListOptions(folder_id, list_options) => {
+                        let mut all_list_options = self.config.flashcard_list_options.clone();
+                        all_list_options.insert(folder_id, list_options);
+
+                        if let Some(handler) = &self.config_handler
+                            && let Err(err) = self
+                                .config
+                                .set_flashcard_list_options(handler, all_list_options.clone())
+                        {
+                            eprintln!("{err}");
+                            // even if it fails we update the config (it won't get saved after restart)
+                            self.config.flashcard_list_options = all_list_options;
+                        }
+                        Task::none()
+                    }
+                    flashcards::Action::StudyFolder(folder_id, tag) => {
                         self.core.window.show_context = false;
-                        self.update(Message::OpenStudy(folder_id))
+                        self.update(Message::OpenStudy(folder_id, tag))
                     }
                 }
             }
-            Message::OpenFlashcards(folder_id) => {
+            Message::OpenFlashcards(folder_id, flashcard_id) => {
                 let State::Ready {
                     screen, database, ..
                 } = &mut self.state
@@ -622,7 +1026,18 @@ impl cosmic::Application for AppModel {
                     return Task::none();
                 };
 
-                let (flashcards, task) = screen::FlashcardsScreen::new(database, folder_id);
+                let (flashcards, task) = screen::FlashcardsScreen::new(
+                    database,
+                    folder_id,
+                    Arc::clone(&self.clock),
+                    self.config.duplicate_scope,
+                    self.config
+                        .flashcard_list_options
+                        .get(&folder_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    flashcard_id,
+                );
                 *screen = Screen::Flashcards(flashcards);
                 task.map(|msg| cosmic::action::app(Message::Flashcards(msg)))
             }
@@ -643,14 +1058,14 @@ impl cosmic::Application for AppModel {
                     study::Action::None => Task::none(),
                     study::Action::AddToast(toast) => self.update(Message::AddToast(toast)),
                     study::Action::Back(folder_id) => {
-                        self.update(Message::OpenFlashcards(folder_id))
+                        self.update(Message::OpenFlashcards(folder_id, None))
                     }
                     study::Action::Run(task) => {
                         task.map(|msg| cosmic::action::app(Message::Study(msg)))
                     }
                 }
             }
-            Message::OpenStudy(folder_id) => {
+            Message::OpenStudy(folder_id, tag) => {
                 let State::Ready {
                     screen, database, ..
                 } = &mut self.state
@@ -658,11 +1073,184 @@ impl cosmic::Application for AppModel {
                     return Task::none();
                 };
 
-                let (study, task) = screen::StudyScreen::new(database, folder_id);
+                let (study, task) = screen::StudyScreen::new(
+                    database,
+                    folder_id,
+                    tag,
+                    Arc::clone(&self.clock),
+                    self.config.autoplay_audio,
+                );
                 *screen = Screen::Study(study);
                 task.map(|msg| cosmic::action::app(Message::Study(msg)))
             }
 
+            Message::NoteTypes(message) => {
+                let State::Ready {
+                    screen, database, ..
+                } = &mut self.state
+                else {
+                    return Task::none();
+                };
+
+                let Screen::NoteTypes(note_types) = screen else {
+                    return Task::none();
+                };
+
+                match note_types.update(message, database) {
+                    note_types::Action::None => Task::none(),
+                    note_types::Action::AddToast(toast) => self.update(Message::AddToast(toast)),
+                    note_types::Action::Run(task) => {
+                        task.map(|msg| cosmic::action::app(Message::NoteTypes(msg)))
+                    }
+                    note_types::Action::OpenDeleteNoteTypeDialog(note_type_id) => {
+                        self.update(Message::DialogAction(
+                            dialogs::DialogAction::OpenDeleteNoteTypeDialog(note_type_id),
+                        ))
+                    }
+                    note_types::Action::OpenContextPage(context_page) => {
+                        self.update(Message::ToggleContextPage(context_page))
+                    }
+                    note_types::Action::Back => {
+                        self.core.window.show_context = false;
+                        self.on_nav_select(self.nav.active())
+                    }
+                }
+            }
+            Message::OpenNoteTypes => {
+                let State::Ready {
+                    screen, database, ..
+                } = &mut self.state
+                else {
+                    return Task::none();
+                };
+
+                self.core.window.show_context = false;
+
+                let (note_types, task) =
+                    screen::NoteTypesScreen::new(database, Arc::clone(&self.clock));
+                *screen = Screen::NoteTypes(note_types);
+                task.map(|msg| cosmic::action::app(Message::NoteTypes(msg)))
+            }
+
+            Message::Trash(message) => {
+                let State::Ready {
+                    screen, database, ..
+                } = &mut self.state
+                else {
+                    return Task::none();
+                };
+
+                let Screen::Trash(trash) = screen else {
+                    return Task::none();
+                };
+
+                match trash.update(message, database) {
+                    trash::Action::None => Task::none(),
+                    trash::Action::AddToast(toast) => self.update(Message::AddToast(toast)),
+                    trash::Action::Run(task) => {
+                        task.map(|msg| cosmic::action::app(Message::Trash(msg)))
+                    }
+                    trash::Action::OpenPurgeTrashItemDialog(item) => {
+                        self.update(Message::DialogAction(
+                            dialogs::DialogAction::OpenPurgeTrashItemDialog(item),
+                        ))
+                    }
+                    trash::Action::OpenEmptyTrashDialog => self.update(Message::DialogAction(
+                        dialogs::DialogAction::OpenEmptyTrashDialog,
+                    )),
+                    // Restored study sets need to be back on the nav bar
+                    trash::Action::Back => self.update(Message::FetchStudySets),
+                }
+            }
+            Message::FoldersPurged(folder_ids) => {
+                // The list options of the deleted folders are not needed anymore
+                let mut all_list_options = self.config.flashcard_list_options.clone();
+                all_list_options.retain(|folder_id, _| !folder_ids.contains(folder_id));
+
+                if all_list_options.len() != self.config.flashcard_list_options.len()
+                    && let Some(handler) = &self.config_handler
+                    && let Err(err) = self
+                        .config
+                        .set_flashcard_list_options(handler, all_list_options.clone())
+                {
+                    eprintln!("{err}");
+                    // even if it fails we update the config (it won't get saved after restart)
+                    self.config.flashcard_list_options = all_list_options;
+                }
+
+                // Only reloads the trash if it's open
+                self.update(Message::Trash(trash::Message::LoadTrash))
+            }
+            Message::Search(message) => {
+                let State::Ready { screen, .. } = &mut self.state else {
+                    return Task::none();
+                };
+
+                let Screen::Search(search) = screen else {
+                    return Task::none();
+                };
+
+                match search.update(message) {
+                    search::Action::None => Task::none(),
+                    search::Action::AddToast(toast) => self.update(Message::AddToast(toast)),
+                    search::Action::OpenFlashcard {
+                        studyset_id,
+                        folder_id,
+                        flashcard_id,
+                    } => {
+                        // The studyset of the flashcard becomes the selected one on the nav bar
+                        let entity = self
+                            .nav
+                            .iter()
+                            .find(|entity| self.nav.data::<i32>(*entity) == Some(&studyset_id));
+                        if let Some(entity) = entity {
+                            self.nav.activate(entity);
+                        }
+
+                        Task::batch([
+                            self.update_title(),
+                            self.update(Message::OpenFlashcards(folder_id, Some(flashcard_id))),
+                        ])
+                    }
+                }
+            }
+            Message::SearchQueryInput(query) => {
+                self.search_query = query;
+                Task::none()
+            }
+            Message::OpenSearch => {
+                let State::Ready {
+                    screen, database, ..
+                } = &mut self.state
+                else {
+                    return Task::none();
+                };
+
+                if self.search_query.trim().is_empty() {
+                    return Task::none();
+                }
+
+                self.core.window.show_context = false;
+
+                let (search, task) = screen::SearchScreen::new(database, self.search_query.clone());
+                *screen = Screen::Search(search);
+                task.map(|msg| cosmic::action::app(Message::Search(msg)))
+            }
+            Message::OpenTrash => {
+                let State::Ready {
+                    screen, database, ..
+                } = &mut self.state
+                else {
+                    return Task::none();
+                };
+
+                self.core.window.show_context = false;
+
+                let (trash, task) = screen::TrashScreen::new(database);
+                *screen = Screen::Trash(trash);
+                task.map(|msg| cosmic::action::app(Message::Trash(msg)))
+            }
+
             Message::ComleteBackup(file_path) => {
                 let State::Ready { database, .. } = &mut self.state else {
                     return Task::none();
@@ -756,6 +1344,74 @@ impl AppModel {
                     )),
                 )
                 .into(),
+            widget::settings::section()
+                .title(fl!("study"))
+                .add(
+                    widget::settings::item::builder(fl!("next-day-starts-at"))
+                        .description(fl!("next-day-starts-at-description"))
+                        .control(widget::dropdown(
+                            &self.day_start_hours,
+                            Some(self.config.next_day_starts_at as usize),
+                            Message::UpdateNextDayStartsAt,
+                        )),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("autoplay-audio"))
+                        .description(fl!("autoplay-audio-description"))
+                        .toggler(self.config.autoplay_audio, Message::UpdateAutoplayAudio),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("duplicate-scope"))
+                        .description(fl!("duplicate-scope-description"))
+                        .control(widget::dropdown(
+                            &self.duplicate_scopes,
+                            Some(match self.config.duplicate_scope {
+                                DuplicateScope::Folder => 0,
+                                DuplicateScope::StudySet => 1,
+                            }),
+                            Message::UpdateDuplicateScope,
+                        )),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("ordering"))
+                .add(
+                    widget::settings::item::builder(fl!("studyset-order"))
+                        .description(fl!("studyset-order-description"))
+                        .control(widget::dropdown(
+                            &self.order_modes,
+                            OrderMode::ALL
+                                .iter()
+                                .position(|order| *order == self.config.studyset_order),
+                            Message::UpdateStudySetOrder,
+                        )),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("folder-order"))
+                        .description(fl!("folder-order-description"))
+                        .control(widget::dropdown(
+                            &self.order_modes,
+                            OrderMode::ALL
+                                .iter()
+                                .position(|order| *order == self.config.folder_order),
+                            Message::UpdateFolderOrder,
+                        )),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("trash"))
+                .add(
+                    widget::settings::item::builder(fl!("trash-retention"))
+                        .description(fl!("trash-retention-description"))
+                        .control(widget::dropdown(
+                            &self.trash_retention_options,
+                            TRASH_RETENTION_DAYS
+                                .iter()
+                                .position(|days| *days == self.config.trash_retention_days),
+                            Message::UpdateTrashRetentionDays,
+                        )),
+                )
+                .into(),
         ])
         .into()
     }
@@ -6,7 +6,7 @@ use cosmic::{
     Core, Element,
     widget::{
         menu::{self, ItemHeight, ItemWidth, KeyBind},
-        responsive_menu_bar,
+        nav_bar, responsive_menu_bar,
     },
 };
 use std::{collections::HashMap, sync::LazyLock};
@@ -28,6 +28,10 @@ pub enum MenuAction {
     About,
     /// Open the Settings [`ContextPage`] of the application
     Settings,
+    /// Open the note types page of the application
+    NoteTypes,
+    /// Open the trash page of the application
+    Trash,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -42,10 +46,29 @@ impl menu::action::MenuAction for MenuAction {
             MenuAction::RenameStudySet => Message::MenuAction(MenuAction::RenameStudySet),
             MenuAction::DeleteStudySet => Message::MenuAction(MenuAction::DeleteStudySet),
             MenuAction::Settings => Message::MenuAction(MenuAction::Settings),
+            MenuAction::NoteTypes => Message::MenuAction(MenuAction::NoteTypes),
+            MenuAction::Trash => Message::MenuAction(MenuAction::Trash),
         }
     }
 }
 
+/// Represents a Action that executes after clicking on the context menu of a [`StudySet`] of the nav bar
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NavMenuAction {
+    /// Move the [`StudySet`] one place up on the nav bar
+    MoveUp(nav_bar::Id),
+    /// Move the [`StudySet`] one place down on the nav bar
+    MoveDown(nav_bar::Id),
+}
+
+impl menu::action::MenuAction for NavMenuAction {
+    type Message = cosmic::Action<crate::app::Message>;
+
+    fn message(&self) -> Self::Message {
+        cosmic::Action::App(Message::NavMenuAction(*self))
+    }
+}
+
 //
 // Responsive Menu Bar implementation based on cosmic-edit implementation (04/02/2026)
 // Relevant links:
@@ -87,6 +110,8 @@ pub fn menu_bar<'a>(core: &Core, key_binds: &HashMap<KeyBind, MenuAction>) -> El
                     vec![
                         MenuItem::Button(fl!("about"), None, MenuAction::About),
                         MenuItem::Button(fl!("settings"), None, MenuAction::Settings),
+                        MenuItem::Button(fl!("note-types"), None, MenuAction::NoteTypes),
+                        MenuItem::Button(fl!("trash"), None, MenuAction::Trash),
                     ],
                 ),
             ],
@@ -21,6 +21,8 @@ pub enum ContextPage {
     AddEditFlashcard,
     /// FolderContent/Flashards Settings [`ContextPage`] of the application
     FolderContentOptions,
+    /// Upsert Note Type [`ContextPage`] of the application
+    EditNoteType,
 }
 
 impl ContextPage {
@@ -94,6 +96,23 @@ impl ContextPage {
                 )
                 .title(fl!("flashcard-options"))
             }
+            ContextPage::EditNoteType => {
+                let crate::app::State::Ready { screen, .. } = &app_model.state else {
+                    return None;
+                };
+
+                let crate::app::Screen::NoteTypes(note_types_screen) = &screen else {
+                    return None;
+                };
+
+                context_drawer::context_drawer(
+                    note_types_screen
+                        .edit_contextpage(spacing)
+                        .map(Message::NoteTypes),
+                    Message::ToggleContextPage(ContextPage::EditNoteType),
+                )
+                .title(fl!("note-type-details"))
+            }
         })
     }
 }
@@ -33,6 +33,10 @@ pub async fn init_database(app_id: &str) -> Arc<Pool<Sqlite>> {
         }
     };
 
+    if let Err(e) = upgrade_flashcard_fields(&pool).await {
+        eprintln!("Error upgrading flashcard fields: {e}");
+    }
+
     let old_db_path = dirs::data_dir()
         .unwrap()
         .join(app_id)
@@ -49,6 +53,53 @@ pub async fn init_database(app_id: &str) -> Arc<Pool<Sqlite>> {
     Arc::new(pool)
 }
 
+/// Upgrades the flashcard fields saved as ron (before the json field schema) to versioned json,
+/// values that can't be read are left untouched (they will show up as unsupported fields)
+async fn upgrade_flashcard_fields(pool: &Pool<Sqlite>) -> Result<(), anywho::Error> {
+    use sqlx::Row;
+
+    let legacy_flashcards = sqlx::query(
+        "SELECT id, front, back FROM flashcards WHERE front NOT LIKE '{%' OR back NOT LIKE '{%'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if legacy_flashcards.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "Upgrading {} flashcards to the current field schema",
+        legacy_flashcards.len()
+    );
+
+    let mut transaction = pool.begin().await?;
+
+    for row in legacy_flashcards {
+        let id: i32 = row.try_get("id")?;
+        let front: String = row.try_get("front")?;
+        let back: String = row.try_get("back")?;
+
+        // Values that can't be read are kept as unsupported, so they are not scanned on every start
+        let upgrade = |value: String| {
+            FlashcardField::from_legacy_ron(&value)
+                .unwrap_or(FlashcardField::Unsupported(value))
+                .to_json()
+        };
+
+        sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
+            .bind(upgrade(front)?)
+            .bind(upgrade(back)?)
+            .bind(id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
 /// Imports the content from the old database to the new one and deletes the old database
 async fn import_old_database_data(
     pool: &Pool<Sqlite>,
@@ -145,8 +196,8 @@ async fn import_old_database_data(
              (front, back, status, fsrs_state, due_date, last_reviewed, folder_id)
              VALUES (?, ?, ?, NULL, NULL, NULL, ?)",
         )
-        .bind(FlashcardField::Text(front).to_ron()?)
-        .bind(FlashcardField::Text(back).to_ron()?)
+        .bind(FlashcardField::Text(front).to_json()?)
+        .bind(FlashcardField::Text(back).to_json()?)
         .bind(FlashcardStatus::None.to_id())
         .bind(new_folder_id)
         .execute(pool)
@@ -1,5 +1,14 @@
 // SPDX-License-Identifier: GPL-3.0
 
+pub mod bulk;
+pub mod duplicates;
 pub mod flashcard;
 pub mod folder;
+pub mod list_options;
+pub mod note;
+pub mod ordering;
+pub mod revision;
+pub mod revlog;
+pub mod search;
 pub mod studyset;
+pub mod trash;
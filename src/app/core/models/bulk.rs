@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+
+use crate::app::core::models::flashcard::{FlashcardStatus, parse_tags};
+
+/// An edit applied to several [`Flashcard`]s at once
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BulkEdit {
+    /// Move them to the trash
+    Delete,
+    /// Reset their status and FSRS data
+    ResetStatus,
+    /// Suspend (or unsuspend) them
+    Suspend(bool),
+    /// Add the given tags (separated by spaces) to them
+    AddTags(String),
+}
+
+impl BulkEdit {
+    /// Applies the [`BulkEdit`] to the flashcards with the given ids in a single transaction,
+    /// now (seconds since epoch) is used as the deletion time
+    pub async fn apply(
+        self,
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_ids: Vec<i32>,
+        now: i64,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        match self {
+            BulkEdit::Delete => {
+                for flashcard_id in &flashcard_ids {
+                    sqlx::query("UPDATE flashcards SET deleted_at = $1 WHERE id = $2")
+                        .bind(now)
+                        .bind(flashcard_id)
+                        .execute(&mut *transaction)
+                        .await?;
+                }
+            }
+            BulkEdit::ResetStatus => {
+                for flashcard_id in &flashcard_ids {
+                    sqlx::query("UPDATE flashcards SET status = $1, fsrs_state = NULL, due_date = NULL, last_reviewed = NULL, learning_step = NULL, relearning = 0 WHERE id = $2")
+                        .bind(FlashcardStatus::None.to_id())
+                        .bind(flashcard_id)
+                        .execute(&mut *transaction)
+                        .await?;
+                }
+            }
+            BulkEdit::Suspend(suspended) => {
+                for flashcard_id in &flashcard_ids {
+                    sqlx::query("UPDATE flashcards SET suspended = $1 WHERE id = $2")
+                        .bind(suspended)
+                        .bind(flashcard_id)
+                        .execute(&mut *transaction)
+                        .await?;
+                }
+            }
+            BulkEdit::AddTags(tags) => {
+                for tag in parse_tags(&tags) {
+                    sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT(name) DO NOTHING")
+                        .bind(&tag)
+                        .execute(&mut *transaction)
+                        .await?;
+
+                    for flashcard_id in &flashcard_ids {
+                        sqlx::query(
+                            "INSERT OR IGNORE INTO flashcard_tags (flashcard_id, tag_id)
+                             SELECT ?, id FROM tags WHERE name = ?",
+                        )
+                        .bind(flashcard_id)
+                        .bind(&tag)
+                        .execute(&mut *transaction)
+                        .await?;
+                    }
+                }
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
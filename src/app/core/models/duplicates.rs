@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::{collections::HashMap, sync::Arc};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::app::core::models::{
+    flashcard::{Flashcard, FlashcardField, TAGS_SUBQUERY, parse_tags},
+    note::{Note, NoteType},
+    revision::FlashcardRevision,
+};
+
+/// Where flashcards with the same front are considered duplicates
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DuplicateScope {
+    /// Only on the same [`Folder`] (not including subfolders)
+    #[default]
+    Folder,
+    /// On any [`Folder`] of the same [`StudySet`]
+    StudySet,
+}
+
+/// What to do with an imported [`Flashcard`] whose front already exists
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateAction {
+    /// Don't import it
+    #[default]
+    Skip,
+    /// Replace the front and back of the existing flashcard (keeping it's progress) and add the new tags
+    Update,
+    /// Import it anyway
+    KeepBoth,
+}
+
+impl DuplicateAction {
+    pub const ALL: [Self; 3] = [Self::Skip, Self::Update, Self::KeepBoth];
+}
+
+/// How many flashcards an import has added, updated and skipped
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Normalizes a text so it can be compared with others, ignoring case, whitespace and unicode representation
+pub fn normalize(text: &str) -> String {
+    text.nfkc()
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Normalized front of the given [`Flashcard`], [`None`] if it has no text to compare (ej: an image without alt text)
+pub fn normalized_front(flashcard: &Flashcard) -> Option<String> {
+    let front = normalize(&flashcard.front.plain_text());
+    (!front.is_empty()).then_some(front)
+}
+
+/// Get the id and normalized front of every [`Flashcard`] in the given scope of the given [`Folder`]
+pub async fn get_normalized_fronts(
+    pool: Arc<Pool<Sqlite>>,
+    folder_id: i32,
+    scope: DuplicateScope,
+) -> Result<Vec<(i32, String)>, anywho::Error> {
+    let mut connection = pool.acquire().await?;
+    fetch_normalized_fronts(&mut *connection, folder_id, scope).await
+}
+
+// Helper function to get the normalized fronts using the given connection (so it can be part of a transaction)
+async fn fetch_normalized_fronts(
+    connection: &mut SqliteConnection,
+    folder_id: i32,
+    scope: DuplicateScope,
+) -> Result<Vec<(i32, String)>, anywho::Error> {
+    let query = match scope {
+        DuplicateScope::Folder => {
+            "SELECT fc.id, fc.front FROM flashcards fc
+             WHERE fc.folder_id = $1 AND fc.deleted_at IS NULL
+             ORDER BY fc.id ASC"
+        }
+        DuplicateScope::StudySet => {
+            "SELECT fc.id, fc.front FROM flashcards fc
+             INNER JOIN folders f ON f.id = fc.folder_id
+             WHERE f.studyset_id = (SELECT studyset_id FROM folders WHERE id = $1)
+                AND f.deleted_at IS NULL AND fc.deleted_at IS NULL
+             ORDER BY fc.id ASC"
+        }
+    };
+
+    let rows = sqlx::query(query)
+        .bind(folder_id)
+        .fetch_all(&mut *connection)
+        .await?;
+
+    let mut result = Vec::<(i32, String)>::new();
+
+    for row in rows {
+        let front = FlashcardField::from_json(&row.try_get::<String, _>("front")?);
+        let front = normalize(&front.plain_text());
+        if !front.is_empty() {
+            result.push((row.try_get("id")?, front));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Adds imported flashcards looking for duplicates (of what is already on the database and of the ones imported before)
+pub struct DuplicateChecker {
+    scope: DuplicateScope,
+    action: DuplicateAction,
+    /// Seconds since epoch, for the revisions of the updated flashcards
+    imported_at: i64,
+    /// Normalized front → flashcard id, by folder (or by study set, with the [`None`] key)
+    fronts: HashMap<Option<i32>, HashMap<String, i32>>,
+    pub summary: ImportSummary,
+}
+
+impl DuplicateChecker {
+    pub fn new(scope: DuplicateScope, action: DuplicateAction, imported_at: i64) -> Self {
+        Self {
+            scope,
+            action,
+            imported_at,
+            fronts: HashMap::new(),
+            summary: ImportSummary::default(),
+        }
+    }
+
+    /// Adds the given [`Flashcard`] to the given [`Folder`] unless it's a duplicate (then the [`DuplicateAction`] is applied),
+    /// uses the given connection (so it can be part of a transaction)
+    pub async fn import(
+        &mut self,
+        connection: &mut SqliteConnection,
+        flashcard: &Flashcard,
+        folder_id: i32,
+    ) -> Result<(), anywho::Error> {
+        self.import_with(connection, &mut flashcard.clone(), folder_id, |_| Ok(()))
+            .await
+    }
+
+    /// Like [`DuplicateChecker::import`], but the given function is called right before the [`Flashcard`] is added or updated
+    /// (ej: to copy it's files), it's not called if the flashcard is skipped
+    pub async fn import_with(
+        &mut self,
+        connection: &mut SqliteConnection,
+        flashcard: &mut Flashcard,
+        folder_id: i32,
+        prepare: impl FnOnce(&mut Flashcard) -> Result<(), anywho::Error>,
+    ) -> Result<(), anywho::Error> {
+        let key = match self.scope {
+            DuplicateScope::Folder => Some(folder_id),
+            DuplicateScope::StudySet => None,
+        };
+
+        // The fronts of each scope are only loaded once
+        if !self.fronts.contains_key(&key) {
+            let mut fronts = HashMap::new();
+            for (id, front) in
+                fetch_normalized_fronts(&mut *connection, folder_id, self.scope).await?
+            {
+                fronts.entry(front).or_insert(id);
+            }
+            self.fronts.insert(key, fronts);
+        }
+        let fronts = self.fronts.entry(key).or_default();
+
+        let Some(front) = normalized_front(flashcard) else {
+            prepare(flashcard)?;
+            Flashcard::insert(&mut *connection, flashcard, folder_id).await?;
+            self.summary.added += 1;
+            return Ok(());
+        };
+
+        match (fronts.get(&front).copied(), self.action) {
+            (Some(_), DuplicateAction::Skip) => self.summary.skipped += 1,
+            (Some(existing_id), DuplicateAction::Update) => {
+                if self
+                    .update(&mut *connection, existing_id, flashcard, prepare)
+                    .await?
+                {
+                    self.summary.updated += 1;
+                } else {
+                    self.summary.skipped += 1;
+                }
+            }
+            (_, _) => {
+                prepare(flashcard)?;
+                let id = Flashcard::insert(&mut *connection, flashcard, folder_id).await?;
+                fronts.entry(front).or_insert(id);
+                self.summary.added += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Helper function to update an existing flashcard with the front, back and tags of an imported one. Flashcards generated by a
+    // [`Note`] are updated through it, so the next sync of the note keeps the changes. Returns false if it can't be updated
+    async fn update(
+        &self,
+        connection: &mut SqliteConnection,
+        existing_id: i32,
+        flashcard: &mut Flashcard,
+        prepare: impl FnOnce(&mut Flashcard) -> Result<(), anywho::Error>,
+    ) -> Result<bool, anywho::Error> {
+        let row = sqlx::query(&format!(
+            "SELECT fc.note_id, fc.template_ord, {TAGS_SUBQUERY} AS tags FROM flashcards fc WHERE fc.id = $1"
+        ))
+        .bind(existing_id)
+        .fetch_one(&mut *connection)
+        .await?;
+        let note_id: Option<i32> = row.try_get("note_id").ok();
+        let template_ord: Option<u32> = row.try_get("template_ord").ok();
+        let existing_tags: Option<String> = row.try_get("tags").ok();
+
+        let mut tags = parse_tags(&existing_tags.unwrap_or_default());
+        for tag in &flashcard.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        let note = match (note_id, template_ord) {
+            (Some(note_id), Some(template_ord)) => {
+                // Notes only generate text flashcards
+                let (FlashcardField::Text(front), FlashcardField::Text(back)) =
+                    (&flashcard.front, &flashcard.back)
+                else {
+                    return Ok(false);
+                };
+
+                let mut note = Note::fetch(&mut *connection, note_id).await?;
+                let note_type = NoteType::fetch(&mut *connection, note.note_type_id).await?;
+                let Some(template) = note_type.templates.iter().find(|t| t.ord == template_ord)
+                else {
+                    return Ok(false);
+                };
+
+                if !note.set_rendered(&note_type, template, front, back) {
+                    eprintln!(
+                        "Flashcard {existing_id} can't be updated, the template of it's note is not made of single fields"
+                    );
+                    return Ok(false);
+                }
+
+                Some(note)
+            }
+            _ => None,
+        };
+
+        prepare(flashcard)?;
+
+        // Updating the note saves the revisions of it's flashcards
+        match note {
+            Some(note) => {
+                Note::update(&mut *connection, &note, Some(&tags), self.imported_at, None).await?
+            }
+            None => {
+                let new_front = flashcard.front.to_json()?;
+                let new_back = flashcard.back.to_json()?;
+
+                FlashcardRevision::save_if_changed(
+                    &mut *connection,
+                    existing_id,
+                    &new_front,
+                    &new_back,
+                    self.imported_at,
+                    None,
+                )
+                .await?;
+
+                sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
+                    .bind(&new_front)
+                    .bind(&new_back)
+                    .bind(existing_id)
+                    .execute(&mut *connection)
+                    .await?;
+
+                Flashcard::set_tags(&mut *connection, existing_id, &tags).await?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_uses_the_compatibility_form() {
+        // Full width letters, ligatures and decomposed accents
+        assert_eq!(normalize("ｆｕｌｌ"), "full");
+        assert_eq!(normalize("ﬁne"), "fine");
+        assert_eq!(normalize("cafe\u{301}"), normalize("caf\u{e9}"));
+    }
+
+    #[test]
+    fn normalize_ignores_case() {
+        assert_eq!(normalize("HeLLo WoRLD"), "hello world");
+        assert_eq!(normalize("ÉCOLE"), "école");
+    }
+
+    #[test]
+    fn normalize_collapses_whitespace() {
+        assert_eq!(normalize("  a \t b\n\n c  "), "a b c");
+        // No-break and ideographic spaces become regular spaces
+        assert_eq!(normalize("a\u{a0}b\u{3000}c"), "a b c");
+        assert_eq!(normalize(" \n\t "), "");
+    }
+}
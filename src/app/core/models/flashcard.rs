@@ -1,11 +1,34 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use crate::{app::core::utils::current_day, fl};
+use crate::{
+    app::core::{
+        models::{
+            duplicates::{DuplicateAction, DuplicateChecker, DuplicateScope, ImportSummary},
+            folder::{Folder, FolderCounts},
+            revision::FlashcardRevision,
+            revlog::RevlogEntry,
+        },
+        utils::{self, Clock, ScheduledReview, cloze, math},
+    },
+    fl,
+};
 use cosmic::iced::Color;
 use futures::stream::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite};
-use std::sync::Arc;
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, sqlite::SqliteRow};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// How far ahead (in seconds) a flashcard on a learning step can be studied before it's due
+const LEARN_AHEAD_SECONDS: i64 = 20 * 60;
+
+/// Selects the tags of a flashcard (aliased as fc) separated by spaces, tags can't contain whitespace
+pub const TAGS_SUBQUERY: &str = "(SELECT group_concat(t.name, ' ') FROM flashcard_tags ft INNER JOIN tags t ON t.id = ft.tag_id WHERE ft.flashcard_id = fc.id)";
+
+/// Tag given to the flashcards that keep getting forgotten (see [`Folder::leech_threshold`])
+pub const LEECH_TAG: &str = "leech";
+
+/// Columns needed to build a [`Flashcard`] from a row of the flashcards table (aliased as fc), needs the [`TAGS_SUBQUERY`] as tags too
+const FLASHCARD_COLUMNS: &str = "fc.id, fc.front, fc.back, fc.status, fc.fsrs_state, fc.due_date, fc.last_reviewed, fc.learning_step, fc.relearning, fc.note_id, fc.template_ord, fc.suspended, fc.buried_until, fc.lapses";
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Flashcard {
@@ -16,8 +39,31 @@ pub struct Flashcard {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fsrs_state: Option<SerializableMemoryState>,
-    pub due_date: Option<i32>,      // Days since epoch
-    pub last_reviewed: Option<i32>, // Days since epoch
+    pub due_date: Option<i64>,      // Seconds since epoch
+    pub last_reviewed: Option<i64>, // Seconds since epoch
+    /// Current (re)learning step, [`None`] if the flashcard is new or in review
+    #[serde(default)]
+    pub learning_step: Option<u32>,
+    /// True if the current step belongs to the relearning steps
+    #[serde(default)]
+    pub relearning: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// [`Note`] this flashcard was generated from, [`None`] for plain flashcards
+    #[serde(default)]
+    pub note_id: Option<i32>,
+    /// Ord of the template of the [`Note`] that generated this flashcard
+    #[serde(default)]
+    pub template_ord: Option<u32>,
+    /// Suspended flashcards are never studied until they are unsuspended
+    #[serde(default)]
+    pub suspended: bool,
+    /// Study day (days since epoch) a buried flashcard comes back on
+    #[serde(default)]
+    pub buried_until: Option<i32>,
+    /// Times the flashcard has been forgotten after being learned
+    #[serde(default)]
+    pub lapses: u32,
 }
 
 impl PartialEq for Flashcard {
@@ -29,7 +75,7 @@ impl PartialEq for Flashcard {
 impl Eq for Flashcard {}
 
 /// The different Status a [`Flashcard`] can have
-#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FlashcardStatus {
     #[default]
     None,
@@ -52,6 +98,8 @@ impl std::fmt::Display for FlashcardStatus {
 }
 
 impl FlashcardStatus {
+    pub const ALL: [Self; 5] = [Self::None, Self::Bad, Self::Ok, Self::Great, Self::Easy];
+
     /// Convert the [`FlashcardStatus`] to it's appropiate id
     pub fn to_id(self) -> i32 {
         match self {
@@ -138,12 +186,47 @@ impl FlashcardStatus {
     }
 }
 
+/// Version of the JSON schema used to save a [`FlashcardField`] on the database.
+/// Bump it (and upgrade the old versions on [`FlashcardField::from_json`]) if a field changes in an incompatible way
+pub const FIELD_SCHEMA_VERSION: u32 = 1;
+
 /// Represents the different field types the flashcard can have in either it's front or it's back
-/// get's serialized into ron on the database
+/// get's serialized into versioned json on the database (see [`FIELD_SCHEMA_VERSION`])
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FlashcardField {
     Text(String),
-    Image { path: String, alt_text: String },
+    Image {
+        path: String,
+        alt_text: String,
+    },
+    /// Audio file (saved on the Oboete data directory) with an optional transcript
+    Audio {
+        path: String,
+        transcript: String,
+    },
+    /// Cloze deletion text (`{{c1::answer::hint}}`), only for the front.
+    /// The flashcard reviews the deletions with the given index, adding one generates a flashcard per index
+    Cloze {
+        text: String,
+        index: u32,
+    },
+    /// Source code shown in a monospace font with syntax highlighting for the given language
+    Code {
+        language: String,
+        source: String,
+    },
+    /// Several fields shown one after another (top to bottom), see [`FlashcardField::BLOCK_TYPES`]
+    Blocks(Vec<FlashcardField>),
+    /// A field this version of Oboete can't read (saved by a newer version or corrupted),
+    /// it keeps the raw database value so it's never lost
+    Unsupported(String),
+}
+
+/// Envelope of a [`FlashcardField`] saved on the database
+#[derive(Serialize, Deserialize)]
+struct StoredField<T> {
+    version: u32,
+    field: T,
 }
 
 impl Default for FlashcardField {
@@ -160,6 +243,11 @@ impl std::fmt::Display for FlashcardField {
                 path: _path,
                 alt_text: _alt_text,
             } => write!(f, "Image"),
+            FlashcardField::Audio { .. } => write!(f, "Audio"),
+            FlashcardField::Cloze { .. } => write!(f, "Cloze"),
+            FlashcardField::Code { .. } => write!(f, "Code"),
+            FlashcardField::Blocks(_) => write!(f, "Blocks"),
+            FlashcardField::Unsupported(_) => write!(f, "Unsupported"),
         }
     }
 }
@@ -171,18 +259,130 @@ impl FlashcardField {
             path: String::new(),
             alt_text: String::new(),
         },
+        Self::Audio {
+            path: String::new(),
+            transcript: String::new(),
+        },
+        Self::Cloze {
+            text: String::new(),
+            index: 1,
+        },
+        Self::Code {
+            language: String::new(),
+            source: String::new(),
+        },
+        Self::Blocks(Vec::new()),
     ];
 
-    /// Get the serialized ron of the [`FlashcardField`]
-    // TODO: Maybe saving RON on the DB is not the best practice, because we will have to be carefull If we ever want to
-    // modify the FlashcardField Enum
-    pub fn to_ron(&self) -> Result<String, anywho::Error> {
-        Ok(ron::to_string(&self)?)
+    /// Field types the back can have (cloze deletions are only for the front)
+    pub const ALL_BACK: &'static [Self] = &[
+        Self::Text(String::new()),
+        Self::Image {
+            path: String::new(),
+            alt_text: String::new(),
+        },
+        Self::Audio {
+            path: String::new(),
+            transcript: String::new(),
+        },
+        Self::Code {
+            language: String::new(),
+            source: String::new(),
+        },
+        Self::Blocks(Vec::new()),
+    ];
+
+    /// Field types a block of [`FlashcardField::Blocks`] can have
+    pub const BLOCK_TYPES: &'static [Self] = &[
+        Self::Text(String::new()),
+        Self::Image {
+            path: String::new(),
+            alt_text: String::new(),
+        },
+        Self::Audio {
+            path: String::new(),
+            transcript: String::new(),
+        },
+    ];
+
+    /// Get the versioned json of the [`FlashcardField`] to save it on the database
+    pub fn to_json(&self) -> Result<String, anywho::Error> {
+        match self {
+            // Unsupported fields are saved back exactly as they were read, unless they are not json
+            // (unreadable legacy values), those go inside the envelope so they are never upgraded again
+            FlashcardField::Unsupported(raw) if raw.starts_with('{') => Ok(raw.clone()),
+            field => Ok(serde_json::to_string(&StoredField {
+                version: FIELD_SCHEMA_VERSION,
+                field,
+            })?),
+        }
+    }
+
+    /// Get the [`FlashcardField`] from the database value, never fails, unreadable values become [`FlashcardField::Unsupported`]
+    pub fn from_json(raw: &str) -> Self {
+        match serde_json::from_str::<StoredField<serde_json::Value>>(raw) {
+            Ok(stored) if stored.version <= FIELD_SCHEMA_VERSION => {
+                serde_json::from_value(stored.field)
+                    .unwrap_or_else(|_| Self::Unsupported(raw.to_string()))
+            }
+            Ok(_) => Self::Unsupported(raw.to_string()),
+            // Values saved before the json schema were ron
+            Err(_) => {
+                Self::from_legacy_ron(raw).unwrap_or_else(|| Self::Unsupported(raw.to_string()))
+            }
+        }
     }
 
-    /// Get the [`FlashcardField`] from a ron string
-    pub fn from_ron(ron: String) -> Result<Self, anywho::Error> {
-        Ok(ron::from_str(&ron)?)
+    /// Get the [`FlashcardField`] from the ron string fields were saved as before [`FIELD_SCHEMA_VERSION`] 1
+    pub fn from_legacy_ron(ron: &str) -> Option<Self> {
+        ron::from_str(ron).ok()
+    }
+
+    /// Text that represents the [`FlashcardField`] on plain text places (lists, exports...)
+    pub fn plain_text(&self) -> Cow<'_, str> {
+        match self {
+            FlashcardField::Text(t) => Cow::Borrowed(t),
+            FlashcardField::Image { alt_text, .. } => Cow::Borrowed(alt_text),
+            FlashcardField::Audio { transcript, .. } => Cow::Borrowed(transcript),
+            FlashcardField::Cloze { text, index } => Cow::Owned(cloze::front_text(text, *index)),
+            FlashcardField::Code { source, .. } => Cow::Borrowed(source),
+            FlashcardField::Blocks(blocks) => Cow::Owned(
+                blocks
+                    .iter()
+                    .map(|block| block.plain_text())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            FlashcardField::Unsupported(_) => Cow::Borrowed(""),
+        }
+    }
+
+    /// Fields the [`FlashcardField`] is made of, every block of [`FlashcardField::Blocks`] or itself for the rest
+    pub fn blocks(&self) -> &[FlashcardField] {
+        match self {
+            FlashcardField::Blocks(blocks) => blocks,
+            field => std::slice::from_ref(field),
+        }
+    }
+
+    /// Mutable version of [`FlashcardField::blocks`]
+    pub fn blocks_mut(&mut self) -> &mut [FlashcardField] {
+        match self {
+            FlashcardField::Blocks(blocks) => blocks,
+            field => std::slice::from_mut(field),
+        }
+    }
+
+    /// Math formulas (latex, display) of the [`FlashcardField`], only text fields render math
+    pub fn math_formulas(&self) -> Vec<(String, bool)> {
+        match self {
+            FlashcardField::Text(t) => math::math_formulas(t),
+            FlashcardField::Blocks(blocks) => {
+                blocks.iter().flat_map(|b| b.math_formulas()).collect()
+            }
+            _ => Vec::new(),
+        }
     }
 
     /// Returns true if [`FlashcardField`] is ready for database submission
@@ -190,6 +390,13 @@ impl FlashcardField {
         match self {
             FlashcardField::Text(t) => !t.is_empty(),
             FlashcardField::Image { path, alt_text } => !path.is_empty() && !alt_text.is_empty(),
+            FlashcardField::Audio { path, .. } => !path.is_empty(),
+            FlashcardField::Cloze { text, .. } => !cloze::indexes(text).is_empty(),
+            FlashcardField::Code { source, .. } => !source.is_empty(),
+            FlashcardField::Blocks(blocks) => {
+                !blocks.is_empty() && blocks.iter().all(FlashcardField::is_valid)
+            }
+            FlashcardField::Unsupported(_) => false,
         }
     }
 }
@@ -222,107 +429,224 @@ impl From<SerializableMemoryState> for fsrs::MemoryState {
 impl Flashcard {
     /// Returns true if the flashcard is ready for db submission
     pub fn is_valid(&self) -> bool {
-        self.front.is_valid() && self.back.is_valid()
+        match &self.front {
+            // The back of a cloze flashcard is optional extra text
+            FlashcardField::Cloze { .. } => {
+                self.front.is_valid() && matches!(self.back, FlashcardField::Text(_))
+            }
+            _ => self.front.is_valid() && self.back.is_valid(),
+        }
+    }
+
+    /// Returns the flashcards that should be generated for this one, one per cloze index (if it's a cloze)
+    pub fn expand_cloze(&self) -> Vec<Flashcard> {
+        match &self.front {
+            FlashcardField::Cloze { text, .. } => cloze::indexes(text)
+                .into_iter()
+                .map(|index| Flashcard {
+                    front: FlashcardField::Cloze {
+                        text: text.clone(),
+                        index,
+                    },
+                    ..self.clone()
+                })
+                .collect(),
+            _ => vec![self.clone()],
+        }
     }
 
-    // Check if card is due for review
-    pub fn is_due(&self) -> bool {
+    /// Returns true if the flashcard has the given tag (ignoring case)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Returns true if the flashcard has been tagged as a leech
+    pub fn is_leech(&self) -> bool {
+        self.has_tag(LEECH_TAG)
+    }
+
+    /// Returns true if the flashcard has been buried and it's not the day it comes back yet
+    pub fn is_buried(&self, clock: &dyn Clock) -> bool {
+        self.buried_until.is_some_and(|day| day > clock.today())
+    }
+
+    // Check if card is due for review (due_condition does the same check on SQL, keep both in sync)
+    pub fn is_due(&self, clock: &dyn Clock) -> bool {
+        // Suspended and buried cards are never due
+        if self.suspended || self.is_buried(clock) {
+            return false;
+        }
+
         match self.due_date {
-            Some(due) => due <= current_day(),
+            // Cards on a (re)learning step are due by the second (allowing to study a bit ahead),
+            // cards in review are due for the whole day
+            Some(due) if self.learning_step.is_some() => due <= clock.now() + LEARN_AHEAD_SECONDS,
+            Some(due) => clock.day_of(due) <= clock.today(),
             // None => true, // New cards are always "due"
             // Only never-reviewed cards (no FSRS state) are due
             None => self.fsrs_state.is_none(),
         }
     }
 
-    /// Get all flashcards of the given [`Folder`] from the database, also returns the desired retention rate for the folder of this flashcards
+    /// Get all flashcards of the given [`Folder`] from the database
     pub async fn get_all(
         pool: Arc<Pool<Sqlite>>,
         folder_id: i32,
     ) -> Result<Vec<Flashcard>, anywho::Error> {
-        let mut rows = sqlx::query(
-            "SELECT id, front, back, status, fsrs_state, due_date, last_reviewed 
-             FROM flashcards 
-             WHERE folder_id = $1 
-             ORDER BY id ASC",
-        )
+        let mut rows = sqlx::query(&format!(
+            "SELECT {FLASHCARD_COLUMNS}, {TAGS_SUBQUERY} AS tags 
+             FROM flashcards fc 
+             WHERE fc.folder_id = $1 AND fc.deleted_at IS NULL 
+             ORDER BY fc.id ASC"
+        ))
         .bind(folder_id)
         .fetch(pool.as_ref());
 
         let mut result = Vec::<Flashcard>::new();
 
         while let Some(row) = rows.try_next().await? {
-            let id: i32 = row.try_get("id")?;
-            let front: String = row.try_get("front")?;
-            let back: String = row.try_get("back")?;
-            let status: i32 = row.try_get("status")?;
-
-            let fsrs_state: Option<String> = row.try_get("fsrs_state").ok();
-            let due_date: Option<i32> = row.try_get("due_date").ok();
-            let last_reviewed: Option<i32> = row.try_get("last_reviewed").ok();
-
-            let flashcard = Flashcard {
-                id: Some(id),
-                front: FlashcardField::from_ron(front)?,
-                back: FlashcardField::from_ron(back)?,
-                status: FlashcardStatus::from_id(status).unwrap_or_default(),
-                fsrs_state: fsrs_state.and_then(|s| ron::from_str(&s).ok()),
-                due_date,
-                last_reviewed,
-            };
-
-            result.push(flashcard);
+            result.push(Self::from_row(&row)?);
         }
 
         Ok(result)
     }
 
-    /// Get all flashcards of the given [`Folder`] from the database, also returns the desired retention rate for the folder of this flashcards
-    pub async fn get_all_with_retention_rate(
+    /// Get all flashcards of the given [`Folder`] and it's subfolders from the database, also returns the [`Folder`] itself (needed for it's scheduling options)
+    pub async fn get_all_with_folder(
         pool: Arc<Pool<Sqlite>>,
         folder_id: i32,
-    ) -> Result<(Vec<Flashcard>, f32), anywho::Error> {
-        let desired_retention =
-            sqlx::query_scalar("SELECT desired_retention FROM folders WHERE id = ?")
-                .bind(folder_id)
-                .fetch_one(pool.as_ref())
-                .await?;
+    ) -> Result<(Vec<Flashcard>, Folder), anywho::Error> {
+        let folder = Folder::get(pool.clone(), folder_id).await?;
 
-        let mut rows = sqlx::query(
-            "SELECT id, front, back, status, fsrs_state, due_date, last_reviewed 
-             FROM flashcards 
-             WHERE folder_id = $1 
-             ORDER BY id ASC",
-        )
+        let mut rows = sqlx::query(&format!(
+            "WITH RECURSIVE tree(id) AS (
+                SELECT $1
+                UNION
+                SELECT f.id FROM folders f INNER JOIN tree ON f.parent_id = tree.id WHERE f.deleted_at IS NULL
+             )
+             SELECT {FLASHCARD_COLUMNS}, {TAGS_SUBQUERY} AS tags 
+             FROM flashcards fc 
+             WHERE fc.folder_id IN (SELECT id FROM tree) AND fc.deleted_at IS NULL 
+             ORDER BY fc.id ASC"
+        ))
         .bind(folder_id)
         .fetch(pool.as_ref());
 
-        let mut result = Vec::<Flashcard>::new();
+        let mut flashcards = Vec::<Flashcard>::new();
 
         while let Some(row) = rows.try_next().await? {
-            let id: i32 = row.try_get("id")?;
-            let front: String = row.try_get("front")?;
-            let back: String = row.try_get("back")?;
-            let status: i32 = row.try_get("status")?;
-
-            let fsrs_state: Option<String> = row.try_get("fsrs_state").ok();
-            let due_date: Option<i32> = row.try_get("due_date").ok();
-            let last_reviewed: Option<i32> = row.try_get("last_reviewed").ok();
-
-            let flashcard = Flashcard {
-                id: Some(id),
-                front: FlashcardField::from_ron(front)?,
-                back: FlashcardField::from_ron(back)?,
-                status: FlashcardStatus::from_id(status).unwrap_or_default(),
-                fsrs_state: fsrs_state.and_then(|s| ron::from_str(&s).ok()),
-                due_date,
-                last_reviewed,
-            };
+            flashcards.push(Self::from_row(&row)?);
+        }
+
+        Ok((flashcards, folder))
+    }
+
+    /// Counts the due and total flashcards of every [`Folder`] of the given [`StudySet`] (not including subfolders)
+    pub async fn count_by_folder(
+        pool: Arc<Pool<Sqlite>>,
+        set_id: i32,
+        clock: &dyn Clock,
+    ) -> Result<HashMap<i32, FolderCounts>, anywho::Error> {
+        let rows = sqlx::query(&format!(
+            "SELECT fc.folder_id, COUNT(*) AS total, COUNT(*) FILTER (WHERE {}) AS due
+             FROM flashcards fc
+             INNER JOIN folders f ON f.id = fc.folder_id
+             WHERE f.studyset_id = $1 AND fc.deleted_at IS NULL
+             GROUP BY fc.folder_id",
+            due_condition("$2", "$3", "$4")
+        ))
+        .bind(set_id)
+        .bind(clock.today())
+        .bind(clock.now() + LEARN_AHEAD_SECONDS)
+        .bind(clock.next_day_start())
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = HashMap::<i32, FolderCounts>::new();
+
+        for row in rows {
+            let folder_id: i32 = row.try_get("folder_id")?;
+            let total: i64 = row.try_get("total")?;
+            let due: i64 = row.try_get("due")?;
+
+            result.insert(
+                folder_id,
+                FolderCounts {
+                    due: due as usize,
+                    total: total as usize,
+                },
+            );
+        }
 
-            result.push(flashcard);
+        Ok(result)
+    }
+
+    /// Counts the due flashcards of every [`StudySet`] (by studyset_id)
+    pub async fn count_due_by_studyset(
+        pool: Arc<Pool<Sqlite>>,
+        clock: &dyn Clock,
+    ) -> Result<HashMap<i32, usize>, anywho::Error> {
+        let rows = sqlx::query(&format!(
+            "SELECT f.studyset_id, COUNT(*) AS due
+             FROM flashcards fc
+             INNER JOIN folders f ON f.id = fc.folder_id
+             WHERE fc.deleted_at IS NULL AND f.deleted_at IS NULL AND {}
+             GROUP BY f.studyset_id",
+            due_condition("$1", "$2", "$3")
+        ))
+        .bind(clock.today())
+        .bind(clock.now() + LEARN_AHEAD_SECONDS)
+        .bind(clock.next_day_start())
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = HashMap::<i32, usize>::new();
+
+        for row in rows {
+            let studyset_id: i32 = row.try_get("studyset_id")?;
+            let due: i64 = row.try_get("due")?;
+            result.insert(studyset_id, due as usize);
         }
 
-        Ok((result, desired_retention))
+        Ok(result)
+    }
+
+    // Helper function to build a flashcard from a row that selected the FLASHCARD_COLUMNS and tags
+    fn from_row(row: &SqliteRow) -> Result<Flashcard, anywho::Error> {
+        let id: i32 = row.try_get("id")?;
+        let front: String = row.try_get("front")?;
+        let back: String = row.try_get("back")?;
+        let status: i32 = row.try_get("status")?;
+
+        let fsrs_state: Option<String> = row.try_get("fsrs_state").ok();
+        let due_date: Option<i64> = row.try_get("due_date").ok();
+        let last_reviewed: Option<i64> = row.try_get("last_reviewed").ok();
+        let learning_step: Option<u32> = row.try_get("learning_step").ok();
+        let relearning: bool = row.try_get("relearning")?;
+        let tags: Option<String> = row.try_get("tags").ok();
+        let note_id: Option<i32> = row.try_get("note_id").ok();
+        let template_ord: Option<u32> = row.try_get("template_ord").ok();
+        let suspended: bool = row.try_get("suspended")?;
+        let buried_until: Option<i32> = row.try_get("buried_until").ok();
+        let lapses: u32 = row.try_get("lapses")?;
+
+        Ok(Flashcard {
+            id: Some(id),
+            front: FlashcardField::from_json(&front),
+            back: FlashcardField::from_json(&back),
+            status: FlashcardStatus::from_id(status).unwrap_or_default(),
+            fsrs_state: fsrs_state.and_then(|s| ron::from_str(&s).ok()),
+            due_date,
+            last_reviewed,
+            learning_step,
+            relearning,
+            tags: parse_tags(&tags.unwrap_or_default()),
+            note_id,
+            template_ord,
+            suspended,
+            buried_until,
+            lapses,
+        })
     }
 
     /// Add a [`Flashcard`] to the database
@@ -331,9 +655,27 @@ impl Flashcard {
         flashcard: Flashcard,
         folder_id: i32,
     ) -> Result<(), anywho::Error> {
-        sqlx::query("INSERT INTO flashcards (front, back, status, fsrs_state, due_date, last_reviewed, folder_id) VALUES (?, ?, ?, ?, ?, ?, ?)")
-            .bind(&flashcard.front.to_ron()?)
-            .bind(&flashcard.back.to_ron()?)
+        let mut transaction = pool.begin().await?;
+
+        for flashcard in flashcard.expand_cloze() {
+            Self::insert(&mut *transaction, &flashcard, folder_id).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Insert a [`Flashcard`] (and it's tags) into the given folder, returns the new id,
+    /// uses the given connection (so it can be part of a transaction)
+    pub async fn insert(
+        connection: &mut SqliteConnection,
+        flashcard: &Flashcard,
+        folder_id: i32,
+    ) -> Result<i32, anywho::Error> {
+        let flashcard_id = sqlx::query("INSERT INTO flashcards (front, back, status, fsrs_state, due_date, last_reviewed, learning_step, relearning, note_id, template_ord, suspended, buried_until, lapses, folder_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id")
+            .bind(&flashcard.front.to_json()?)
+            .bind(&flashcard.back.to_json()?)
             .bind(flashcard.status.to_id())
             .bind(
                 flashcard
@@ -343,31 +685,206 @@ impl Flashcard {
             )
             .bind(flashcard.due_date)
             .bind(flashcard.last_reviewed)
+            .bind(flashcard.learning_step)
+            .bind(flashcard.relearning)
+            .bind(flashcard.note_id)
+            .bind(flashcard.template_ord)
+            .bind(flashcard.suspended)
+            .bind(flashcard.buried_until)
+            .bind(flashcard.lapses)
             .bind(folder_id)
-            .execute(pool.as_ref())
+            .fetch_one(&mut *connection)
+            .await?
+            .try_get::<i32, _>("id")?;
+
+        Self::set_tags(&mut *connection, flashcard_id, &flashcard.tags).await?;
+
+        Ok(flashcard_id)
+    }
+
+    /// Edit a [`Flashcard`] on the database, editing a cloze also edits the flashcards generated by the same text.
+    /// The previous front and back are saved as a [`FlashcardRevision`] alongside the reason of the edit (if any)
+    pub async fn edit(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard: Flashcard,
+        edited_at: i64,
+        reason: Option<String>,
+    ) -> Result<(), anywho::Error> {
+        let front = &flashcard.front.to_json()?;
+        let back = &flashcard.back.to_json()?;
+        let flashcard_id = flashcard
+            .id
+            .ok_or_else(|| anywho::anywho!("Flashcard has not been saved yet"))?;
+
+        let mut transaction = pool.begin().await?;
+
+        // The previous wording is kept as a revision
+        FlashcardRevision::save_if_changed(
+            &mut *transaction,
+            flashcard_id,
+            front,
+            back,
+            edited_at,
+            reason,
+        )
+        .await?;
+
+        if let FlashcardField::Cloze { text, .. } = &flashcard.front {
+            Self::sync_cloze(&mut *transaction, flashcard_id, text, &flashcard, edited_at).await?;
+        } else {
+            sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
+                .bind(front)
+                .bind(back)
+                .bind(flashcard_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            Self::set_tags(&mut *transaction, flashcard_id, &flashcard.tags).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    // Helper function to edit a cloze flashcard alongside it's siblings (flashcards of the same folder generated by the same text),
+    // siblings whose index is no longer on the text are moved to the trash and new indexes get a new flashcard
+    async fn sync_cloze(
+        connection: &mut SqliteConnection,
+        flashcard_id: i32,
+        text: &str,
+        flashcard: &Flashcard,
+        edited_at: i64,
+    ) -> Result<(), anywho::Error> {
+        let row = sqlx::query("SELECT front, folder_id FROM flashcards WHERE id = $1")
+            .bind(flashcard_id)
+            .fetch_one(&mut *connection)
             .await?;
+        let old_front = FlashcardField::from_json(&row.try_get::<String, _>("front")?);
+        let folder_id: i32 = row.try_get("folder_id")?;
+
+        let indexes = cloze::indexes(text);
+
+        // (id, index) of the flashcards generated by the old text
+        let siblings: Vec<(i32, u32)> = match old_front {
+            FlashcardField::Cloze {
+                text: old_text,
+                index,
+            } => {
+                let rows = sqlx::query(
+                    "SELECT id, front FROM flashcards WHERE folder_id = $1 AND id != $2 AND note_id IS NULL AND deleted_at IS NULL",
+                )
+                .bind(folder_id)
+                .bind(flashcard_id)
+                .fetch_all(&mut *connection)
+                .await?;
+
+                let mut siblings = vec![(flashcard_id, index)];
+                for row in rows {
+                    if let FlashcardField::Cloze { text, index } =
+                        FlashcardField::from_json(&row.try_get::<String, _>("front")?)
+                        && text == old_text
+                    {
+                        siblings.push((row.try_get("id")?, index));
+                    }
+                }
+                siblings
+            }
+            // The flashcard just became a cloze, it keeps the first index
+            _ => indexes
+                .first()
+                .map(|index| vec![(flashcard_id, *index)])
+                .unwrap_or_default(),
+        };
+
+        let back = flashcard.back.to_json()?;
+
+        for (id, index) in &siblings {
+            if indexes.contains(index) {
+                let front = FlashcardField::Cloze {
+                    text: text.to_string(),
+                    index: *index,
+                };
+
+                sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
+                    .bind(front.to_json()?)
+                    .bind(&back)
+                    .bind(id)
+                    .execute(&mut *connection)
+                    .await?;
+
+                Self::set_tags(&mut *connection, *id, &flashcard.tags).await?;
+            } else {
+                sqlx::query("UPDATE flashcards SET deleted_at = $1 WHERE id = $2")
+                    .bind(edited_at)
+                    .bind(id)
+                    .execute(&mut *connection)
+                    .await?;
+            }
+        }
+
+        for index in indexes
+            .iter()
+            .filter(|index| !siblings.iter().any(|(_, i)| i == *index))
+        {
+            let new_flashcard = Flashcard {
+                front: FlashcardField::Cloze {
+                    text: text.to_string(),
+                    index: *index,
+                },
+                back: flashcard.back.clone(),
+                tags: flashcard.tags.clone(),
+                ..Default::default()
+            };
+
+            Self::insert(&mut *connection, &new_flashcard, folder_id).await?;
+        }
 
         Ok(())
     }
 
-    /// Edit a [`Flashcard`] on the database
-    pub async fn edit(pool: Arc<Pool<Sqlite>>, flashcard: Flashcard) -> Result<(), anywho::Error> {
-        let front = &flashcard.front.to_ron()?;
-        let back = &flashcard.back.to_ron()?;
+    /// Replaces the tags of a [`Flashcard`] using the given connection (so it can be part of a transaction), unused tags are removed
+    pub async fn set_tags(
+        connection: &mut SqliteConnection,
+        flashcard_id: i32,
+        tags: &[String],
+    ) -> Result<(), anywho::Error> {
+        sqlx::query("DELETE FROM flashcard_tags WHERE flashcard_id = ?")
+            .bind(flashcard_id)
+            .execute(&mut *connection)
+            .await?;
 
-        sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
-            .bind(front)
-            .bind(back)
-            .bind(flashcard.id)
-            .execute(pool.as_ref())
+        for tag in tags {
+            sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT(name) DO NOTHING")
+                .bind(tag)
+                .execute(&mut *connection)
+                .await?;
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO flashcard_tags (flashcard_id, tag_id)
+                 SELECT ?, id FROM tags WHERE name = ?",
+            )
+            .bind(flashcard_id)
+            .bind(tag)
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM flashcard_tags)")
+            .execute(&mut *connection)
             .await?;
 
         Ok(())
     }
 
-    /// Delete a [`Flashcard`] on the database
-    pub async fn delete(pool: Arc<Pool<Sqlite>>, flashcard_id: i32) -> Result<(), anywho::Error> {
-        sqlx::query("DELETE FROM flashcards WHERE id = ?")
+    /// Moves a [`Flashcard`] to the trash, it's only deleted from the database once it's purged (see [`TrashItem`])
+    pub async fn delete(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_id: i32,
+        deleted_at: i64,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query("UPDATE flashcards SET deleted_at = $1 WHERE id = $2")
+            .bind(deleted_at)
             .bind(flashcard_id)
             .execute(pool.as_ref())
             .await?;
@@ -375,27 +892,196 @@ impl Flashcard {
         Ok(())
     }
 
-    /// Updates the status and FSRS data of a [`Flashcard`] on the database
+    /// Moves the given flashcards to the given folder keeping their progress,
+    /// flashcards generated by a [`Note`] move alongside the note (and it's other flashcards)
+    pub async fn move_to(
+        pool: Arc<Pool<Sqlite>>,
+        flashcards: Vec<Flashcard>,
+        folder_id: i32,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        for flashcard in &flashcards {
+            if let Some(note_id) = flashcard.note_id {
+                sqlx::query("UPDATE notes SET folder_id = $1 WHERE id = $2")
+                    .bind(folder_id)
+                    .bind(note_id)
+                    .execute(&mut *transaction)
+                    .await?;
+
+                sqlx::query("UPDATE flashcards SET folder_id = $1 WHERE note_id = $2")
+                    .bind(folder_id)
+                    .bind(note_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            } else {
+                sqlx::query("UPDATE flashcards SET folder_id = $1 WHERE id = $2")
+                    .bind(folder_id)
+                    .bind(flashcard.id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Copies the given flashcards into the given folder as plain flashcards (not linked to their [`Note`]),
+    /// images and audios are copied too so each flashcard owns it's files. The progress is reset unless asked to keep it
+    pub async fn copy_to(
+        pool: Arc<Pool<Sqlite>>,
+        flashcards: Vec<Flashcard>,
+        folder_id: i32,
+        keep_progress: bool,
+    ) -> Result<(), anywho::Error> {
+        // Files are copied while the flashcards are inserted, if anything fails the copies are deleted (nothing references them)
+        let mut copied_images = Vec::<String>::new();
+        let mut copied_audio = Vec::<String>::new();
+
+        let result: Result<(), anywho::Error> = async {
+            let mut transaction = pool.begin().await?;
+
+            for flashcard in flashcards {
+                let mut copy = if keep_progress {
+                    Flashcard {
+                        id: None,
+                        note_id: None,
+                        template_ord: None,
+                        ..flashcard
+                    }
+                } else {
+                    Flashcard {
+                        front: flashcard.front,
+                        back: flashcard.back,
+                        // A new flashcard can't be a leech
+                        tags: flashcard
+                            .tags
+                            .into_iter()
+                            .filter(|tag| !tag.eq_ignore_ascii_case(LEECH_TAG))
+                            .collect(),
+                        ..Default::default()
+                    }
+                };
+
+                for field in copy
+                    .front
+                    .blocks_mut()
+                    .iter_mut()
+                    .chain(copy.back.blocks_mut().iter_mut())
+                {
+                    match field {
+                        FlashcardField::Image { path, .. } if !path.is_empty() => {
+                            *path = utils::save_image(path)?;
+                            copied_images.push(path.clone());
+                        }
+                        FlashcardField::Audio { path, .. } if !path.is_empty() => {
+                            *path = utils::save_audio(path)?;
+                            copied_audio.push(path.clone());
+                        }
+                        _ => {}
+                    }
+                }
+
+                Self::insert(&mut *transaction, &copy, folder_id).await?;
+            }
+
+            transaction.commit().await?;
+
+            Ok(())
+        }
+        .await;
+
+        if result.is_err() {
+            for path in copied_images {
+                if let Err(e) = utils::delete_image(path).await {
+                    eprintln!("{e}");
+                }
+            }
+            for path in copied_audio {
+                if let Err(e) = utils::delete_audio(path).await {
+                    eprintln!("{e}");
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Updates the status and FSRS data of a [`Flashcard`] on the database, the review is also recorded on the revlog,
+    /// if the flashcard has been forgotten (lapsed) it's lapse count goes up
     pub async fn update_status(
         pool: Arc<Pool<Sqlite>>,
-        status: FlashcardStatus,
-        flashcard_id: i32,
-        fsrs_state: SerializableMemoryState,
-        due_date: i32,
+        review: RevlogEntry,
+        scheduled: ScheduledReview,
+        lapsed: bool,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE flashcards 
+             SET status = $1, fsrs_state = $2, due_date = $3, last_reviewed = $4, learning_step = $5, relearning = $6, lapses = lapses + $7 
+             WHERE id = $8",
+        )
+        .bind(review.rating.to_id())
+        .bind(ron::to_string(&review.new_fsrs_state)?)
+        .bind(scheduled.due_date)
+        .bind(review.reviewed_at)
+        .bind(scheduled.learning_step)
+        .bind(scheduled.relearning)
+        .bind(lapsed as i32)
+        .bind(review.flashcard_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        RevlogEntry::add(&mut *transaction, &review).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Undoes the last review of a [`Flashcard`] on the database, it's status and FSRS data go back to the ones of the given flashcard
+    /// (as it was before the review) and the review is removed from the revlog
+    pub async fn undo_review(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard: Flashcard,
     ) -> Result<(), anywho::Error> {
+        let flashcard_id = flashcard
+            .id
+            .ok_or_else(|| anywho::anywho!("Flashcard has not been saved yet"))?;
+
+        let mut transaction = pool.begin().await?;
+
         sqlx::query(
             "UPDATE flashcards 
-             SET status = $1, fsrs_state = $2, due_date = $3, last_reviewed = $4 
-             WHERE id = $5",
+             SET status = $1, fsrs_state = $2, due_date = $3, last_reviewed = $4, learning_step = $5, relearning = $6, lapses = $7, suspended = $8 
+             WHERE id = $9",
+        )
+        .bind(flashcard.status.to_id())
+        .bind(
+            flashcard
+                .fsrs_state
+                .as_ref()
+                .and_then(|s| ron::to_string(s).ok()),
         )
-        .bind(status.to_id())
-        .bind(ron::to_string(&fsrs_state)?)
-        .bind(due_date)
-        .bind(current_day())
+        .bind(flashcard.due_date)
+        .bind(flashcard.last_reviewed)
+        .bind(flashcard.learning_step)
+        .bind(flashcard.relearning)
+        .bind(flashcard.lapses)
+        .bind(flashcard.suspended)
         .bind(flashcard_id)
-        .execute(pool.as_ref())
+        .execute(&mut *transaction)
         .await?;
 
+        // The review may have tagged the flashcard as a leech
+        Self::set_tags(&mut *transaction, flashcard_id, &flashcard.tags).await?;
+        RevlogEntry::delete_last(&mut *transaction, flashcard_id).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 
@@ -404,7 +1090,7 @@ impl Flashcard {
         pool: Arc<Pool<Sqlite>>,
         flashcard_id: i32,
     ) -> Result<(), anywho::Error> {
-        sqlx::query("UPDATE flashcards SET status = $1, fsrs_state = NULL, due_date = NULL, last_reviewed = NULL WHERE id = $2")
+        sqlx::query("UPDATE flashcards SET status = $1, fsrs_state = NULL, due_date = NULL, last_reviewed = NULL, learning_step = NULL, relearning = 0 WHERE id = $2")
             .bind(FlashcardStatus::None.to_id())
             .bind(flashcard_id)
             .execute(pool.as_ref())
@@ -418,7 +1104,7 @@ impl Flashcard {
         pool: Arc<Pool<Sqlite>>,
         folder_id: i32,
     ) -> Result<(), anywho::Error> {
-        sqlx::query("UPDATE flashcards SET status = $1, fsrs_state = NULL, due_date = NULL, last_reviewed = NULL WHERE folder_id = $2")
+        sqlx::query("UPDATE flashcards SET status = $1, fsrs_state = NULL, due_date = NULL, last_reviewed = NULL, learning_step = NULL, relearning = 0 WHERE folder_id = $2 AND deleted_at IS NULL")
             .bind(FlashcardStatus::None.to_id())
             .bind(folder_id)
             .execute(pool.as_ref())
@@ -427,15 +1113,218 @@ impl Flashcard {
         Ok(())
     }
 
-    /// Add more than one [`Flashcard`] to the database
-    pub async fn add_bulk(
+    /// Suspends (or unsuspends) a [`Flashcard`] on the database
+    pub async fn set_suspended(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_id: i32,
+        suspended: bool,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query("UPDATE flashcards SET suspended = $1 WHERE id = $2")
+            .bind(suspended)
+            .bind(flashcard_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unsuspends all the [`Flashcard`] of a given folder on the database
+    pub async fn unsuspend_all(
         pool: Arc<Pool<Sqlite>>,
-        flashcards: Vec<Flashcard>,
         folder_id: i32,
     ) -> Result<(), anywho::Error> {
-        for flashcard in flashcards {
-            Self::add(pool.clone(), flashcard, folder_id).await?
+        sqlx::query(
+            "UPDATE flashcards SET suspended = 0 WHERE folder_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(folder_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Buries a [`Flashcard`] on the database so it's not studied until the given study day
+    pub async fn bury(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_id: i32,
+        until_day: i32,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query("UPDATE flashcards SET buried_until = $1 WHERE id = $2")
+            .bind(until_day)
+            .bind(flashcard_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Tags a [`Flashcard`] as a leech (see [`LEECH_TAG`]) on the database, it's also suspended if asked to
+    pub async fn mark_leech(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_id: i32,
+        suspend: bool,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT(name) DO NOTHING")
+            .bind(LEECH_TAG)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO flashcard_tags (flashcard_id, tag_id)
+             SELECT ?, id FROM tags WHERE name = ?",
+        )
+        .bind(flashcard_id)
+        .bind(LEECH_TAG)
+        .execute(&mut *transaction)
+        .await?;
+
+        if suspend {
+            sqlx::query("UPDATE flashcards SET suspended = 1 WHERE id = $1")
+                .bind(flashcard_id)
+                .execute(&mut *transaction)
+                .await?;
         }
+
+        transaction.commit().await?;
+
         Ok(())
     }
+
+    /// Add more than one [`Flashcard`] to the database, the ones whose front already exists are handled with the given [`DuplicateAction`]
+    pub async fn add_bulk(
+        pool: Arc<Pool<Sqlite>>,
+        flashcards: Vec<Flashcard>,
+        folder_id: i32,
+        scope: DuplicateScope,
+        action: DuplicateAction,
+        imported_at: i64,
+    ) -> Result<ImportSummary, anywho::Error> {
+        let mut checker = DuplicateChecker::new(scope, action, imported_at);
+        let mut transaction = pool.begin().await?;
+
+        for flashcard in flashcards.iter().flat_map(Flashcard::expand_cloze) {
+            checker
+                .import(&mut *transaction, &flashcard, folder_id)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(checker.summary)
+    }
+}
+
+/// Splits the given text into tags (separated by whitespace), ignoring repeated ones
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split_whitespace() {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Helper function to get the SQL condition (for flashcards aliased as fc) that matches the same flashcards as [`Flashcard::is_due`].
+// The given parameters must be bound to the current study day, the current time plus LEARN_AHEAD_SECONDS and the start of the next study day
+fn due_condition(today: &str, learn_ahead: &str, next_day_start: &str) -> String {
+    format!(
+        "(fc.suspended = 0
+          AND (fc.buried_until IS NULL OR fc.buried_until <= {today})
+          AND CASE
+              WHEN fc.due_date IS NULL THEN fc.fsrs_state IS NULL
+              WHEN fc.learning_step IS NOT NULL THEN fc.due_date <= {learn_ahead}
+              ELSE fc.due_date < {next_day_start}
+          END)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::core::utils::FixedClock;
+
+    // 1970-01-11 12:00 UTC, study day 10
+    const NOW: i64 = 10 * 86400 + 12 * 3600;
+
+    fn clock() -> FixedClock {
+        FixedClock {
+            now: NOW,
+            next_day_starts_at: 4,
+        }
+    }
+
+    fn reviewed_flashcard(due_date: i64) -> Flashcard {
+        Flashcard {
+            fsrs_state: Some(SerializableMemoryState {
+                stability: 5.0,
+                difficulty: 5.0,
+            }),
+            due_date: Some(due_date),
+            last_reviewed: Some(due_date - 5 * 86400),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn new_flashcards_are_due() {
+        assert!(Flashcard::default().is_due(&clock()));
+    }
+
+    #[test]
+    fn suspended_and_buried_flashcards_are_not_due() {
+        let suspended = Flashcard {
+            suspended: true,
+            ..Default::default()
+        };
+        let buried = Flashcard {
+            buried_until: Some(11),
+            ..Default::default()
+        };
+        let buried_yesterday = Flashcard {
+            buried_until: Some(10),
+            ..Default::default()
+        };
+
+        assert!(!suspended.is_due(&clock()));
+        assert!(!buried.is_due(&clock()));
+        assert!(buried_yesterday.is_due(&clock()));
+    }
+
+    #[test]
+    fn review_flashcards_are_due_for_the_whole_study_day() {
+        // Later today
+        assert!(reviewed_flashcard(NOW + 6 * 3600).is_due(&clock()));
+        // Before the rollover hour of the next day, still the same study day
+        assert!(reviewed_flashcard(11 * 86400 + 3 * 3600).is_due(&clock()));
+        // Once the next study day starts
+        assert!(!reviewed_flashcard(11 * 86400 + 4 * 3600).is_due(&clock()));
+    }
+
+    #[test]
+    fn learning_flashcards_are_due_by_the_second() {
+        let learning = |due_date: i64| Flashcard {
+            learning_step: Some(0),
+            ..reviewed_flashcard(due_date)
+        };
+
+        assert!(learning(NOW).is_due(&clock()));
+        assert!(learning(NOW + LEARN_AHEAD_SECONDS).is_due(&clock()));
+        assert!(!learning(NOW + LEARN_AHEAD_SECONDS + 1).is_due(&clock()));
+    }
+
+    #[test]
+    fn unreadable_legacy_fields_are_saved_as_json() {
+        let field = FlashcardField::from_json("not a field");
+        assert_eq!(
+            field,
+            FlashcardField::Unsupported(String::from("not a field"))
+        );
+
+        let json = field.to_json().unwrap();
+        assert!(json.starts_with('{'));
+        assert_eq!(FlashcardField::from_json(&json), field);
+    }
 }
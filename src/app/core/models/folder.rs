@@ -2,14 +2,59 @@
 
 use futures::stream::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite};
-use std::sync::Arc;
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::app::core::{
+    models::flashcard::Flashcard,
+    utils::{Clock, fsrs_scheduler},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Folder {
     pub id: Option<i32>,
     pub name: String,
     pub desired_retention: f32,
+    /// Optimised FSRS parameters, [`None`] means the default parameters are used
+    #[serde(default)]
+    pub fsrs_parameters: Option<Vec<f32>>,
+    /// Space separated learning steps for new flashcards, ej: "1m 10m"
+    #[serde(default = "default_learning_steps")]
+    pub learning_steps: String,
+    /// Space separated relearning steps for forgotten flashcards, ej: "10m"
+    #[serde(default = "default_relearning_steps")]
+    pub relearning_steps: String,
+    /// Folder this folder is nested in, [`None`] for top level folders
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    /// Lapses after which a flashcard is tagged as a leech, 0 disables leech detection
+    #[serde(default = "default_leech_threshold")]
+    pub leech_threshold: u32,
+    /// Leeches are also suspended if true
+    #[serde(default)]
+    pub leech_suspend: bool,
+    /// Position of the folder between it's siblings on the manual order
+    #[serde(default)]
+    pub position: i32,
+}
+
+/// Number of flashcards of a [`Folder`] (including it's subfolders)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FolderCounts {
+    pub due: usize,
+    pub total: usize,
+}
+
+fn default_learning_steps() -> String {
+    String::from("1m 10m")
+}
+
+fn default_relearning_steps() -> String {
+    String::from("10m")
+}
+
+fn default_leech_threshold() -> u32 {
+    8
 }
 
 impl Default for Folder {
@@ -18,6 +63,13 @@ impl Default for Folder {
             id: Default::default(),
             desired_retention: 0.90,
             name: Default::default(),
+            fsrs_parameters: None,
+            learning_steps: default_learning_steps(),
+            relearning_steps: default_relearning_steps(),
+            parent_id: None,
+            leech_threshold: default_leech_threshold(),
+            leech_suspend: false,
+            position: 0,
         }
     }
 }
@@ -29,7 +81,7 @@ impl Folder {
         set_id: i32,
     ) -> Result<Vec<Folder>, anywho::Error> {
         let mut rows =
-            sqlx::query("SELECT id, name, desired_retention FROM folders WHERE studyset_id = $1 ORDER BY id ASC")
+            sqlx::query("SELECT id, name, desired_retention, fsrs_parameters, learning_steps, relearning_steps, parent_id, leech_threshold, leech_suspend, position FROM folders WHERE studyset_id = $1 AND deleted_at IS NULL ORDER BY position ASC, id ASC")
                 .bind(set_id)
                 .fetch(pool.as_ref());
 
@@ -39,11 +91,25 @@ impl Folder {
             let id: i32 = row.try_get("id")?;
             let name: String = row.try_get("name")?;
             let desired_retention: f32 = row.try_get("desired_retention")?;
+            let fsrs_parameters: Option<String> = row.try_get("fsrs_parameters").ok();
+            let learning_steps: String = row.try_get("learning_steps")?;
+            let relearning_steps: String = row.try_get("relearning_steps")?;
+            let parent_id: Option<i32> = row.try_get("parent_id").ok();
+            let leech_threshold: u32 = row.try_get("leech_threshold")?;
+            let leech_suspend: bool = row.try_get("leech_suspend")?;
+            let position: i32 = row.try_get("position")?;
 
             let folder = Folder {
                 id: Some(id),
                 name,
                 desired_retention,
+                fsrs_parameters: fsrs_parameters.and_then(|p| ron::from_str(&p).ok()),
+                learning_steps,
+                relearning_steps,
+                parent_id,
+                leech_threshold,
+                leech_suspend,
+                position,
             };
 
             result.push(folder);
@@ -52,27 +118,162 @@ impl Folder {
         Ok(result)
     }
 
+    /// Get a single [`Folder`] from the database
+    pub async fn get(pool: Arc<Pool<Sqlite>>, folder_id: i32) -> Result<Folder, anywho::Error> {
+        let row =
+            sqlx::query("SELECT id, name, desired_retention, fsrs_parameters, learning_steps, relearning_steps, parent_id, leech_threshold, leech_suspend, position FROM folders WHERE id = $1")
+                .bind(folder_id)
+                .fetch_one(pool.as_ref())
+                .await?;
+
+        let fsrs_parameters: Option<String> = row.try_get("fsrs_parameters").ok();
+
+        Ok(Folder {
+            id: Some(row.try_get("id")?),
+            name: row.try_get("name")?,
+            desired_retention: row.try_get("desired_retention")?,
+            fsrs_parameters: fsrs_parameters.and_then(|p| ron::from_str(&p).ok()),
+            learning_steps: row.try_get("learning_steps")?,
+            relearning_steps: row.try_get("relearning_steps")?,
+            parent_id: row.try_get("parent_id").ok(),
+            leech_threshold: row.try_get("leech_threshold")?,
+            leech_suspend: row.try_get("leech_suspend")?,
+            position: row.try_get("position")?,
+        })
+    }
+
+    /// Get the id and path (study set / folder / subfolder) of every [`Folder`] of every [`StudySet`], ordered by path
+    pub async fn get_all_paths(
+        pool: Arc<Pool<Sqlite>>,
+    ) -> Result<Vec<(i32, String)>, anywho::Error> {
+        let rows = sqlx::query(
+            "WITH RECURSIVE tree(id, path) AS (
+                SELECT f.id, s.name || ' / ' || f.name FROM folders f
+                INNER JOIN studysets s ON s.id = f.studyset_id
+                WHERE f.parent_id IS NULL AND f.deleted_at IS NULL AND s.deleted_at IS NULL
+                UNION ALL
+                SELECT f.id, tree.path || ' / ' || f.name FROM folders f
+                INNER JOIN tree ON f.parent_id = tree.id
+                WHERE f.deleted_at IS NULL
+             )
+             SELECT id, path FROM tree ORDER BY path COLLATE NOCASE",
+        )
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = Vec::<(i32, String)>::new();
+
+        for row in rows {
+            result.push((row.try_get("id")?, row.try_get("path")?));
+        }
+
+        Ok(result)
+    }
+
+    /// Get all folders of the given [`StudySet`] from the database with their [`FolderCounts`] (aggregated up the tree)
+    pub async fn get_all_with_counts(
+        pool: Arc<Pool<Sqlite>>,
+        set_id: i32,
+        clock: Arc<dyn Clock>,
+    ) -> Result<(Vec<Folder>, HashMap<i32, FolderCounts>), anywho::Error> {
+        let folders = Self::get_all(pool.clone(), set_id).await?;
+        let own_counts = Flashcard::count_by_folder(pool, set_id, clock.as_ref()).await?;
+
+        // Add the counts of every folder to itself and all of it's ancestors
+        let mut counts: HashMap<i32, FolderCounts> = HashMap::new();
+        for (folder_id, own) in own_counts {
+            let mut current = Some(folder_id);
+            // The depth limit protects us from (theoretically impossible) cycles
+            for _ in 0..=folders.len() {
+                let Some(id) = current else {
+                    break;
+                };
+
+                let entry = counts.entry(id).or_default();
+                entry.due += own.due;
+                entry.total += own.total;
+
+                current = folders
+                    .iter()
+                    .find(|f| f.id == Some(id))
+                    .and_then(|f| f.parent_id);
+            }
+        }
+
+        Ok((folders, counts))
+    }
+
     /// Add a [`Folder`] to the database
     pub async fn add(
         pool: Arc<Pool<Sqlite>>,
         studyset_id: i32,
         folder: Folder,
     ) -> Result<(), anywho::Error> {
-        sqlx::query("INSERT INTO folders (name, studyset_id, desired_retention) VALUES (?, ?, ?)")
+        sqlx::query("INSERT INTO folders (name, studyset_id, desired_retention, learning_steps, relearning_steps, parent_id, leech_threshold, leech_suspend) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(&folder.name)
             .bind(studyset_id)
             .bind(folder.desired_retention)
+            .bind(&folder.learning_steps)
+            .bind(&folder.relearning_steps)
+            .bind(folder.parent_id)
+            .bind(folder.leech_threshold)
+            .bind(folder.leech_suspend)
             .execute(pool.as_ref())
             .await?;
 
         Ok(())
     }
 
+    /// Get the id of the folder at the given path of names under the given parent, creating the missing folders,
+    /// uses the given connection (so it can be part of a transaction)
+    pub async fn get_or_create_path(
+        connection: &mut SqliteConnection,
+        studyset_id: i32,
+        parent_id: Option<i32>,
+        path: &[String],
+    ) -> Result<Option<i32>, anywho::Error> {
+        let mut current = parent_id;
+
+        for name in path {
+            let existing = sqlx::query(
+                "SELECT id FROM folders WHERE studyset_id = $1 AND parent_id IS $2 AND name = $3 AND deleted_at IS NULL",
+            )
+            .bind(studyset_id)
+            .bind(current)
+            .bind(name)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+            current = Some(match existing {
+                Some(row) => row.try_get("id")?,
+                None => sqlx::query(
+                    "INSERT INTO folders (name, studyset_id, desired_retention, learning_steps, relearning_steps, parent_id) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
+                )
+                .bind(name)
+                .bind(studyset_id)
+                .bind(Folder::default().desired_retention)
+                .bind(default_learning_steps())
+                .bind(default_relearning_steps())
+                .bind(current)
+                .fetch_one(&mut *connection)
+                .await?
+                .try_get("id")?,
+            });
+        }
+
+        Ok(current)
+    }
+
     /// Edit a [`Folder`] on the database
     pub async fn edit(pool: Arc<Pool<Sqlite>>, folder: Folder) -> Result<(), anywho::Error> {
-        sqlx::query("UPDATE folders SET name = $1, desired_retention = $2 WHERE id = $3")
+        sqlx::query("UPDATE folders SET name = $1, desired_retention = $2, learning_steps = $3, relearning_steps = $4, parent_id = $5, leech_threshold = $6, leech_suspend = $7 WHERE id = $8")
             .bind(&folder.name)
             .bind(folder.desired_retention)
+            .bind(&folder.learning_steps)
+            .bind(&folder.relearning_steps)
+            .bind(folder.parent_id)
+            .bind(folder.leech_threshold)
+            .bind(folder.leech_suspend)
             .bind(folder.id)
             .execute(pool.as_ref())
             .await?;
@@ -80,13 +281,116 @@ impl Folder {
         Ok(())
     }
 
-    /// Delete a [`Folder`] from the database (and it's flashcards)
-    pub async fn delete(pool: Arc<Pool<Sqlite>>, folder_id: i32) -> Result<(), anywho::Error> {
-        sqlx::query("DELETE FROM folders WHERE id = ?")
+    /// Places the given folders under the given parent folder ([`None`] for the top level) in the given order
+    pub async fn reorder(
+        pool: Arc<Pool<Sqlite>>,
+        parent_id: Option<i32>,
+        folder_ids: Vec<i32>,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        for (index, folder_id) in folder_ids.into_iter().enumerate() {
+            sqlx::query("UPDATE folders SET parent_id = $1, position = $2 WHERE id = $3")
+                .bind(parent_id)
+                .bind(index as i32 + 1)
+                .bind(folder_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Saves the given FSRS parameters for a [`Folder`] on the database, [`None`] goes back to the default parameters
+    pub async fn set_fsrs_parameters(
+        pool: Arc<Pool<Sqlite>>,
+        folder_id: i32,
+        parameters: Option<Vec<f32>>,
+    ) -> Result<(), anywho::Error> {
+        let parameters = match parameters {
+            Some(parameters) => {
+                fsrs_scheduler::validate_parameters(&parameters)?;
+                Some(ron::to_string(&parameters)?)
+            }
+            None => None,
+        };
+
+        sqlx::query("UPDATE folders SET fsrs_parameters = $1 WHERE id = $2")
+            .bind(parameters)
             .bind(folder_id)
             .execute(pool.as_ref())
             .await?;
 
         Ok(())
     }
+
+    /// Moves a [`Folder`] (and it's subfolders) to the trash, it's only deleted from the database once it's purged (see [`TrashItem`])
+    pub async fn delete(
+        pool: Arc<Pool<Sqlite>>,
+        folder_id: i32,
+        deleted_at: i64,
+    ) -> Result<(), anywho::Error> {
+        // Subfolders that were already on the trash keep their own time, so they are restored separately
+        sqlx::query(
+            "WITH RECURSIVE tree(id) AS (
+                SELECT $1
+                UNION
+                SELECT f.id FROM folders f INNER JOIN tree ON f.parent_id = tree.id WHERE f.deleted_at IS NULL
+             )
+             UPDATE folders SET deleted_at = $2 WHERE id IN (SELECT id FROM tree)",
+        )
+        .bind(folder_id)
+        .bind(deleted_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Orders the given folders as a tree (every folder followed by it's subfolders) alongside their depth
+pub fn folder_tree(folders: &[Folder]) -> Vec<(usize, &Folder)> {
+    fn push_children<'a>(
+        folders: &'a [Folder],
+        parent_id: Option<i32>,
+        depth: usize,
+        result: &mut Vec<(usize, &'a Folder)>,
+    ) {
+        for folder in folders.iter().filter(|f| f.parent_id == parent_id) {
+            result.push((depth, folder));
+            push_children(folders, folder.id, depth + 1, result);
+        }
+    }
+
+    let mut result = Vec::with_capacity(folders.len());
+    push_children(folders, None, 0, &mut result);
+
+    // Folders whose parent is missing (it should not happen) are shown at the top level
+    for folder in folders {
+        if !result.iter().any(|(_, f)| f.id == folder.id) {
+            result.push((0, folder));
+            push_children(folders, folder.id, 1, &mut result);
+        }
+    }
+
+    result
+}
+
+/// Returns the ids of the given folder and all of it's subfolders
+pub fn folder_descendants(folders: &[Folder], folder_id: i32) -> Vec<i32> {
+    let mut result = vec![folder_id];
+    let mut index = 0;
+    while let Some(id) = result.get(index).copied() {
+        for folder in folders.iter().filter(|f| f.parent_id == Some(id)) {
+            if let Some(child_id) = folder.id
+                && !result.contains(&child_id)
+            {
+                result.push(child_id);
+            }
+        }
+        index += 1;
+    }
+    result
 }
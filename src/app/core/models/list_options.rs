@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::app::core::{
+    models::flashcard::{Flashcard, FlashcardField, FlashcardStatus},
+    utils::Clock,
+};
+
+/// How the flashcards of a folder are sorted on the list
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FlashcardSort {
+    /// Order in which they were created
+    #[default]
+    Created,
+    /// By the text of the front
+    Alphabetical,
+    DueDate,
+    LastReviewed,
+    Difficulty,
+    Stability,
+}
+
+impl FlashcardSort {
+    pub const ALL: [Self; 6] = [
+        Self::Created,
+        Self::Alphabetical,
+        Self::DueDate,
+        Self::LastReviewed,
+        Self::Difficulty,
+        Self::Stability,
+    ];
+}
+
+/// Scheduling state a [`Flashcard`] can be filtered by
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DueFilter {
+    /// Flashcards that can be studied now
+    Due,
+    /// Flashcards that should have been studied before today
+    Overdue,
+    /// Flashcards that have never been studied
+    New,
+}
+
+impl DueFilter {
+    pub const ALL: [Self; 3] = [Self::Due, Self::Overdue, Self::New];
+}
+
+/// Field type a [`Flashcard`] can be filtered by, it matches if any side (or any block of a side) is of that type
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FieldKind {
+    Text,
+    Image,
+    Audio,
+    Cloze,
+    Code,
+    Blocks,
+}
+
+impl FieldKind {
+    pub const ALL: [Self; 6] = [
+        Self::Text,
+        Self::Image,
+        Self::Audio,
+        Self::Cloze,
+        Self::Code,
+        Self::Blocks,
+    ];
+
+    /// Returns true if the given [`FlashcardField`] is (or contains a block) of this type
+    fn matches(self, field: &FlashcardField) -> bool {
+        if self == Self::Blocks {
+            return matches!(field, FlashcardField::Blocks(_));
+        }
+
+        field.blocks().iter().any(|block| {
+            matches!(
+                (self, block),
+                (Self::Text, FlashcardField::Text(_))
+                    | (Self::Image, FlashcardField::Image { .. })
+                    | (Self::Audio, FlashcardField::Audio { .. })
+                    | (Self::Cloze, FlashcardField::Cloze { .. })
+                    | (Self::Code, FlashcardField::Code { .. })
+            )
+        })
+    }
+}
+
+/// Sort and filters of the flashcards list of a folder, they are remembered (per folder) on the config
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FlashcardListOptions {
+    pub sort: FlashcardSort,
+    pub descending: bool,
+    pub status: Option<FlashcardStatus>,
+    pub due: Option<DueFilter>,
+    pub field_kind: Option<FieldKind>,
+    /// Only show the flashcards containing this text (ignoring case)
+    pub text: String,
+}
+
+impl FlashcardListOptions {
+    /// Returns true if any of the filters is being used
+    pub fn is_filtering(&self) -> bool {
+        self.status.is_some()
+            || self.due.is_some()
+            || self.field_kind.is_some()
+            || !self.text.trim().is_empty()
+    }
+
+    /// Filters and sorts the given flashcards
+    pub fn apply<'a>(
+        &self,
+        flashcards: impl Iterator<Item = &'a Flashcard>,
+        clock: &dyn Clock,
+    ) -> Vec<&'a Flashcard> {
+        let text = self.text.trim().to_lowercase();
+
+        let mut result: Vec<&Flashcard> = flashcards
+            .filter(|f| self.status.is_none_or(|status| f.status == status))
+            .filter(|f| self.due.is_none_or(|due| is_in_due_state(f, due, clock)))
+            .filter(|f| {
+                self.field_kind
+                    .is_none_or(|kind| kind.matches(&f.front) || kind.matches(&f.back))
+            })
+            .filter(|f| {
+                text.is_empty()
+                    || f.front.plain_text().to_lowercase().contains(&text)
+                    || f.back.plain_text().to_lowercase().contains(&text)
+            })
+            .collect();
+
+        // Flashcards without a value to sort by (ej: new flashcards have no due date) always go last
+        match self.sort {
+            FlashcardSort::Created => result.sort_by(|a, b| compare(a.id, b.id, self.descending)),
+            FlashcardSort::Alphabetical => {
+                result.sort_by_cached_key(|f| f.front.plain_text().to_lowercase());
+                if self.descending {
+                    result.reverse();
+                }
+            }
+            FlashcardSort::DueDate => {
+                result.sort_by(|a, b| compare(a.due_date, b.due_date, self.descending))
+            }
+            FlashcardSort::LastReviewed => {
+                result.sort_by(|a, b| compare(a.last_reviewed, b.last_reviewed, self.descending))
+            }
+            FlashcardSort::Difficulty => result.sort_by(|a, b| {
+                compare(
+                    a.fsrs_state.as_ref().map(|s| s.difficulty),
+                    b.fsrs_state.as_ref().map(|s| s.difficulty),
+                    self.descending,
+                )
+            }),
+            FlashcardSort::Stability => result.sort_by(|a, b| {
+                compare(
+                    a.fsrs_state.as_ref().map(|s| s.stability),
+                    b.fsrs_state.as_ref().map(|s| s.stability),
+                    self.descending,
+                )
+            }),
+        }
+
+        result
+    }
+}
+
+// Helper function to compare two optional values, missing values go last regardless of the direction
+fn compare<T: PartialOrd>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// Helper function to check if a flashcard is in the given scheduling state
+fn is_in_due_state(flashcard: &Flashcard, due: DueFilter, clock: &dyn Clock) -> bool {
+    match due {
+        DueFilter::Due => flashcard.is_due(clock),
+        DueFilter::Overdue => {
+            !flashcard.suspended
+                && !flashcard.is_buried(clock)
+                && flashcard
+                    .due_date
+                    .is_some_and(|due| clock.day_of(due) < clock.today())
+        }
+        DueFilter::New => flashcard.fsrs_state.is_none(),
+    }
+}
@@ -0,0 +1,560 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, sqlite::SqliteRow};
+use std::sync::Arc;
+
+use crate::app::core::models::{
+    flashcard::{Flashcard, FlashcardField, TAGS_SUBQUERY, parse_tags},
+    revision::FlashcardRevision,
+};
+
+/// Defines the fields of a [`Note`] and the [`CardTemplate`]s used to generate it's flashcards
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NoteType {
+    pub id: Option<i32>,
+    pub name: String,
+    pub fields: Vec<String>,
+    pub templates: Vec<CardTemplate>,
+}
+
+/// Generates one flashcard for every [`Note`], `{{Field}}` is replaced with the value of the field
+/// and `{{FrontSide}}` (only on the back) with the generated front
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CardTemplate {
+    /// Identifies the template (and it's flashcards) even if other templates are removed
+    pub ord: u32,
+    pub name: String,
+    pub front: String,
+    pub back: String,
+}
+
+/// Values for the fields of a [`NoteType`] (in the same order), every note generates one flashcard per template
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: Option<i32>,
+    pub note_type_id: i32,
+    pub fields: Vec<String>,
+}
+
+impl NoteType {
+    /// Get all [`NoteType`] from the database
+    pub async fn get_all(pool: Arc<Pool<Sqlite>>) -> Result<Vec<NoteType>, anywho::Error> {
+        let mut rows =
+            sqlx::query("SELECT id, name, fields, templates FROM note_types ORDER BY id ASC")
+                .fetch(pool.as_ref());
+
+        let mut result = Vec::<NoteType>::new();
+
+        while let Some(row) = rows.try_next().await? {
+            result.push(Self::from_row(&row)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Get a single [`NoteType`] using the given connection (so it can be part of a transaction)
+    pub async fn fetch(
+        connection: &mut SqliteConnection,
+        note_type_id: i32,
+    ) -> Result<NoteType, anywho::Error> {
+        let row = sqlx::query("SELECT id, name, fields, templates FROM note_types WHERE id = $1")
+            .bind(note_type_id)
+            .fetch_one(&mut *connection)
+            .await?;
+
+        Self::from_row(&row)
+    }
+
+    // Helper function to build a [`NoteType`] from a row of the note_types table
+    fn from_row(row: &SqliteRow) -> Result<NoteType, anywho::Error> {
+        let fields: String = row.try_get("fields")?;
+        let templates: String = row.try_get("templates")?;
+
+        Ok(NoteType {
+            id: Some(row.try_get("id")?),
+            name: row.try_get("name")?,
+            fields: serde_json::from_str(&fields)?,
+            templates: serde_json::from_str(&templates)?,
+        })
+    }
+
+    /// Add a [`NoteType`] to the database
+    pub async fn add(pool: Arc<Pool<Sqlite>>, note_type: NoteType) -> Result<(), anywho::Error> {
+        let mut connection = pool.acquire().await?;
+        Self::insert(&mut *connection, &note_type).await?;
+
+        Ok(())
+    }
+
+    /// Insert a [`NoteType`] into the database, returns the new id,
+    /// uses the given connection (so it can be part of a transaction)
+    pub async fn insert(
+        connection: &mut SqliteConnection,
+        note_type: &NoteType,
+    ) -> Result<i32, anywho::Error> {
+        let note_type_id = sqlx::query(
+            "INSERT INTO note_types (name, fields, templates) VALUES (?, ?, ?) RETURNING id",
+        )
+        .bind(&note_type.name)
+        .bind(serde_json::to_string(&note_type.fields)?)
+        .bind(serde_json::to_string(&note_type.templates)?)
+        .fetch_one(&mut *connection)
+        .await?
+        .try_get::<i32, _>("id")?;
+
+        Ok(note_type_id)
+    }
+
+    /// Edit a [`NoteType`] on the database, the flashcards of all it's notes are generated again
+    pub async fn edit(
+        pool: Arc<Pool<Sqlite>>,
+        note_type: NoteType,
+        edited_at: i64,
+    ) -> Result<(), anywho::Error> {
+        let note_type_id = note_type
+            .id
+            .ok_or_else(|| anywho::anywho!("Note type has not been saved yet"))?;
+
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query("UPDATE note_types SET name = $1, fields = $2, templates = $3 WHERE id = $4")
+            .bind(&note_type.name)
+            .bind(serde_json::to_string(&note_type.fields)?)
+            .bind(serde_json::to_string(&note_type.templates)?)
+            .bind(note_type_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        let notes = sqlx::query(
+            "SELECT id, note_type_id, fields, folder_id FROM notes WHERE note_type_id = $1",
+        )
+        .bind(note_type_id)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        for row in notes {
+            let note = Note::from_row(&row)?;
+            let folder_id: i32 = row.try_get("folder_id")?;
+            Note::sync_flashcards(
+                &mut *transaction,
+                &note_type,
+                &note,
+                folder_id,
+                None,
+                edited_at,
+                None,
+            )
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Delete a [`NoteType`] from the database, only if no notes are using it
+    pub async fn delete(pool: Arc<Pool<Sqlite>>, note_type_id: i32) -> Result<(), anywho::Error> {
+        let in_use: i64 = sqlx::query("SELECT COUNT(*) AS count FROM notes WHERE note_type_id = ?")
+            .bind(note_type_id)
+            .fetch_one(pool.as_ref())
+            .await?
+            .try_get("count")?;
+
+        if in_use > 0 {
+            return Err(anywho::anywho!(
+                "This note type can't be deleted while some notes use it"
+            ));
+        }
+
+        sqlx::query("DELETE FROM note_types WHERE id = ?")
+            .bind(note_type_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns true if the [`NoteType`] can be saved
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty()
+            && !self.fields.is_empty()
+            && self.fields.iter().all(|f| !f.trim().is_empty())
+            && !self.templates.is_empty()
+            && self.templates.iter().all(|t| !t.front.trim().is_empty())
+    }
+
+    /// Ord for a new [`CardTemplate`] of this note type
+    pub fn next_ord(&self) -> u32 {
+        self.templates
+            .iter()
+            .map(|t| t.ord + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Generates the front and back of the given template for the given [`Note`], [`None`] if the front would be empty
+    pub fn render(&self, template: &CardTemplate, note: &Note) -> Option<(String, String)> {
+        let front = self.render_text(&template.front, note);
+        if front.is_empty() {
+            return None;
+        }
+
+        let back = self
+            .render_text(&template.back, note)
+            .replace("{{FrontSide}}", &front);
+
+        Some((front, back))
+    }
+
+    // Helper function to get the index of the field a template text is made of, [`None`] if it has anything else
+    fn single_field(&self, text: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| text.trim() == format!("{{{{{field}}}}}"))
+    }
+
+    // Helper function to replace the field names of a template with the values of the note
+    fn render_text(&self, text: &str, note: &Note) -> String {
+        let mut result = text.to_string();
+        for (index, field) in self.fields.iter().enumerate() {
+            let value = note.fields.get(index).map(|v| v.trim()).unwrap_or_default();
+            result = result.replace(&format!("{{{{{field}}}}}"), value);
+        }
+        result.trim().to_string()
+    }
+}
+
+impl Note {
+    /// Init an empty [`Note`] of the given [`NoteType`]
+    pub fn new(note_type: &NoteType) -> Self {
+        Self {
+            id: None,
+            note_type_id: note_type.id.unwrap_or_default(),
+            fields: vec![String::new(); note_type.fields.len()],
+        }
+    }
+
+    /// Get a single [`Note`] from the database
+    pub async fn get(pool: Arc<Pool<Sqlite>>, note_id: i32) -> Result<Note, anywho::Error> {
+        let mut connection = pool.acquire().await?;
+        Self::fetch(&mut *connection, note_id).await
+    }
+
+    /// Get a single [`Note`] using the given connection (so it can be part of a transaction)
+    pub async fn fetch(
+        connection: &mut SqliteConnection,
+        note_id: i32,
+    ) -> Result<Note, anywho::Error> {
+        let row = sqlx::query("SELECT id, note_type_id, fields FROM notes WHERE id = $1")
+            .bind(note_id)
+            .fetch_one(&mut *connection)
+            .await?;
+
+        Self::from_row(&row)
+    }
+
+    /// Get all notes of the given folder from the database
+    pub async fn get_all(
+        pool: Arc<Pool<Sqlite>>,
+        folder_id: i32,
+    ) -> Result<Vec<Note>, anywho::Error> {
+        let mut rows = sqlx::query(
+            "SELECT id, note_type_id, fields FROM notes WHERE folder_id = $1 ORDER BY id ASC",
+        )
+        .bind(folder_id)
+        .fetch(pool.as_ref());
+
+        let mut result = Vec::<Note>::new();
+
+        while let Some(row) = rows.try_next().await? {
+            result.push(Self::from_row(&row)?);
+        }
+
+        Ok(result)
+    }
+
+    // Helper function to build a [`Note`] from a row of the notes table
+    fn from_row(row: &SqliteRow) -> Result<Note, anywho::Error> {
+        let fields: String = row.try_get("fields")?;
+
+        Ok(Note {
+            id: Some(row.try_get("id")?),
+            note_type_id: row.try_get("note_type_id")?,
+            fields: serde_json::from_str(&fields)?,
+        })
+    }
+
+    /// Add a [`Note`] to the database (into the given folder) and generate it's flashcards
+    pub async fn add(
+        pool: Arc<Pool<Sqlite>>,
+        note: Note,
+        tags: Vec<String>,
+        folder_id: i32,
+        added_at: i64,
+    ) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        let note_id = Self::insert(&mut *transaction, &note, folder_id).await?;
+        let note_type = NoteType::fetch(&mut *transaction, note.note_type_id).await?;
+
+        let note = Note {
+            id: Some(note_id),
+            ..note
+        };
+        Self::sync_flashcards(
+            &mut *transaction,
+            &note_type,
+            &note,
+            folder_id,
+            Some(&tags),
+            added_at,
+            None,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Insert a [`Note`] into the given folder (without generating it's flashcards), returns the new id,
+    /// uses the given connection (so it can be part of a transaction)
+    pub async fn insert(
+        connection: &mut SqliteConnection,
+        note: &Note,
+        folder_id: i32,
+    ) -> Result<i32, anywho::Error> {
+        let note_id = sqlx::query(
+            "INSERT INTO notes (note_type_id, folder_id, fields) VALUES (?, ?, ?) RETURNING id",
+        )
+        .bind(note.note_type_id)
+        .bind(folder_id)
+        .bind(serde_json::to_string(&note.fields)?)
+        .fetch_one(&mut *connection)
+        .await?
+        .try_get::<i32, _>("id")?;
+
+        Ok(note_id)
+    }
+
+    /// Edit a [`Note`] on the database, every flashcard generated by it is updated (keeping it's FSRS data).
+    /// The previous front and back of each flashcard are saved as a [`FlashcardRevision`] alongside the reason of the edit (if any)
+    pub async fn edit(
+        pool: Arc<Pool<Sqlite>>,
+        note: Note,
+        tags: Vec<String>,
+        edited_at: i64,
+        reason: Option<String>,
+    ) -> Result<(), anywho::Error> {
+        let note_id = note
+            .id
+            .ok_or_else(|| anywho::anywho!("Note has not been saved yet"))?;
+
+        let mut transaction = pool.begin().await?;
+        Self::update(&mut *transaction, &note, Some(&tags), edited_at, reason).await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Edit a [`Note`] using the given connection (so it can be part of a transaction), every flashcard generated by it is updated
+    /// (keeping it's FSRS data and saving a [`FlashcardRevision`] if they change). If no tags are given the flashcards keep their current tags
+    pub async fn update(
+        connection: &mut SqliteConnection,
+        note: &Note,
+        tags: Option<&[String]>,
+        edited_at: i64,
+        reason: Option<String>,
+    ) -> Result<(), anywho::Error> {
+        let note_id = note
+            .id
+            .ok_or_else(|| anywho::anywho!("Note has not been saved yet"))?;
+
+        let folder_id: i32 =
+            sqlx::query("UPDATE notes SET fields = $1 WHERE id = $2 RETURNING folder_id")
+                .bind(serde_json::to_string(&note.fields)?)
+                .bind(note_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .try_get("folder_id")?;
+
+        let note_type = NoteType::fetch(&mut *connection, note.note_type_id).await?;
+        Self::sync_flashcards(
+            &mut *connection,
+            &note_type,
+            note,
+            folder_id,
+            tags,
+            edited_at,
+            reason,
+        )
+        .await
+    }
+
+    /// Makes the flashcards of a [`Note`] match it's [`NoteType`] templates: existing flashcards are updated,
+    /// missing ones are created in the given folder and the ones whose front would be empty are moved to the trash.
+    /// If no tags are given the flashcards keep their current tags. The previous wording of the updated flashcards is kept as a
+    /// [`FlashcardRevision`] with the given reason
+    async fn sync_flashcards(
+        connection: &mut SqliteConnection,
+        note_type: &NoteType,
+        note: &Note,
+        folder_id: i32,
+        tags: Option<&[String]>,
+        edited_at: i64,
+        reason: Option<String>,
+    ) -> Result<(), anywho::Error> {
+        let note_id = note
+            .id
+            .ok_or_else(|| anywho::anywho!("Note has not been saved yet"))?;
+
+        let mut existing: Vec<(i32, Option<u32>)> = sqlx::query(
+            "SELECT id, template_ord FROM flashcards WHERE note_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(note_id)
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("id")?, row.try_get("template_ord").ok())))
+        .collect::<Result<_, sqlx::Error>>()?;
+
+        // New flashcards get the tags of their siblings
+        let tags = match tags {
+            Some(tags) => tags.to_vec(),
+            None => {
+                let tags: Option<String> = sqlx::query(&format!(
+                    "SELECT {TAGS_SUBQUERY} AS tags FROM flashcards fc WHERE fc.note_id = $1 LIMIT 1"
+                ))
+                .bind(note_id)
+                .fetch_optional(&mut *connection)
+                .await?
+                .and_then(|row| row.try_get("tags").ok());
+                parse_tags(&tags.unwrap_or_default())
+            }
+        };
+
+        for template in &note_type.templates {
+            let position = existing
+                .iter()
+                .position(|(_, ord)| *ord == Some(template.ord));
+            let flashcard_id = position.map(|index| existing.remove(index).0);
+
+            match (note_type.render(template, note), flashcard_id) {
+                (Some((front, back)), Some(flashcard_id)) => {
+                    let front = FlashcardField::Text(front).to_json()?;
+                    let back = FlashcardField::Text(back).to_json()?;
+
+                    FlashcardRevision::save_if_changed(
+                        &mut *connection,
+                        flashcard_id,
+                        &front,
+                        &back,
+                        edited_at,
+                        reason.clone(),
+                    )
+                    .await?;
+
+                    sqlx::query("UPDATE flashcards SET front = $1, back = $2 WHERE id = $3")
+                        .bind(&front)
+                        .bind(&back)
+                        .bind(flashcard_id)
+                        .execute(&mut *connection)
+                        .await?;
+
+                    Flashcard::set_tags(&mut *connection, flashcard_id, &tags).await?;
+                }
+                (Some((front, back)), None) => {
+                    let flashcard = Flashcard {
+                        front: FlashcardField::Text(front),
+                        back: FlashcardField::Text(back),
+                        note_id: Some(note_id),
+                        template_ord: Some(template.ord),
+                        tags: tags.clone(),
+                        ..Default::default()
+                    };
+                    Flashcard::insert(&mut *connection, &flashcard, folder_id).await?;
+                }
+                (None, Some(flashcard_id)) => {
+                    sqlx::query("UPDATE flashcards SET deleted_at = $1 WHERE id = $2")
+                        .bind(edited_at)
+                        .bind(flashcard_id)
+                        .execute(&mut *connection)
+                        .await?;
+                }
+                (None, None) => {}
+            }
+        }
+
+        // Flashcards of templates that don't exist anymore
+        for (flashcard_id, _) in existing {
+            sqlx::query("UPDATE flashcards SET deleted_at = $1 WHERE id = $2")
+                .bind(edited_at)
+                .bind(flashcard_id)
+                .execute(&mut *connection)
+                .await?;
+        }
+
+        Self::delete_if_empty(&mut *connection, note_id).await
+    }
+
+    /// Deletes the given [`Note`] if it has no flashcards left, uses the given connection (so it can be part of a transaction)
+    pub async fn delete_if_empty(
+        connection: &mut SqliteConnection,
+        note_id: i32,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query(
+            "DELETE FROM notes WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM flashcards WHERE note_id = $1)",
+        )
+        .bind(note_id)
+        .execute(&mut *connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Changes the fields of the [`Note`] so the given template generates the given front and back,
+    /// returns false if it can't be done (a side that changes is not made of a single field)
+    pub fn set_rendered(
+        &mut self,
+        note_type: &NoteType,
+        template: &CardTemplate,
+        front: &str,
+        back: &str,
+    ) -> bool {
+        let (front, back) = (front.trim(), back.trim());
+        let (current_front, current_back) = note_type.render(template, self).unwrap_or_default();
+
+        let mut note = self.clone();
+        for (text, current, new) in [
+            (&template.front, current_front, front),
+            (&template.back, current_back, back),
+        ] {
+            if current == new {
+                continue;
+            }
+
+            match note_type.single_field(text) {
+                Some(index) if index < note.fields.len() => note.fields[index] = new.to_string(),
+                _ => return false,
+            }
+        }
+
+        // Both sides may use the same field
+        if note_type.render(template, &note) != Some((front.to_string(), back.to_string())) {
+            return false;
+        }
+
+        *self = note;
+        true
+    }
+
+    /// Returns true if the [`Note`] would generate at least one flashcard
+    pub fn is_valid(&self, note_type: &NoteType) -> bool {
+        note_type
+            .templates
+            .iter()
+            .any(|template| note_type.render(template, self).is_some())
+    }
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// How the study sets of the nav bar and the folders of a study set are ordered
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum OrderMode {
+    /// Order chosen by the user (saved on the position column)
+    #[default]
+    Manual,
+    /// By name
+    Alphabetical,
+    /// The ones with more due flashcards first
+    MostDue,
+}
+
+impl OrderMode {
+    pub const ALL: [Self; 3] = [Self::Manual, Self::Alphabetical, Self::MostDue];
+
+    /// Sorts the given items (that must already be on their manual order) with this mode, ties keep the manual order
+    pub fn sort<T>(self, items: &mut [T], name: impl Fn(&T) -> &str, due: impl Fn(&T) -> usize) {
+        match self {
+            Self::Manual => {}
+            Self::Alphabetical => items.sort_by_cached_key(|item| name(item).to_lowercase()),
+            Self::MostDue => items.sort_by_key(|item| Reverse(due(item))),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::sync::Arc;
+
+use crate::app::core::models::flashcard::FlashcardField;
+
+/// A previous version of a [`Flashcard`], saved every time it's front or back is edited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlashcardRevision {
+    pub id: Option<i32>,
+    pub flashcard_id: i32,
+    pub front: FlashcardField,
+    pub back: FlashcardField,
+    pub edited_at: i64, // Seconds since epoch
+    pub reason: Option<String>,
+}
+
+impl FlashcardRevision {
+    /// Get the revisions of the given [`Flashcard`], the newest ones go first
+    pub async fn get_all_by_flashcard(
+        pool: Arc<Pool<Sqlite>>,
+        flashcard_id: i32,
+    ) -> Result<Vec<FlashcardRevision>, anywho::Error> {
+        let rows = sqlx::query(
+            "SELECT id, flashcard_id, front, back, edited_at, reason
+             FROM flashcard_revisions
+             WHERE flashcard_id = $1
+             ORDER BY edited_at DESC, id DESC",
+        )
+        .bind(flashcard_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = Vec::<FlashcardRevision>::new();
+
+        for row in rows {
+            result.push(FlashcardRevision {
+                id: Some(row.try_get("id")?),
+                flashcard_id: row.try_get("flashcard_id")?,
+                front: FlashcardField::from_json(&row.try_get::<String, _>("front")?),
+                back: FlashcardField::from_json(&row.try_get::<String, _>("back")?),
+                edited_at: row.try_get("edited_at")?,
+                reason: row.try_get("reason").ok(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Get the revisions of the flashcards of the given [`Folder`], ordered by flashcard and edit time
+    pub async fn get_all_by_folder(
+        pool: Arc<Pool<Sqlite>>,
+        folder_id: i32,
+    ) -> Result<Vec<FlashcardRevision>, anywho::Error> {
+        let rows = sqlx::query(
+            "SELECT r.id, r.flashcard_id, r.front, r.back, r.edited_at, r.reason
+             FROM flashcard_revisions r
+             INNER JOIN flashcards f ON f.id = r.flashcard_id
+             WHERE f.folder_id = $1
+             ORDER BY r.flashcard_id ASC, r.edited_at ASC, r.id ASC",
+        )
+        .bind(folder_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = Vec::<FlashcardRevision>::new();
+
+        for row in rows {
+            result.push(FlashcardRevision {
+                id: Some(row.try_get("id")?),
+                flashcard_id: row.try_get("flashcard_id")?,
+                front: FlashcardField::from_json(&row.try_get::<String, _>("front")?),
+                back: FlashcardField::from_json(&row.try_get::<String, _>("back")?),
+                edited_at: row.try_get("edited_at")?,
+                reason: row.try_get("reason").ok(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Add a [`FlashcardRevision`] to the database using the given connection (so it can be part of a transaction)
+    pub async fn add(
+        connection: &mut SqliteConnection,
+        revision: &FlashcardRevision,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query(
+            "INSERT INTO flashcard_revisions (flashcard_id, front, back, edited_at, reason)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(revision.flashcard_id)
+        .bind(revision.front.to_json()?)
+        .bind(revision.back.to_json()?)
+        .bind(revision.edited_at)
+        .bind(&revision.reason)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saves the current front and back of the given [`Flashcard`] as a revision, only if they are different from the given ones (as json).
+    /// Uses the given connection (so it can be part of the transaction that edits the flashcard)
+    pub async fn save_if_changed(
+        connection: &mut SqliteConnection,
+        flashcard_id: i32,
+        new_front: &str,
+        new_back: &str,
+        edited_at: i64,
+        reason: Option<String>,
+    ) -> Result<(), anywho::Error> {
+        let row = sqlx::query("SELECT front, back FROM flashcards WHERE id = $1")
+            .bind(flashcard_id)
+            .fetch_one(&mut *connection)
+            .await?;
+        let old_front: String = row.try_get("front")?;
+        let old_back: String = row.try_get("back")?;
+
+        if old_front == new_front && old_back == new_back {
+            return Ok(());
+        }
+
+        let revision = FlashcardRevision {
+            id: None,
+            flashcard_id,
+            front: FlashcardField::from_json(&old_front),
+            back: FlashcardField::from_json(&old_back),
+            edited_at,
+            reason: reason.filter(|r| !r.trim().is_empty()),
+        };
+
+        Self::add(connection, &revision).await
+    }
+}
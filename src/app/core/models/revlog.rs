@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::sync::Arc;
+
+use crate::app::core::models::flashcard::{FlashcardStatus, SerializableMemoryState};
+
+/// A single rating given to a [`Flashcard`] while studying, kept so the review history is never lost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevlogEntry {
+    pub id: Option<i32>,
+    pub flashcard_id: i32,
+    pub reviewed_at: i64, // Seconds since epoch
+    pub rating: FlashcardStatus,
+    pub elapsed_days: u32,
+    pub previous_fsrs_state: Option<SerializableMemoryState>,
+    pub new_fsrs_state: SerializableMemoryState,
+    pub interval: i64, // Seconds until the next review
+}
+
+impl RevlogEntry {
+    /// Get the whole review history of the flashcards of the given [`Folder`], ordered by flashcard and review time
+    pub async fn get_all_by_folder(
+        pool: Arc<Pool<Sqlite>>,
+        folder_id: i32,
+    ) -> Result<Vec<RevlogEntry>, anywho::Error> {
+        let mut rows = sqlx::query(
+            "SELECT r.id, r.flashcard_id, r.reviewed_at, r.rating, r.elapsed_days,
+                    r.previous_fsrs_state, r.new_fsrs_state, r.interval
+             FROM revlog r
+             INNER JOIN flashcards f ON f.id = r.flashcard_id
+             WHERE f.folder_id = $1
+             ORDER BY r.flashcard_id ASC, r.reviewed_at ASC, r.id ASC",
+        )
+        .bind(folder_id)
+        .fetch(pool.as_ref());
+
+        let mut result = Vec::<RevlogEntry>::new();
+
+        while let Some(row) = rows.try_next().await? {
+            let id: i32 = row.try_get("id")?;
+            let flashcard_id: i32 = row.try_get("flashcard_id")?;
+            let reviewed_at: i64 = row.try_get("reviewed_at")?;
+            let rating: i32 = row.try_get("rating")?;
+            let elapsed_days: u32 = row.try_get("elapsed_days")?;
+            let previous_fsrs_state: Option<String> = row.try_get("previous_fsrs_state").ok();
+            let new_fsrs_state: String = row.try_get("new_fsrs_state")?;
+            let interval: i64 = row.try_get("interval")?;
+
+            let entry = RevlogEntry {
+                id: Some(id),
+                flashcard_id,
+                reviewed_at,
+                rating: FlashcardStatus::from_id(rating).unwrap_or_default(),
+                elapsed_days,
+                previous_fsrs_state: previous_fsrs_state.and_then(|s| ron::from_str(&s).ok()),
+                new_fsrs_state: ron::from_str(&new_fsrs_state)?,
+                interval,
+            };
+
+            result.push(entry);
+        }
+
+        Ok(result)
+    }
+
+    /// Add a [`RevlogEntry`] to the database using the given connection (so it can be part of a transaction)
+    pub async fn add(
+        connection: &mut SqliteConnection,
+        entry: &RevlogEntry,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query(
+            "INSERT INTO revlog
+             (flashcard_id, reviewed_at, rating, elapsed_days, previous_fsrs_state, new_fsrs_state, interval)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.flashcard_id)
+        .bind(entry.reviewed_at)
+        .bind(entry.rating.to_id())
+        .bind(entry.elapsed_days)
+        .bind(
+            entry
+                .previous_fsrs_state
+                .as_ref()
+                .map(ron::to_string)
+                .transpose()?,
+        )
+        .bind(ron::to_string(&entry.new_fsrs_state)?)
+        .bind(entry.interval)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete the last [`RevlogEntry`] of a flashcard using the given connection (so it can be part of a transaction)
+    pub async fn delete_last(
+        connection: &mut SqliteConnection,
+        flashcard_id: i32,
+    ) -> Result<(), anywho::Error> {
+        sqlx::query(
+            "DELETE FROM revlog WHERE id = (SELECT MAX(id) FROM revlog WHERE flashcard_id = $1)",
+        )
+        .bind(flashcard_id)
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use sqlx::{Pool, Row, Sqlite};
+use std::sync::Arc;
+
+use crate::app::core::models::flashcard::FlashcardField;
+
+/// Max number of results a search returns
+const MAX_RESULTS: i32 = 200;
+
+/// A [`Flashcard`] found by a search, alongside the [`Folder`] and [`StudySet`] it's in
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub flashcard_id: i32,
+    pub front: FlashcardField,
+    pub back: FlashcardField,
+    pub folder_id: i32,
+    pub folder_name: String,
+    pub studyset_id: i32,
+    pub studyset_name: String,
+}
+
+impl SearchResult {
+    /// Searches the text of the flashcards of every [`StudySet`], the most relevant results are ordered by study set and folder
+    /// (and by relevance inside each folder)
+    pub async fn search(
+        pool: Arc<Pool<Sqlite>>,
+        query: String,
+    ) -> Result<Vec<SearchResult>, anywho::Error> {
+        let Some(query) = fts_query(&query) else {
+            return Ok(Vec::new());
+        };
+
+        let rows = sqlx::query(
+            "SELECT * FROM (
+                SELECT fc.id, fc.front, fc.back, f.id AS folder_id, f.name AS folder_name,
+                       s.id AS studyset_id, s.name AS studyset_name, bm25(flashcards_fts) AS relevance
+                FROM flashcards_fts
+                INNER JOIN flashcards fc ON fc.id = flashcards_fts.rowid
+                INNER JOIN folders f ON f.id = fc.folder_id
+                INNER JOIN studysets s ON s.id = f.studyset_id
+                WHERE flashcards_fts MATCH $1
+                    AND fc.deleted_at IS NULL AND f.deleted_at IS NULL AND s.deleted_at IS NULL
+                ORDER BY relevance
+                LIMIT $2
+             )
+             ORDER BY studyset_name COLLATE NOCASE, studyset_id, folder_name COLLATE NOCASE, folder_id, relevance",
+        )
+        .bind(query)
+        .bind(MAX_RESULTS)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut result = Vec::<SearchResult>::new();
+
+        for row in rows {
+            result.push(SearchResult {
+                flashcard_id: row.try_get("id")?,
+                front: FlashcardField::from_json(&row.try_get::<String, _>("front")?),
+                back: FlashcardField::from_json(&row.try_get::<String, _>("back")?),
+                folder_id: row.try_get("folder_id")?,
+                folder_name: row.try_get("folder_name")?,
+                studyset_id: row.try_get("studyset_id")?,
+                studyset_name: row.try_get("studyset_name")?,
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+// Helper function to turn what the user typed into an FTS5 query, every word has to be found (as a prefix).
+// Words are quoted so the FTS5 syntax characters are searched as they are
+fn fts_query(input: &str) -> Option<String> {
+    let words = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<String>>();
+
+    (!words.is_empty()).then(|| words.join(" "))
+}
@@ -5,29 +5,63 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite};
 use std::sync::Arc;
 
+use crate::app::core::{
+    models::{flashcard::Flashcard, ordering::OrderMode},
+    utils::Clock,
+};
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct StudySet {
     pub id: Option<i32>,
     pub name: String,
+    /// Position of the study set on the manual order
+    #[serde(default)]
+    pub position: i32,
 }
 
 impl StudySet {
-    /// Get all [`StudySet`] from the database
-    pub async fn get_all(pool: Arc<Pool<Sqlite>>) -> Result<Vec<StudySet>, anywho::Error> {
-        let mut rows =
-            sqlx::query("SELECT id, name FROM studysets ORDER BY id ASC").fetch(pool.as_ref());
+    /// Get all [`StudySet`] from the database ordered with the given [`OrderMode`]
+    pub async fn get_all(
+        pool: Arc<Pool<Sqlite>>,
+        order: OrderMode,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Vec<StudySet>, anywho::Error> {
+        let mut rows = sqlx::query(
+            "SELECT id, name, position FROM studysets WHERE deleted_at IS NULL ORDER BY position ASC, id ASC",
+        )
+        .fetch(pool.as_ref());
 
         let mut result = Vec::<StudySet>::new();
 
         while let Some(row) = rows.try_next().await? {
             let id: i32 = row.try_get("id")?;
             let name: String = row.try_get("name")?;
+            let position: i32 = row.try_get("position")?;
 
-            let studyset = StudySet { id: Some(id), name };
+            let studyset = StudySet {
+                id: Some(id),
+                name,
+                position,
+            };
 
             result.push(studyset);
         }
 
+        let due_counts = if order == OrderMode::MostDue {
+            Flashcard::count_due_by_studyset(pool, clock.as_ref()).await?
+        } else {
+            Default::default()
+        };
+        order.sort(
+            &mut result,
+            |set| &set.name,
+            |set| {
+                set.id
+                    .and_then(|id| due_counts.get(&id).copied())
+                    .unwrap_or_default()
+            },
+        );
+
         Ok(result)
     }
 
@@ -41,6 +75,23 @@ impl StudySet {
         Ok(())
     }
 
+    /// Saves the manual order of the study sets, the given ids are the study sets in their new order
+    pub async fn reorder(pool: Arc<Pool<Sqlite>>, set_ids: Vec<i32>) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        for (index, set_id) in set_ids.into_iter().enumerate() {
+            sqlx::query("UPDATE studysets SET position = $1 WHERE id = $2")
+                .bind(index as i32 + 1)
+                .bind(set_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
     /// Edit a [`StudySet`] on the database
     pub async fn edit(pool: Arc<Pool<Sqlite>>, studyset: StudySet) -> Result<(), anywho::Error> {
         sqlx::query("UPDATE studysets SET name = $1 WHERE id = $2")
@@ -52,9 +103,14 @@ impl StudySet {
         Ok(())
     }
 
-    /// Delete a [`StudySet`] from the database (and it's folders and the folders flashcards)
-    pub async fn delete(pool: Arc<Pool<Sqlite>>, studyset_id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM studysets WHERE id = ?")
+    /// Moves a [`StudySet`] to the trash, it's only deleted from the database (with it's folders and flashcards) once it's purged
+    pub async fn delete(
+        pool: Arc<Pool<Sqlite>>,
+        studyset_id: i32,
+        deleted_at: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE studysets SET deleted_at = $1 WHERE id = $2")
+            .bind(deleted_at)
             .bind(studyset_id)
             .execute(pool.as_ref())
             .await?;
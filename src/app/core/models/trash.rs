@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use sqlx::{Pool, Row, Sqlite};
+use std::sync::Arc;
+
+use crate::app::core::{
+    models::{flashcard::FlashcardField, note::Note},
+    utils,
+};
+
+/// The different kinds of items that can be on the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashKind {
+    StudySet,
+    Folder,
+    Flashcard,
+}
+
+/// A [`StudySet`], [`Folder`] or [`Flashcard`] that has been moved to the trash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: i32,
+    /// Name of the study set or folder, text of the front for flashcards
+    pub name: String,
+    /// Name of the study set (folders) or folder (flashcards) the item was in, empty for study sets
+    pub location: String,
+    pub deleted_at: i64, // Seconds since epoch
+}
+
+impl TrashItem {
+    /// Get everything that is on the trash from the database, the last deleted items go first.
+    /// Subfolders trashed alongside their parent are not listed (they are restored and purged with it)
+    pub async fn get_all(pool: Arc<Pool<Sqlite>>) -> Result<Vec<TrashItem>, anywho::Error> {
+        let mut result = Vec::<TrashItem>::new();
+
+        let studysets =
+            sqlx::query("SELECT id, name, deleted_at FROM studysets WHERE deleted_at IS NOT NULL")
+                .fetch_all(pool.as_ref())
+                .await?;
+
+        for row in studysets {
+            result.push(TrashItem {
+                kind: TrashKind::StudySet,
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+                location: String::new(),
+                deleted_at: row.try_get("deleted_at")?,
+            });
+        }
+
+        let folders = sqlx::query(
+            "SELECT f.id, f.name, f.deleted_at, s.name AS location
+             FROM folders f
+             INNER JOIN studysets s ON s.id = f.studyset_id
+             LEFT JOIN folders p ON p.id = f.parent_id
+             WHERE f.deleted_at IS NOT NULL AND (p.deleted_at IS NULL OR p.deleted_at != f.deleted_at)",
+        )
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        for row in folders {
+            result.push(TrashItem {
+                kind: TrashKind::Folder,
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+                location: row.try_get("location")?,
+                deleted_at: row.try_get("deleted_at")?,
+            });
+        }
+
+        let flashcards = sqlx::query(
+            "SELECT fc.id, fc.front, fc.deleted_at, f.name AS location
+             FROM flashcards fc
+             INNER JOIN folders f ON f.id = fc.folder_id
+             WHERE fc.deleted_at IS NOT NULL",
+        )
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        for row in flashcards {
+            let front = FlashcardField::from_json(&row.try_get::<String, _>("front")?);
+            result.push(TrashItem {
+                kind: TrashKind::Flashcard,
+                id: row.try_get("id")?,
+                name: front.plain_text().to_string(),
+                location: row.try_get("location")?,
+                deleted_at: row.try_get("deleted_at")?,
+            });
+        }
+
+        result.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+
+        Ok(result)
+    }
+
+    /// Takes the given [`TrashItem`] out of the trash (a folder also brings back the subfolders trashed alongside it).
+    /// The folders and study set a folder or flashcard is in are restored too, so it's not hidden by a trashed parent
+    pub async fn restore(pool: Arc<Pool<Sqlite>>, item: TrashItem) -> Result<(), anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        let folder_id: Option<i32> = match item.kind {
+            TrashKind::StudySet => {
+                sqlx::query("UPDATE studysets SET deleted_at = NULL WHERE id = $1")
+                    .bind(item.id)
+                    .execute(&mut *transaction)
+                    .await?;
+                None
+            }
+            TrashKind::Folder => {
+                sqlx::query(
+                    "WITH RECURSIVE tree(id) AS (
+                        SELECT $1
+                        UNION
+                        SELECT f.id FROM folders f INNER JOIN tree ON f.parent_id = tree.id WHERE f.deleted_at = $2
+                     )
+                     UPDATE folders SET deleted_at = NULL WHERE id IN (SELECT id FROM tree)",
+                )
+                .bind(item.id)
+                .bind(item.deleted_at)
+                .execute(&mut *transaction)
+                .await?;
+                Some(item.id)
+            }
+            TrashKind::Flashcard => sqlx::query(
+                "UPDATE flashcards SET deleted_at = NULL WHERE id = $1 RETURNING folder_id",
+            )
+            .bind(item.id)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .and_then(|row| row.try_get("folder_id").ok()),
+        };
+
+        if let Some(folder_id) = folder_id {
+            sqlx::query(
+                "WITH RECURSIVE ancestors(id) AS (
+                    SELECT $1
+                    UNION
+                    SELECT f.parent_id FROM folders f INNER JOIN ancestors ON f.id = ancestors.id
+                    WHERE f.parent_id IS NOT NULL
+                 )
+                 UPDATE folders SET deleted_at = NULL WHERE id IN (SELECT id FROM ancestors)",
+            )
+            .bind(folder_id)
+            .execute(&mut *transaction)
+            .await?;
+
+            sqlx::query(
+                "UPDATE studysets SET deleted_at = NULL WHERE id = (SELECT studyset_id FROM folders WHERE id = $1)",
+            )
+            .bind(folder_id)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Deletes the given [`TrashItem`] from the database for good (with everything it contains),
+    /// the images and audios of the deleted flashcards are removed too. Returns the ids of the deleted folders
+    pub async fn purge(
+        pool: Arc<Pool<Sqlite>>,
+        item: TrashItem,
+    ) -> Result<Vec<i32>, anywho::Error> {
+        let mut transaction = pool.begin().await?;
+
+        // Folders that are going to be deleted (alongside their subfolders)
+        let folder_ids: Vec<i32> = match item.kind {
+            TrashKind::StudySet => {
+                sqlx::query("SELECT id FROM folders WHERE studyset_id = $1")
+                    .bind(item.id)
+                    .fetch_all(&mut *transaction)
+                    .await?
+            }
+            TrashKind::Folder => {
+                sqlx::query(
+                    "WITH RECURSIVE tree(id) AS (
+                        SELECT $1
+                        UNION
+                        SELECT f.id FROM folders f INNER JOIN tree ON f.parent_id = tree.id
+                     )
+                     SELECT id FROM tree",
+                )
+                .bind(item.id)
+                .fetch_all(&mut *transaction)
+                .await?
+            }
+            TrashKind::Flashcard => Vec::new(),
+        }
+        .iter()
+        .map(|row| row.try_get("id"))
+        .collect::<Result<_, sqlx::Error>>()?;
+
+        // Sides of every flashcard (and it's revisions) that is going to be deleted
+        let sides = match item.kind {
+            TrashKind::StudySet => {
+                sqlx::query(
+                    "SELECT fc.front, fc.back FROM flashcards fc
+                     INNER JOIN folders f ON f.id = fc.folder_id
+                     WHERE f.studyset_id = $1
+                     UNION ALL
+                     SELECT r.front, r.back FROM flashcard_revisions r
+                     INNER JOIN flashcards fc ON fc.id = r.flashcard_id
+                     INNER JOIN folders f ON f.id = fc.folder_id
+                     WHERE f.studyset_id = $1",
+                )
+                .bind(item.id)
+                .fetch_all(&mut *transaction)
+                .await?
+            }
+            TrashKind::Folder => {
+                sqlx::query(
+                    "WITH RECURSIVE tree(id) AS (
+                        SELECT $1
+                        UNION
+                        SELECT f.id FROM folders f INNER JOIN tree ON f.parent_id = tree.id
+                     )
+                     SELECT front, back FROM flashcards WHERE folder_id IN (SELECT id FROM tree)
+                     UNION ALL
+                     SELECT r.front, r.back FROM flashcard_revisions r
+                     INNER JOIN flashcards fc ON fc.id = r.flashcard_id
+                     WHERE fc.folder_id IN (SELECT id FROM tree)",
+                )
+                .bind(item.id)
+                .fetch_all(&mut *transaction)
+                .await?
+            }
+            TrashKind::Flashcard => {
+                sqlx::query(
+                    "SELECT front, back FROM flashcards WHERE id = $1
+                     UNION ALL
+                     SELECT front, back FROM flashcard_revisions WHERE flashcard_id = $1",
+                )
+                .bind(item.id)
+                .fetch_all(&mut *transaction)
+                .await?
+            }
+        };
+
+        let mut fields = Vec::<FlashcardField>::new();
+        for row in sides {
+            fields.push(FlashcardField::from_json(
+                &row.try_get::<String, _>("front")?,
+            ));
+            fields.push(FlashcardField::from_json(
+                &row.try_get::<String, _>("back")?,
+            ));
+        }
+
+        // Deleting a study set or folder also deletes it's contents
+        match item.kind {
+            TrashKind::StudySet => {
+                sqlx::query("DELETE FROM studysets WHERE id = $1")
+                    .bind(item.id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            TrashKind::Folder => {
+                sqlx::query("DELETE FROM folders WHERE id = $1")
+                    .bind(item.id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            TrashKind::Flashcard => {
+                let note_id: Option<i32> =
+                    sqlx::query("DELETE FROM flashcards WHERE id = $1 RETURNING note_id")
+                        .bind(item.id)
+                        .fetch_optional(&mut *transaction)
+                        .await?
+                        .and_then(|row| row.try_get("note_id").ok());
+
+                // The note goes away with it's last flashcard
+                if let Some(note_id) = note_id {
+                    Note::delete_if_empty(&mut *transaction, note_id).await?;
+                }
+            }
+        }
+
+        transaction.commit().await?;
+
+        // Files are shared between copies and revisions, they are only removed once nothing references them.
+        // Only the copies Oboete keeps on it's data directory are removed, files from anywhere else are left alone.
+        // A missing file should not stop the rest from being removed
+        let mut removed_paths = Vec::<&String>::new();
+        for field in fields.iter().flat_map(|field| field.blocks()) {
+            let (path, is_image) = match field {
+                FlashcardField::Image { path, .. } if utils::check_path(path) => (path, true),
+                FlashcardField::Audio { path, .. } if utils::check_audio_path(path) => {
+                    (path, false)
+                }
+                _ => continue,
+            };
+
+            if removed_paths.contains(&path) {
+                continue;
+            }
+            removed_paths.push(path);
+
+            let result = match is_media_referenced(pool.as_ref(), path).await {
+                Ok(true) => Ok(()),
+                Ok(false) if is_image => utils::delete_image(path.clone()).await,
+                Ok(false) => utils::delete_audio(path.clone()).await,
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                eprintln!("{}", e);
+            }
+        }
+
+        Ok(folder_ids)
+    }
+
+    /// Purges everything on the trash, if given only the items deleted before that time (seconds since epoch).
+    /// Returns the ids of the deleted folders
+    pub async fn purge_all(
+        pool: Arc<Pool<Sqlite>>,
+        deleted_before: Option<i64>,
+    ) -> Result<Vec<i32>, anywho::Error> {
+        let items = Self::get_all(pool.clone()).await?;
+        let mut folder_ids = Vec::<i32>::new();
+
+        for item in items
+            .into_iter()
+            .filter(|item| deleted_before.is_none_or(|before| item.deleted_at < before))
+        {
+            folder_ids.extend(Self::purge(pool.clone(), item).await?);
+        }
+
+        Ok(folder_ids)
+    }
+}
+
+// Helper function to know if any flashcard (even on the trash) or revision still references the given image or audio path
+async fn is_media_referenced(pool: &Pool<Sqlite>, path: &str) -> Result<bool, anywho::Error> {
+    let referenced: bool = sqlx::query(
+        "SELECT EXISTS (
+            SELECT 1 FROM (
+                SELECT front AS side FROM flashcards
+                UNION ALL SELECT back FROM flashcards
+                UNION ALL SELECT front FROM flashcard_revisions
+                UNION ALL SELECT back FROM flashcard_revisions
+            ) sides, json_tree(CASE WHEN json_valid(sides.side) THEN sides.side ELSE json_quote(sides.side) END) tree
+            WHERE instr(sides.side, $2) > 0 AND tree.key = 'path' AND tree.value = $1
+         ) AS referenced",
+    )
+    .bind(path)
+    .bind(
+        std::path::Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+    )
+    .fetch_one(pool)
+    .await?
+    .try_get("referenced")?;
+
+    Ok(referenced)
+}
@@ -3,16 +3,32 @@
 const APP_ID: &str = "dev.mariinkys.Oboete";
 
 mod archive_manager;
+mod audio;
+mod clock;
+pub mod cloze;
+pub mod diff;
 mod flashcards;
 pub mod fsrs_scheduler;
 mod images;
+pub mod math;
+mod media;
 mod toast;
 
 pub use archive_manager::backup_oboete;
 pub use archive_manager::import_oboete;
-pub use flashcards::current_day;
+pub use audio::AUDIO_EXTENSIONS;
+pub use audio::check_audio_path;
+pub use audio::delete_audio;
+pub use audio::play_audio;
+pub use audio::save_audio;
+pub use clock::Clock;
+#[cfg(test)]
+pub use clock::FixedClock;
+pub use clock::SystemClock;
+pub use flashcards::ScheduledReview;
 pub use flashcards::export_flashcards;
 pub use flashcards::export_flashcards_anki;
+pub use flashcards::import_anki_flashcards;
 pub use flashcards::parse_ankifile;
 pub use flashcards::parse_import_content;
 pub use flashcards::update_fsrs_data;
@@ -1,17 +1,35 @@
 use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite};
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use super::fsrs_scheduler;
 use crate::app::core::models::{
-    flashcard::{Flashcard, FlashcardField, FlashcardStatus},
+    flashcard::{Flashcard, FlashcardField, FlashcardStatus, TAGS_SUBQUERY, parse_tags},
     folder::Folder,
+    note::{Note, NoteType},
+    revision::FlashcardRevision,
+    revlog::RevlogEntry,
     studyset::StudySet,
 };
 
+/// Current version of the backup format
+/// 0: due dates are days since epoch
+/// 1: due dates are seconds since epoch
+/// 2: the review history (revlog) and the revisions of the flashcards are included
+const BACKUP_VERSION: u32 = 2;
+
 #[derive(Serialize, Deserialize)]
 struct BackupData {
+    #[serde(default)]
+    version: u32,
     study_sets: Vec<BackupStudySet>,
+    #[serde(default)]
+    note_types: Vec<NoteType>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -24,6 +42,12 @@ struct BackupStudySet {
 struct BackupFolder {
     folder: Folder,
     flashcards: Vec<Flashcard>,
+    #[serde(default)]
+    notes: Vec<Note>,
+    #[serde(default)]
+    revlog: Vec<RevlogEntry>,
+    #[serde(default)]
+    revisions: Vec<FlashcardRevision>,
 }
 
 /// Backup all of the data of the application into a .ron file that get's saved on the given path
@@ -31,20 +55,25 @@ pub async fn backup_oboete(
     pool: Arc<Pool<Sqlite>>,
     file_path: String,
 ) -> Result<(), anywho::Error> {
-    let mut rows = sqlx::query(
+    let mut rows = sqlx::query(&format!(
         r#"
             SELECT
-                s.id AS studyset_id, s.name AS studyset_name,
-                f.id AS folder_id, f.name AS folder_name, 
-                f.desired_retention AS desired_retention,
+                s.id AS studyset_id, s.name AS studyset_name, s.position AS studyset_position,
+                f.id AS folder_id, f.name AS folder_name, f.position AS folder_position,
+                f.desired_retention AS desired_retention, f.fsrs_parameters,
+                f.learning_steps, f.relearning_steps, f.parent_id,
+                f.leech_threshold, f.leech_suspend,
                 fc.id AS flashcard_id, fc.front, fc.back, fc.status,
-                fc.fsrs_state, fc.due_date, fc.last_reviewed
+                fc.fsrs_state, fc.due_date, fc.last_reviewed, fc.learning_step, fc.relearning,
+                fc.note_id, fc.template_ord, fc.suspended, fc.buried_until, fc.lapses,
+                {TAGS_SUBQUERY} AS tags
             FROM studysets s
-            LEFT JOIN folders f ON s.id = f.studyset_id
-            LEFT JOIN flashcards fc ON f.id = fc.folder_id
-            ORDER BY s.id, f.id, fc.id
-            "#,
-    )
+            LEFT JOIN folders f ON s.id = f.studyset_id AND f.deleted_at IS NULL
+            LEFT JOIN flashcards fc ON f.id = fc.folder_id AND fc.deleted_at IS NULL
+            WHERE s.deleted_at IS NULL
+            ORDER BY s.position, s.id, f.position, f.id, fc.id
+            "#
+    ))
     .fetch(pool.as_ref());
 
     let mut study_sets: Vec<BackupStudySet> = Vec::new();
@@ -53,10 +82,18 @@ pub async fn backup_oboete(
     while let Some(row) = rows.try_next().await? {
         let studyset_id: i32 = row.try_get("studyset_id")?;
         let studyset_name: String = row.try_get("studyset_name")?;
+        let studyset_position: i32 = row.try_get("studyset_position")?;
 
         let folder_id: Option<i32> = row.try_get("folder_id").ok();
         let folder_name: Option<String> = row.try_get("folder_name").ok();
         let desired_retention: Option<f32> = row.try_get("desired_retention").ok();
+        let fsrs_parameters: Option<String> = row.try_get("fsrs_parameters").ok();
+        let learning_steps: Option<String> = row.try_get("learning_steps").ok();
+        let relearning_steps: Option<String> = row.try_get("relearning_steps").ok();
+        let parent_id: Option<i32> = row.try_get("parent_id").ok();
+        let leech_threshold: Option<u32> = row.try_get("leech_threshold").ok();
+        let leech_suspend: Option<bool> = row.try_get("leech_suspend").ok();
+        let folder_position: Option<i32> = row.try_get("folder_position").ok();
         let flashcard_id: Option<i32> = row.try_get("flashcard_id").ok();
 
         if current_studyset.is_none()
@@ -69,6 +106,7 @@ pub async fn backup_oboete(
                 study_set: StudySet {
                     id: Some(studyset_id),
                     name: studyset_name.clone(),
+                    position: studyset_position,
                 },
                 folders: Vec::new(),
             });
@@ -98,8 +136,18 @@ pub async fn backup_oboete(
                         id: Some(folder_id),
                         name: folder_name,
                         desired_retention,
+                        fsrs_parameters: fsrs_parameters.and_then(|p| ron::from_str(&p).ok()),
+                        learning_steps: learning_steps.unwrap_or_default(),
+                        relearning_steps: relearning_steps.unwrap_or_default(),
+                        parent_id,
+                        leech_threshold: leech_threshold.unwrap_or_default(),
+                        leech_suspend: leech_suspend.unwrap_or_default(),
+                        position: folder_position.unwrap_or_default(),
                     },
                     flashcards: Vec::new(),
+                    notes: Vec::new(),
+                    revlog: Vec::new(),
+                    revisions: Vec::new(),
                 };
                 current_studyset.folders.push(new_folder);
                 current_studyset.folders.len() - 1
@@ -112,17 +160,33 @@ pub async fn backup_oboete(
                     let back: String = row.try_get("back")?;
                     let status: i32 = row.try_get("status")?;
                     let fsrs_state: Option<String> = row.try_get("fsrs_state").ok();
-                    let due_date: Option<i32> = row.try_get("due_date").ok();
-                    let last_reviewed: Option<i32> = row.try_get("last_reviewed").ok();
+                    let due_date: Option<i64> = row.try_get("due_date").ok();
+                    let last_reviewed: Option<i64> = row.try_get("last_reviewed").ok();
+                    let learning_step: Option<u32> = row.try_get("learning_step").ok();
+                    let relearning: bool = row.try_get("relearning")?;
+                    let tags: Option<String> = row.try_get("tags").ok();
+                    let note_id: Option<i32> = row.try_get("note_id").ok();
+                    let template_ord: Option<u32> = row.try_get("template_ord").ok();
+                    let suspended: bool = row.try_get("suspended")?;
+                    let buried_until: Option<i32> = row.try_get("buried_until").ok();
+                    let lapses: u32 = row.try_get("lapses")?;
 
                     let flashcard = Flashcard {
                         id: Some(flashcard_id),
-                        front: FlashcardField::from_ron(front)?,
-                        back: FlashcardField::from_ron(back)?,
+                        front: FlashcardField::from_json(&front),
+                        back: FlashcardField::from_json(&back),
                         status: FlashcardStatus::from_id(status).unwrap_or_default(),
                         fsrs_state: fsrs_state.and_then(|s| ron::from_str(&s).ok()),
                         due_date,
                         last_reviewed,
+                        learning_step,
+                        relearning,
+                        tags: parse_tags(&tags.unwrap_or_default()),
+                        note_id,
+                        template_ord,
+                        suspended,
+                        buried_until,
+                        lapses,
                     };
 
                     current_studyset.folders[folder_index]
@@ -137,7 +201,64 @@ pub async fn backup_oboete(
         study_sets.push(studyset);
     }
 
-    let backup_data = BackupData { study_sets };
+    for backup_folder in study_sets.iter_mut().flat_map(|s| s.folders.iter_mut()) {
+        if let Some(folder_id) = backup_folder.folder.id {
+            backup_folder.notes = Note::get_all(pool.clone(), folder_id).await?;
+
+            // Only the history and revisions of the flashcards that are backed up (not on the trash)
+            let flashcard_ids: HashSet<i32> = backup_folder
+                .flashcards
+                .iter()
+                .filter_map(|fc| fc.id)
+                .collect();
+            backup_folder.revlog = RevlogEntry::get_all_by_folder(pool.clone(), folder_id)
+                .await?
+                .into_iter()
+                .filter(|entry| flashcard_ids.contains(&entry.flashcard_id))
+                .collect();
+            backup_folder.revisions = FlashcardRevision::get_all_by_folder(pool.clone(), folder_id)
+                .await?
+                .into_iter()
+                .filter(|revision| flashcard_ids.contains(&revision.flashcard_id))
+                .collect();
+        }
+    }
+
+    // Audio files are copied next to the backup, so it can be restored on another device
+    let audio_paths: Vec<&String> = study_sets
+        .iter()
+        .flat_map(|s| &s.folders)
+        .flat_map(|f| {
+            f.flashcards
+                .iter()
+                .flat_map(|fc| [&fc.front, &fc.back])
+                .chain(f.revisions.iter().flat_map(|r| [&r.front, &r.back]))
+        })
+        .flat_map(|field| field.blocks())
+        .filter_map(|field| match field {
+            FlashcardField::Audio { path, .. } if !path.is_empty() => Some(path),
+            _ => None,
+        })
+        .collect();
+
+    if !audio_paths.is_empty() {
+        let media_dir = media_dir(&file_path);
+        tokio::fs::create_dir_all(&media_dir).await?;
+
+        for path in audio_paths {
+            if let Some(file_name) = Path::new(path).file_name()
+                && let Err(e) = tokio::fs::copy(path, media_dir.join(file_name)).await
+            {
+                eprintln!("Failed to backup audio {path}: {e}");
+            }
+        }
+    }
+
+    let backup_data = BackupData {
+        version: BACKUP_VERSION,
+        study_sets,
+        note_types: NoteType::get_all(pool.clone()).await?,
+    };
     let ron_string = ron::ser::to_string_pretty(&backup_data, ron::ser::PrettyConfig::default())
         .map_err(|e| anywho::Error::msg(format!("Failed to serialize to RON: {}", e)))?;
 
@@ -153,50 +274,174 @@ pub async fn import_oboete(
     pool: Arc<Pool<Sqlite>>,
     file_path: String,
 ) -> Result<(), anywho::Error> {
-    let ron_string = tokio::fs::read_to_string(file_path).await?;
+    let ron_string = tokio::fs::read_to_string(&file_path).await?;
     let backup_data: BackupData = ron::from_str(&ron_string)?;
+    let media_dir = media_dir(&file_path);
 
+    let existing_note_types = NoteType::get_all(pool.clone()).await?;
     let mut transaction = pool.begin().await?;
 
-    for backup_studyset in backup_data.study_sets {
+    // Map backup note_type_id → note_type_id, note types that already exist (same name and fields) are reused
+    let mut note_type_id_map = HashMap::new();
+    for note_type in &backup_data.note_types {
+        let Some(backup_id) = note_type.id else {
+            continue;
+        };
+
+        let note_type_id = match existing_note_types
+            .iter()
+            .find(|t| t.name == note_type.name && t.fields == note_type.fields)
+            .and_then(|t| t.id)
+        {
+            Some(id) => id,
+            None => NoteType::insert(&mut *transaction, note_type).await?,
+        };
+        note_type_id_map.insert(backup_id, note_type_id);
+    }
+
+    // Study sets are inserted on their backup order, after the existing ones
+    let mut study_sets = backup_data.study_sets;
+    study_sets.sort_by_key(|s| s.study_set.position);
+
+    for backup_studyset in study_sets {
         let studyset_id = sqlx::query("INSERT INTO studysets (name) VALUES (?) RETURNING id")
             .bind(&backup_studyset.study_set.name)
             .fetch_one(&mut *transaction)
             .await?
             .try_get::<i32, _>("id")?;
 
-        for backup_folder in backup_studyset.folders {
+        // Map backup folder_id → new folder_id (to restore the folder tree)
+        let mut folder_id_map = HashMap::new();
+
+        for backup_folder in backup_studyset.folders.iter() {
+            // Invalid FSRS parameters are not imported, the folder goes back to the default ones
+            let fsrs_parameters = backup_folder.folder.fsrs_parameters.as_ref().filter(|p| {
+                fsrs_scheduler::validate_parameters(p)
+                    .inspect_err(|e| eprintln!("{} ({})", e, backup_folder.folder.name))
+                    .is_ok()
+            });
+
             let folder_id =
-                sqlx::query("INSERT INTO folders (name, studyset_id, desired_retention) VALUES (?, ?, ?) RETURNING id")
+                sqlx::query("INSERT INTO folders (name, studyset_id, desired_retention, fsrs_parameters, learning_steps, relearning_steps, leech_threshold, leech_suspend, position) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id")
                     .bind(&backup_folder.folder.name)
                     .bind(studyset_id)
                     .bind(backup_folder.folder.desired_retention)
+                    .bind(fsrs_parameters.and_then(|p| ron::to_string(p).ok()))
+                    .bind(&backup_folder.folder.learning_steps)
+                    .bind(&backup_folder.folder.relearning_steps)
+                    .bind(backup_folder.folder.leech_threshold)
+                    .bind(backup_folder.folder.leech_suspend)
+                    .bind(backup_folder.folder.position)
                     .fetch_one(&mut *transaction)
                     .await?
                     .try_get::<i32, _>("id")?;
 
-            for flashcard in backup_folder.flashcards {
-                sqlx::query(
-                    r#"
-                        INSERT INTO flashcards 
-                        (front, back, status, fsrs_state, due_date, last_reviewed, folder_id) 
-                        VALUES (?, ?, ?, ?, ?, ?, ?)
-                        "#,
-                )
-                .bind(&flashcard.front.to_ron()?)
-                .bind(&flashcard.back.to_ron()?)
-                .bind(flashcard.status.to_id())
-                .bind(
-                    flashcard
-                        .fsrs_state
-                        .as_ref()
-                        .and_then(|s| ron::to_string(s).ok()),
-                )
-                .bind(flashcard.due_date)
-                .bind(flashcard.last_reviewed)
-                .bind(folder_id)
-                .execute(&mut *transaction)
-                .await?;
+            if let Some(backup_id) = backup_folder.folder.id {
+                folder_id_map.insert(backup_id, folder_id);
+            }
+        }
+
+        for backup_folder in backup_studyset.folders {
+            let Some(folder_id) = backup_folder
+                .folder
+                .id
+                .and_then(|id| folder_id_map.get(&id).copied())
+            else {
+                continue;
+            };
+
+            // Parents may have been created after their subfolders, so they are linked once all exist
+            if let Some(parent_id) = backup_folder
+                .folder
+                .parent_id
+                .and_then(|id| folder_id_map.get(&id))
+            {
+                sqlx::query("UPDATE folders SET parent_id = $1 WHERE id = $2")
+                    .bind(parent_id)
+                    .bind(folder_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+
+            // Map backup note_id → note_id
+            let mut note_id_map = HashMap::new();
+            for note in &backup_folder.notes {
+                let (Some(backup_id), Some(note_type_id)) =
+                    (note.id, note_type_id_map.get(&note.note_type_id))
+                else {
+                    continue;
+                };
+
+                let note = Note {
+                    note_type_id: *note_type_id,
+                    ..note.clone()
+                };
+                let note_id = Note::insert(&mut *transaction, &note, folder_id).await?;
+                note_id_map.insert(backup_id, note_id);
+            }
+
+            // Map backup flashcard_id → flashcard_id (to restore the review history and revisions)
+            let mut flashcard_id_map = HashMap::new();
+
+            for mut flashcard in backup_folder.flashcards {
+                if backup_data.version < 1 {
+                    flashcard.due_date = flashcard.due_date.map(|day| day * 86400);
+                    flashcard.last_reviewed = flashcard.last_reviewed.map(|day| day * 86400);
+                }
+
+                // Flashcards whose note is missing from the backup become plain flashcards
+                flashcard.note_id = flashcard
+                    .note_id
+                    .and_then(|id| note_id_map.get(&id).copied());
+                if flashcard.note_id.is_none() {
+                    flashcard.template_ord = None;
+                }
+
+                for field in [&mut flashcard.front, &mut flashcard.back]
+                    .into_iter()
+                    .flat_map(|field| field.blocks_mut())
+                {
+                    if let FlashcardField::Audio { path, .. } = field {
+                        restore_audio(path, &media_dir);
+                    }
+                }
+
+                let flashcard_id =
+                    Flashcard::insert(&mut *transaction, &flashcard, folder_id).await?;
+                if let Some(backup_id) = flashcard.id {
+                    flashcard_id_map.insert(backup_id, flashcard_id);
+                }
+            }
+
+            for entry in backup_folder.revlog {
+                let Some(flashcard_id) = flashcard_id_map.get(&entry.flashcard_id).copied() else {
+                    continue;
+                };
+
+                let entry = RevlogEntry {
+                    flashcard_id,
+                    ..entry
+                };
+                RevlogEntry::add(&mut *transaction, &entry).await?;
+            }
+
+            for mut revision in backup_folder.revisions {
+                let Some(flashcard_id) = flashcard_id_map.get(&revision.flashcard_id).copied()
+                else {
+                    continue;
+                };
+
+                for field in [&mut revision.front, &mut revision.back]
+                    .into_iter()
+                    .flat_map(|field| field.blocks_mut())
+                {
+                    if let FlashcardField::Audio { path, .. } = field {
+                        restore_audio(path, &media_dir);
+                    }
+                }
+
+                revision.flashcard_id = flashcard_id;
+                FlashcardRevision::add(&mut *transaction, &revision).await?;
             }
         }
     }
@@ -205,3 +450,24 @@ pub async fn import_oboete(
 
     Ok(())
 }
+
+/// Directory where the audio files of the given backup file are copied
+fn media_dir(file_path: &str) -> PathBuf {
+    PathBuf::from(format!("{file_path}.media"))
+}
+
+// Helper function to save a new copy of a backed up audio, from the backup media directory if present or from it's original path.
+// Every imported flashcard gets it's own copy so deleting one never breaks another
+fn restore_audio(path: &mut String, media_dir: &Path) {
+    let source = Path::new(path.as_str())
+        .file_name()
+        .map(|name| media_dir.join(name))
+        .filter(|backup_copy| backup_copy.exists())
+        .map(|backup_copy| backup_copy.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    match super::save_audio(&source) {
+        Ok(new_path) => *path = new_path,
+        Err(e) => eprintln!("Failed to restore audio {source}: {e}"),
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use std::{
+    fs,
+    io::BufReader,
+    sync::{Arc, Mutex},
+};
+
+use super::media::{self, MediaKind};
+
+/// Extensions of the audio files that can be attached to a flashcard
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "ogg", "wav", "flac"];
+
+/// Returns true if the path corresponds to an audio already saved in the Oboete directory
+pub fn check_audio_path(audio_path: &String) -> bool {
+    media::is_managed(MediaKind::Audio, audio_path)
+}
+
+/// Attempts to save the audio of the given path on the standard oboete output path
+pub fn save_audio(audio_path: &String) -> Result<String, anywho::Error> {
+    media::save_media(MediaKind::Audio, audio_path)
+}
+
+/// Deletes the audio of the given path
+pub async fn delete_audio(audio_path: String) -> Result<(), anywho::Error> {
+    media::delete_media(MediaKind::Audio, audio_path).await
+}
+
+// Sink of the audio that is playing right now, so it can be stopped when another one starts
+static PLAYING_SINK: Mutex<Option<Arc<rodio::Sink>>> = Mutex::new(None);
+
+/// Plays the audio of the given path on the default output device (stopping any other audio), finishes when the audio ends
+pub async fn play_audio(audio_path: String) -> Result<(), anywho::Error> {
+    // The output stream can't be sent between threads, so everything happens on a blocking one
+    tokio::task::spawn_blocking(move || -> Result<(), anywho::Error> {
+        let file = fs::File::open(&audio_path)?;
+        let source = rodio::Decoder::new(BufReader::new(file))?;
+
+        let (_stream, handle) = rodio::OutputStream::try_default()?;
+        let sink = Arc::new(rodio::Sink::try_new(&handle)?);
+
+        if let Some(previous) = PLAYING_SINK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(Arc::clone(&sink))
+        {
+            previous.stop();
+        }
+
+        sink.append(source);
+        sink.sleep_until_end();
+
+        let mut playing = PLAYING_SINK.lock().unwrap_or_else(|e| e.into_inner());
+        if playing.as_ref().is_some_and(|p| Arc::ptr_eq(p, &sink)) {
+            *playing = None;
+        }
+
+        Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Source of the current time and of the study day a moment belongs to.
+/// Everything that schedules flashcards gets it from here so it can be swapped for a fixed one.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// Current seconds since epoch
+    fn now(&self) -> i64;
+
+    /// Study day (days since epoch) the given seconds since epoch belong to
+    fn day_of(&self, timestamp: i64) -> i32;
+
+    /// Current study day
+    fn today(&self) -> i32 {
+        self.day_of(self.now())
+    }
+
+    /// First second (since epoch) of the next study day
+    fn next_day_start(&self) -> i64 {
+        let today = self.today();
+
+        // Days are not always 24 hours long (daylight saving time), so the first second of the next day is searched
+        let (mut low, mut high) = (self.now(), self.now() + 2 * 86400);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.day_of(middle) > today {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        low
+    }
+}
+
+/// [`Clock`] using the system time, days roll over at the given local hour.
+/// The hour can be changed while it's shared, so every open screen sees the new one
+#[derive(Debug)]
+pub struct SystemClock {
+    next_day_starts_at: AtomicU8,
+}
+
+impl SystemClock {
+    /// Init a new [`SystemClock`] where days start at the given local hour (0-23)
+    pub fn new(next_day_starts_at: u8) -> Self {
+        Self {
+            next_day_starts_at: AtomicU8::new(next_day_starts_at.min(23)),
+        }
+    }
+
+    /// Change the local hour (0-23) where days start
+    pub fn set_next_day_starts_at(&self, next_day_starts_at: u8) {
+        self.next_day_starts_at
+            .store(next_day_starts_at.min(23), Ordering::Relaxed);
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+
+    fn day_of(&self, timestamp: i64) -> i32 {
+        match DateTime::from_timestamp(timestamp, 0) {
+            Some(date) => study_day(
+                date.with_timezone(&Local).naive_local(),
+                self.next_day_starts_at.load(Ordering::Relaxed),
+            ),
+            None => timestamp.div_euclid(86400) as i32,
+        }
+    }
+}
+
+// Helper function to get the days since epoch of a local date, moments before the rollover hour belong to the day before
+fn study_day(local: NaiveDateTime, next_day_starts_at: u8) -> i32 {
+    let shifted = local - TimeDelta::hours(next_day_starts_at as i64);
+    (shifted.date() - DateTime::UNIX_EPOCH.date_naive()).num_days() as i32
+}
+
+/// [`Clock`] stuck on a given moment, days roll over at the given UTC hour so it doesn't depend on the local timezone
+#[cfg(test)]
+#[derive(Debug)]
+pub struct FixedClock {
+    pub now: i64,
+    pub next_day_starts_at: u8,
+}
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.now
+    }
+
+    fn day_of(&self, timestamp: i64) -> i32 {
+        let date = DateTime::from_timestamp(timestamp, 0).expect("timestamp out of range");
+        study_day(date.naive_utc(), self.next_day_starts_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn study_day_starts_at_midnight_by_default() {
+        assert_eq!(study_day(local(1970, 1, 1, 0, 0), 0), 0);
+        assert_eq!(study_day(local(1970, 1, 1, 23, 59), 0), 0);
+        assert_eq!(study_day(local(1970, 1, 2, 0, 0), 0), 1);
+    }
+
+    #[test]
+    fn study_day_rolls_over_at_the_given_hour() {
+        let day = study_day(local(2024, 3, 10, 12, 0), 0);
+
+        assert_eq!(study_day(local(2024, 3, 10, 3, 59), 4), day - 1);
+        assert_eq!(study_day(local(2024, 3, 10, 4, 0), 4), day);
+        assert_eq!(study_day(local(2024, 3, 11, 3, 59), 4), day);
+        assert_eq!(study_day(local(2024, 3, 11, 4, 0), 4), day + 1);
+    }
+
+    #[test]
+    fn fixed_clock_today_uses_the_rollover_hour() {
+        // 1970-01-02 02:00 UTC
+        let clock = FixedClock {
+            now: 86400 + 2 * 3600,
+            next_day_starts_at: 4,
+        };
+
+        assert_eq!(clock.today(), 0);
+        assert_eq!(clock.day_of(86400 + 4 * 3600), 1);
+        assert_eq!(clock.next_day_start(), 86400 + 4 * 3600);
+    }
+
+    #[test]
+    fn system_clock_rollover_hour_can_be_changed() {
+        let clock = SystemClock::new(30);
+        assert_eq!(clock.next_day_starts_at.load(Ordering::Relaxed), 23);
+
+        clock.set_next_day_starts_at(4);
+        assert_eq!(clock.next_day_starts_at.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0
+
+//! Cloze deletions (`{{c1::answer::hint}}`), every index of a text becomes it's own flashcard
+
+/// A piece of a cloze text
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClozePart<'a> {
+    Text(&'a str),
+    Deletion {
+        index: u32,
+        answer: &'a str,
+        hint: Option<&'a str>,
+    },
+}
+
+impl std::fmt::Display for ClozePart<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClozePart::Text(t) => write!(f, "{t}"),
+            ClozePart::Deletion {
+                index,
+                answer,
+                hint: Some(hint),
+            } => write!(f, "{{{{c{index}::{answer}::{hint}}}}}"),
+            ClozePart::Deletion {
+                index,
+                answer,
+                hint: None,
+            } => write!(f, "{{{{c{index}::{answer}}}}}"),
+        }
+    }
+}
+
+/// Splits a cloze text into it's parts, malformed deletions are kept as text
+pub fn parse(text: &str) -> Vec<ClozePart<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let deletion = after.split_once("::").and_then(|(number, content)| {
+            let index = number.parse::<u32>().ok().filter(|i| *i > 0)?;
+            let (content, tail) = content.split_once("}}")?;
+            let (answer, hint) = match content.split_once("::") {
+                Some((answer, hint)) => (answer, Some(hint)),
+                None => (content, None),
+            };
+            Some((
+                ClozePart::Deletion {
+                    index,
+                    answer,
+                    hint,
+                },
+                tail,
+            ))
+        });
+
+        match deletion {
+            Some((deletion, tail)) => {
+                if start > 0 {
+                    parts.push(ClozePart::Text(&rest[..start]));
+                }
+                parts.push(deletion);
+                rest = tail;
+            }
+            None => {
+                parts.push(ClozePart::Text(&rest[..start + 3]));
+                rest = after;
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        parts.push(ClozePart::Text(rest));
+    }
+
+    parts
+}
+
+/// Sorted (and deduplicated) indexes of the deletions of a cloze text
+pub fn indexes(text: &str) -> Vec<u32> {
+    let mut indexes: Vec<u32> = parse(text)
+        .into_iter()
+        .filter_map(|part| match part {
+            ClozePart::Deletion { index, .. } => Some(index),
+            ClozePart::Text(_) => None,
+        })
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+/// Text of the front of the given index, it's deletions are blanked (showing the hint if any) and the rest are revealed
+pub fn front_text(text: &str, index: u32) -> String {
+    parse(text)
+        .into_iter()
+        .map(|part| match part {
+            ClozePart::Text(t) => t.to_string(),
+            ClozePart::Deletion {
+                index: i,
+                hint: Some(hint),
+                ..
+            } if i == index => format!("[{hint}]"),
+            ClozePart::Deletion { index: i, .. } if i == index => String::from("[...]"),
+            ClozePart::Deletion { answer, .. } => answer.to_string(),
+        })
+        .collect()
+}
+
+/// Text of the back, every deletion is revealed
+pub fn back_text(text: &str) -> String {
+    parse(text)
+        .into_iter()
+        .map(|part| match part {
+            ClozePart::Text(t) => t,
+            ClozePart::Deletion { answer, .. } => answer,
+        })
+        .collect()
+}
+
+/// Wraps the first occurrence of the selection (outside other deletions) with a new deletion,
+/// returns [`None`] if the selection can't be found
+pub fn wrap_selection(text: &str, selection: &str) -> Option<String> {
+    if selection.is_empty() {
+        return None;
+    }
+
+    let next_index = indexes(text).last().copied().unwrap_or_default() + 1;
+    let mut wrapped = false;
+
+    let result: String = parse(text)
+        .into_iter()
+        .map(|part| match part {
+            ClozePart::Text(t) if !wrapped && t.contains(selection) => {
+                wrapped = true;
+                t.replacen(selection, &format!("{{{{c{next_index}::{selection}}}}}"), 1)
+            }
+            part => part.to_string(),
+        })
+        .collect();
+
+    wrapped.then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_text_and_deletions() {
+        assert_eq!(
+            parse("The capital of {{c1::France}} is {{c2::Paris::city}}."),
+            vec![
+                ClozePart::Text("The capital of "),
+                ClozePart::Deletion {
+                    index: 1,
+                    answer: "France",
+                    hint: None,
+                },
+                ClozePart::Text(" is "),
+                ClozePart::Deletion {
+                    index: 2,
+                    answer: "Paris",
+                    hint: Some("city"),
+                },
+                ClozePart::Text("."),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_keeps_malformed_deletions_as_text() {
+        let unclosed = "{{c1::never closed";
+        assert_eq!(
+            parse(unclosed),
+            vec![ClozePart::Text("{{c"), ClozePart::Text("1::never closed")]
+        );
+        assert_eq!(back_text(unclosed), unclosed);
+
+        for text in [
+            "{{c0::zero}}",
+            "{{cx::no number}}",
+            "{{c1 missing separator}}",
+        ] {
+            assert!(indexes(text).is_empty(), "{text}");
+            assert_eq!(back_text(text), text);
+        }
+    }
+
+    #[test]
+    fn parse_ends_a_deletion_at_the_first_closing_braces() {
+        // Deletions can't be nested, the inner one closes the outer one
+        assert_eq!(
+            parse("{{c1::a {{c2::b}} c}}"),
+            vec![
+                ClozePart::Deletion {
+                    index: 1,
+                    answer: "a {{c2",
+                    hint: Some("b"),
+                },
+                ClozePart::Text(" c}}"),
+            ]
+        );
+        assert_eq!(indexes("{{c1::a {{c2::b}} c}}"), vec![1]);
+    }
+
+    #[test]
+    fn parts_are_displayed_as_the_original_text() {
+        let text = "{{c1::a}} b {{c2::c::hint}} d";
+        let displayed: String = parse(text).iter().map(ToString::to_string).collect();
+        assert_eq!(displayed, text);
+    }
+
+    #[test]
+    fn indexes_are_sorted_and_deduplicated() {
+        assert_eq!(
+            indexes("{{c3::c}} {{c1::a}} {{c3::d}} {{c2::b}}"),
+            vec![1, 2, 3]
+        );
+        assert!(indexes("no deletions").is_empty());
+    }
+
+    #[test]
+    fn front_text_only_blanks_the_given_index() {
+        let text = "{{c2::b}} and {{c1::a::letter}} and {{c2::c}}";
+
+        assert_eq!(front_text(text, 1), "b and [letter] and c");
+        assert_eq!(front_text(text, 2), "[...] and a and [...]");
+        assert_eq!(back_text(text), "b and a and c");
+    }
+
+    #[test]
+    fn wrap_selection_adds_the_next_index() {
+        assert_eq!(
+            wrap_selection("{{c1::Paris}} is in France", "France").as_deref(),
+            Some("{{c1::Paris}} is in {{c2::France}}")
+        );
+        assert_eq!(
+            wrap_selection("a b a", "a").as_deref(),
+            Some("{{c1::a}} b a")
+        );
+    }
+
+    #[test]
+    fn wrap_selection_ignores_text_inside_deletions() {
+        assert_eq!(wrap_selection("{{c1::Paris}} is nice", "Paris"), None);
+        assert_eq!(wrap_selection("missing", "word"), None);
+        assert_eq!(wrap_selection("empty", ""), None);
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: GPL-3.0
+
+//! Word by word differences between two versions of a text
+
+/// A piece of the difference between two texts
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffPart<'a> {
+    /// Text both versions have
+    Same(&'a str),
+    /// Text only the old version has
+    Removed(&'a str),
+    /// Text only the new version has
+    Added(&'a str),
+}
+
+/// Compares both texts word by word (whitespace is kept attached to the word before it)
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffPart<'a>> {
+    let old_words = words(old);
+    let new_words = words(new);
+
+    // Length of the longest common subsequence between the words after i (old) and j (new)
+    let mut lcs = vec![vec![0usize; new_words.len() + 1]; old_words.len() + 1];
+    for i in (0..old_words.len()).rev() {
+        for j in (0..new_words.len()).rev() {
+            lcs[i][j] = if old_words[i].trim_end() == new_words[j].trim_end() {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut parts = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_words.len() && j < new_words.len() {
+        if old_words[i].trim_end() == new_words[j].trim_end() {
+            parts.push(DiffPart::Same(new_words[j]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            parts.push(DiffPart::Removed(old_words[i]));
+            i += 1;
+        } else {
+            parts.push(DiffPart::Added(new_words[j]));
+            j += 1;
+        }
+    }
+    parts.extend(old_words[i..].iter().map(|w| DiffPart::Removed(w)));
+    parts.extend(new_words[j..].iter().map(|w| DiffPart::Added(w)));
+
+    parts
+}
+
+/// Returns true if both texts are the same
+pub fn is_same(parts: &[DiffPart]) -> bool {
+    parts.iter().all(|part| matches!(part, DiffPart::Same(_)))
+}
+
+// Helper function to split a text in words, each word keeps the whitespace that follows it
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_whitespace = false;
+
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_whitespace = true;
+        } else if in_whitespace {
+            words.push(&text[start..index]);
+            start = index;
+            in_whitespace = false;
+        }
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_of_the_same_text_is_unchanged() {
+        let parts = diff("the black cat", "the black cat");
+
+        assert_eq!(
+            parts,
+            vec![
+                DiffPart::Same("the "),
+                DiffPart::Same("black "),
+                DiffPart::Same("cat"),
+            ]
+        );
+        assert!(is_same(&parts));
+    }
+
+    #[test]
+    fn diff_finds_inserted_words() {
+        let parts = diff("the cat", "the black cat");
+
+        assert_eq!(
+            parts,
+            vec![
+                DiffPart::Same("the "),
+                DiffPart::Added("black "),
+                DiffPart::Same("cat"),
+            ]
+        );
+        assert!(!is_same(&parts));
+    }
+
+    #[test]
+    fn diff_finds_deleted_words() {
+        assert_eq!(
+            diff("the black cat", "the cat"),
+            vec![
+                DiffPart::Same("the "),
+                DiffPart::Removed("black "),
+                DiffPart::Same("cat"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_of_a_replaced_word_removes_it_and_adds_the_new_one() {
+        assert_eq!(
+            diff("one two three", "one four three"),
+            vec![
+                DiffPart::Same("one "),
+                DiffPart::Removed("two "),
+                DiffPart::Added("four "),
+                DiffPart::Same("three"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_ignores_trailing_whitespace_changes() {
+        assert!(is_same(&diff("cat  ", "cat")));
+        assert_eq!(
+            diff("", "new words"),
+            vec![DiffPart::Added("new "), DiffPart::Added("words")]
+        );
+    }
+}
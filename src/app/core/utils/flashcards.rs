@@ -1,17 +1,25 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fs::File,
     io::{self, BufRead, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use fsrs::MemoryState;
 use percent_encoding::percent_decode_str;
+use sqlx::{Pool, Row, Sqlite};
 
 use crate::app::core::{
-    models::flashcard::{Flashcard, FlashcardField, FlashcardStatus},
-    utils::fsrs_scheduler::FSRSScheduler,
+    models::{
+        duplicates::{DuplicateAction, DuplicateChecker, DuplicateScope, ImportSummary},
+        flashcard::{Flashcard, FlashcardField, FlashcardStatus, parse_tags},
+        folder::Folder,
+    },
+    utils::{Clock, cloze, fsrs_scheduler::FSRSScheduler},
 };
 
 /// Custom Import into Oboete Flashcards
@@ -39,8 +47,15 @@ pub fn parse_import_content(
         .collect()
 }
 
+/// A [`Flashcard`] parsed from an anki export file alongside the deck it belongs to
+pub struct AnkiFlashcard {
+    /// Path of the deck, ej: "Lang::Spanish::Verbs" is ["Lang", "Spanish", "Verbs"]
+    pub deck: Vec<String>,
+    pub flashcard: Flashcard,
+}
+
 /// Given a path to an anki export file parses it to Flashcards in Oboete
-pub fn parse_ankifile(file_path: &str) -> Result<Vec<Flashcard>, anywho::Error> {
+pub fn parse_ankifile(file_path: &str) -> Result<Vec<AnkiFlashcard>, anywho::Error> {
     let decoded_path = percent_decode_str(file_path)
         .decode_utf8_lossy()
         .to_string();
@@ -48,22 +63,99 @@ pub fn parse_ankifile(file_path: &str) -> Result<Vec<Flashcard>, anywho::Error>
     let file = File::open(path)?;
     let reader = io::BufReader::new(file);
 
+    // Anki doesn't include the media on the text export, it's looked for next to the file
+    let media_dirs: Vec<PathBuf> = path
+        .parent()
+        .map(|dir| {
+            vec![
+                dir.to_path_buf(),
+                dir.join("collection.media"),
+                dir.join("media"),
+            ]
+        })
+        .unwrap_or_default();
+
     let mut flashcards = Vec::new();
+    // Index of the columns that hold the tags, the deck and the metadata we don't use (if any)
+    let mut tags_column: Option<usize> = None;
+    let mut deck_column: Option<usize> = None;
+    let mut ignored_columns: Vec<usize> = Vec::new();
 
-    for (index, line) in reader.lines().enumerate() {
+    for line in reader.lines() {
         let line = line?;
-        // Skip the first three lines which are metadata
-        if index < 3 {
+        // Header lines (metadata) start with #
+        if let Some(header) = line.strip_prefix('#') {
+            if let Some(column) = header.strip_prefix("tags column:") {
+                tags_column = parse_header_column(column);
+            } else if let Some(column) = header.strip_prefix("deck column:") {
+                deck_column = parse_header_column(column);
+            } else if let Some(column) = header
+                .strip_prefix("notetype column:")
+                .or_else(|| header.strip_prefix("guid column:"))
+            {
+                ignored_columns.extend(parse_header_column(column));
+            }
             continue;
         }
+
         let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() == 2 {
-            flashcards.push(Flashcard {
+        let column = |index: Option<usize>| index.and_then(|i| parts.get(i).copied());
+
+        let tags = column(tags_column).map(parse_tags).unwrap_or_default();
+        let deck = column(deck_column)
+            .map(|deck| {
+                deck.split("::")
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // The remaining columns are the front and the back
+        let fields: Vec<&str> = parts
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                Some(*i) != tags_column && Some(*i) != deck_column && !ignored_columns.contains(i)
+            })
+            .map(|(_, part)| *part)
+            .collect();
+
+        // Cloze notes have the cloze text and an (optional) back extra field
+        if let Some(text) = fields.first()
+            && !cloze::indexes(text).is_empty()
+            && fields.len() <= 2
+        {
+            let flashcard = Flashcard {
                 id: None,
-                front: FlashcardField::Text(parts[0].to_string()),
-                back: FlashcardField::Text(parts[1].to_string()),
+                front: FlashcardField::Cloze {
+                    text: text.to_string(),
+                    index: 1,
+                },
+                back: FlashcardField::Text(fields.get(1).unwrap_or(&"").to_string()),
                 status: FlashcardStatus::None,
+                tags,
                 ..Default::default()
+            };
+
+            flashcards.extend(flashcard.expand_cloze().into_iter().map(|flashcard| {
+                AnkiFlashcard {
+                    deck: deck.clone(),
+                    flashcard,
+                }
+            }));
+        } else if fields.len() == 2 {
+            flashcards.push(AnkiFlashcard {
+                deck,
+                flashcard: Flashcard {
+                    id: None,
+                    front: parse_anki_field(fields[0], &media_dirs),
+                    back: parse_anki_field(fields[1], &media_dirs),
+                    status: FlashcardStatus::None,
+                    tags,
+                    ..Default::default()
+                },
             });
         }
     }
@@ -71,26 +163,133 @@ pub fn parse_ankifile(file_path: &str) -> Result<Vec<Flashcard>, anywho::Error>
     Ok(flashcards)
 }
 
+// Helper function to parse an anki field, fields with a sound tag ([sound:file.mp3]) become audio
+// if the file can be found on the given media directories (the rest of the field is the transcript)
+fn parse_anki_field(field: &str, media_dirs: &[PathBuf]) -> FlashcardField {
+    if let Some(start) = field.find("[sound:")
+        && let Some(length) = field[start..].find(']')
+    {
+        let file_name = &field[start + "[sound:".len()..start + length];
+        if let Some(audio_path) = media_dirs
+            .iter()
+            .map(|dir| dir.join(file_name))
+            .find(|path| path.is_file())
+        {
+            let transcript = format!("{}{}", &field[..start], &field[start + length + 1..]);
+            return FlashcardField::Audio {
+                path: audio_path.to_string_lossy().to_string(),
+                transcript: transcript.trim().to_string(),
+            };
+        }
+    }
+
+    FlashcardField::Text(field.to_string())
+}
+
+// Helper function to parse the (1 based) column number of an anki file header
+fn parse_header_column(column: &str) -> Option<usize> {
+    column
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|c| c.checked_sub(1))
+}
+
+/// Adds the given [`AnkiFlashcard`]s to the given folder, every deck becomes a subfolder (created if missing).
+/// If the top deck is named like the given folder it's considered to be the folder itself.
+/// The flashcards whose front already exists are handled with the given [`DuplicateAction`]
+pub async fn import_anki_flashcards(
+    pool: Arc<Pool<Sqlite>>,
+    flashcards: Vec<AnkiFlashcard>,
+    folder_id: i32,
+    scope: DuplicateScope,
+    action: DuplicateAction,
+    imported_at: i64,
+) -> Result<ImportSummary, anywho::Error> {
+    let folder = Folder::get(pool.clone(), folder_id).await?;
+    let mut checker = DuplicateChecker::new(scope, action, imported_at);
+
+    // The audio is copied while the flashcards are imported, if anything fails the copies are deleted (nothing references them)
+    let mut copied_audio = Vec::<String>::new();
+
+    let result: Result<(), anywho::Error> = async {
+        let mut transaction = pool.begin().await?;
+
+        let studyset_id: i32 = sqlx::query("SELECT studyset_id FROM folders WHERE id = $1")
+            .bind(folder_id)
+            .fetch_one(&mut *transaction)
+            .await?
+            .try_get("studyset_id")?;
+
+        // Map deck path → folder_id (so every deck is only looked up once)
+        let mut deck_folders: HashMap<Vec<String>, i32> = HashMap::new();
+
+        for AnkiFlashcard {
+            deck,
+            mut flashcard,
+        } in flashcards
+        {
+            let path = match deck.split_first() {
+                Some((top, rest)) if top.eq_ignore_ascii_case(&folder.name) => rest,
+                _ => deck.as_slice(),
+            };
+
+            let target_id = match deck_folders.get(path) {
+                Some(id) => *id,
+                None => {
+                    let id = Folder::get_or_create_path(
+                        &mut *transaction,
+                        studyset_id,
+                        Some(folder_id),
+                        path,
+                    )
+                    .await?
+                    .unwrap_or(folder_id);
+                    deck_folders.insert(path.to_vec(), id);
+                    id
+                }
+            };
+
+            // The audio found on the anki media gets it's own copy on the Oboete data path,
+            // only for the flashcards that are actually added or updated
+            checker
+                .import_with(&mut *transaction, &mut flashcard, target_id, |flashcard| {
+                    for field in [&mut flashcard.front, &mut flashcard.back] {
+                        if let FlashcardField::Audio { path, .. } = field {
+                            *path = super::save_audio(path)?;
+                            copied_audio.push(path.clone());
+                        }
+                    }
+                    Ok(())
+                })
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        for path in copied_audio {
+            if let Err(e) = super::delete_audio(path).await {
+                eprintln!("{e}");
+            }
+        }
+        return Err(e);
+    }
+
+    Ok(checker.summary)
+}
+
 /// Given a path to save the file and a Vec<Flashcard> creates a file with the flashcards data
 pub fn export_flashcards(file_path: &str, flashcards: &[Flashcard]) -> Result<(), anywho::Error> {
     let mut file = File::create(file_path)?;
 
     for flashcard in flashcards {
-        let front = match &flashcard.front {
-            FlashcardField::Text(t) => t,
-            FlashcardField::Image {
-                path: _path,
-                alt_text,
-            } => alt_text,
-        };
-
-        let back = match &flashcard.back {
-            FlashcardField::Text(t) => t,
-            FlashcardField::Image {
-                path: _path,
-                alt_text,
-            } => alt_text,
-        };
+        let front = export_field(&flashcard.front);
+        let back = export_back(flashcard);
 
         writeln!(file, "{}\\#*#\\{}", front, back)?;
         writeln!(file, "/#")?;
@@ -109,36 +308,67 @@ pub fn export_flashcards_anki(
 
     writeln!(file, "#separator:tab")?;
     writeln!(file, "#html:false")?;
-    writeln!(file)?; //adds a \n before the flashcards
+    writeln!(file, "#tags column:3")?;
 
     for flashcard in flashcards {
-        let front = match &flashcard.front {
-            FlashcardField::Text(t) => t,
-            FlashcardField::Image {
-                path: _path,
-                alt_text,
-            } => alt_text,
-        };
-
-        let back = match &flashcard.back {
-            FlashcardField::Text(t) => t,
-            FlashcardField::Image {
-                path: _path,
-                alt_text,
-            } => alt_text,
-        };
+        let front = export_field(&flashcard.front);
+        let back = export_back(flashcard);
 
-        writeln!(file, "{}\t{}", front, back)?;
+        writeln!(
+            file,
+            "{}\t{}\t{}",
+            anki_quote(&front),
+            anki_quote(&back),
+            flashcard.tags.join(" ")
+        )?;
     }
 
     Ok(())
 }
 
-// Helper function to get current day since epoch
-pub fn current_day() -> i32 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    (duration.as_secs() / 86400) as i32
+// Helper function to get the back of a flashcard for the exports, cloze flashcards use the revealed text (and the back extra if any)
+fn export_back(flashcard: &Flashcard) -> Cow<'_, str> {
+    match &flashcard.front {
+        FlashcardField::Cloze { text, .. } => {
+            let extra = flashcard.back.plain_text();
+            if extra.is_empty() {
+                Cow::Owned(cloze::back_text(text))
+            } else {
+                Cow::Owned(format!("{} {}", cloze::back_text(text), extra))
+            }
+        }
+        _ => export_field(&flashcard.back),
+    }
+}
+
+// Helper function to get the text of a field for the exports, code is exported as a fenced block
+fn export_field(field: &FlashcardField) -> Cow<'_, str> {
+    match field {
+        FlashcardField::Code { language, source } => {
+            Cow::Owned(format!("```{}\n{}\n```", language.to_lowercase(), source))
+        }
+        field => field.plain_text(),
+    }
+}
+
+// Helper function to quote the fields that span multiple lines (like code blocks) so anki reads them as a single field
+fn anki_quote(field: &str) -> Cow<'_, str> {
+    if field.contains(['\n', '\t']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Result of scheduling a [`Flashcard`] after the user rates it
+pub struct ScheduledReview {
+    pub memory_state: MemoryState,
+    pub due_date: i64,     // Seconds since epoch
+    pub interval: i64,     // Seconds until the next review
+    pub elapsed_days: u32, // Days since the last review
+    /// (Re)learning step the flashcard is on, [`None`] if it's in review
+    pub learning_step: Option<u32>,
+    pub relearning: bool,
 }
 
 // Helper function to generate updated FSRS data for the given flashcard
@@ -146,12 +376,13 @@ pub fn update_fsrs_data(
     selected_state: &FlashcardStatus,
     flashcard: &Flashcard,
     scheduler: &FSRSScheduler,
-) -> Option<(MemoryState, i32)> {
+    clock: &dyn Clock,
+) -> Option<ScheduledReview> {
     // Calculate days elapsed since last review
-    let current = current_day();
+    let now = clock.now();
     let days_elapsed = flashcard
         .last_reviewed
-        .map(|last| (current - last) as u32)
+        .map(|last| (clock.day_of(now) - clock.day_of(last)).max(0) as u32)
         .unwrap_or(0);
 
     // Get next states from FSRS
@@ -195,9 +426,132 @@ pub fn update_fsrs_data(
         }
     };
 
-    // Force minimum 1 day interval to prevent infinite same-day loops
-    let effective_interval = interval_days.max(1.0) as i32;
-    let new_due_date = current + effective_interval;
+    let (interval, learning_step, relearning) = match scheduler.next_step(flashcard, selected_state)
+    {
+        Some(step) => (step.delay, Some(step.index), step.relearning),
+        None => {
+            // Force minimum 1 day interval once the flashcard leaves it's (re)learning steps
+            let interval = interval_days.round().max(1.0) as i64 * 86400;
+            (interval, None, false)
+        }
+    };
 
-    Some((new_memory_state, new_due_date))
+    Some(ScheduledReview {
+        memory_state: new_memory_state,
+        due_date: now + interval,
+        interval,
+        elapsed_days: days_elapsed,
+        learning_step,
+        relearning,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::core::{models::flashcard::SerializableMemoryState, utils::FixedClock};
+
+    // 1970-01-11 12:00 UTC, study day 10
+    const NOW: i64 = 10 * 86400 + 12 * 3600;
+
+    fn clock() -> FixedClock {
+        FixedClock {
+            now: NOW,
+            next_day_starts_at: 4,
+        }
+    }
+
+    fn scheduler() -> FSRSScheduler {
+        FSRSScheduler::new(&Folder::default()).unwrap()
+    }
+
+    fn reviewed_flashcard(last_reviewed: i64) -> Flashcard {
+        Flashcard {
+            fsrs_state: Some(SerializableMemoryState {
+                stability: 5.0,
+                difficulty: 5.0,
+            }),
+            due_date: Some(NOW),
+            last_reviewed: Some(last_reviewed),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn new_flashcards_go_through_the_learning_steps() {
+        let review = update_fsrs_data(
+            &FlashcardStatus::Great,
+            &Flashcard::default(),
+            &scheduler(),
+            &clock(),
+        )
+        .unwrap();
+
+        // Default learning steps are "1m 10m"
+        assert_eq!(review.learning_step, Some(1));
+        assert!(!review.relearning);
+        assert_eq!(review.interval, 10 * 60);
+        assert_eq!(review.due_date, NOW + 10 * 60);
+        assert_eq!(review.elapsed_days, 0);
+    }
+
+    #[test]
+    fn review_flashcards_are_scheduled_in_whole_days() {
+        let review = update_fsrs_data(
+            &FlashcardStatus::Great,
+            &reviewed_flashcard(NOW - 3 * 86400),
+            &scheduler(),
+            &clock(),
+        )
+        .unwrap();
+
+        assert_eq!(review.learning_step, None);
+        assert_eq!(review.elapsed_days, 3);
+        assert!(review.interval >= 86400);
+        assert_eq!(review.interval % 86400, 0);
+        assert_eq!(review.due_date, NOW + review.interval);
+    }
+
+    #[test]
+    fn elapsed_days_count_study_days() {
+        // Reviewed at 03:00 of study day 10 (still study day 9 with the rollover at 04:00)
+        let review = update_fsrs_data(
+            &FlashcardStatus::Great,
+            &reviewed_flashcard(10 * 86400 + 3 * 3600),
+            &scheduler(),
+            &clock(),
+        )
+        .unwrap();
+
+        assert_eq!(review.elapsed_days, 1);
+    }
+
+    #[test]
+    fn forgotten_flashcards_go_to_the_relearning_steps() {
+        let review = update_fsrs_data(
+            &FlashcardStatus::Bad,
+            &reviewed_flashcard(NOW - 3 * 86400),
+            &scheduler(),
+            &clock(),
+        )
+        .unwrap();
+
+        // Default relearning steps are "10m"
+        assert_eq!(review.learning_step, Some(0));
+        assert!(review.relearning);
+        assert_eq!(review.due_date, NOW + 10 * 60);
+    }
+
+    #[test]
+    fn no_rating_schedules_nothing() {
+        assert!(
+            update_fsrs_data(
+                &FlashcardStatus::None,
+                &Flashcard::default(),
+                &scheduler(),
+                &clock(),
+            )
+            .is_none()
+        );
+    }
 }
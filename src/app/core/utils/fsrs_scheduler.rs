@@ -1,22 +1,45 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use fsrs::{FSRS, MemoryState, NextStates};
+use std::{collections::BTreeMap, sync::Arc};
+
+use fsrs::{
+    ComputeParametersInput, DEFAULT_PARAMETERS, FSRS, FSRSItem, FSRSReview, MemoryState, NextStates,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::app::core::models::{
+    flashcard::{Flashcard, FlashcardStatus},
+    folder::Folder,
+    revlog::RevlogEntry,
+};
 
 /// Allows us to schedule flashcards using the fsrs algorithm
 pub struct FSRSScheduler {
     fsrs: FSRS,
     desired_retention: f32,
+    learning_steps: Vec<i64>,
+    relearning_steps: Vec<i64>,
+}
+
+/// A (re)learning step a flashcard is moved to after being rated
+pub struct LearningStep {
+    pub index: u32,
+    pub relearning: bool,
+    pub delay: i64, // Seconds until the flashcard is due again
 }
 
 impl FSRSScheduler {
-    /// Init a new [`FSRSScheduler`]
-    pub fn new(desired_retention: f32) -> Result<Self, anywho::Error> {
-        // Use default parameters (works well for most users)
-        let fsrs = FSRS::new(Some(&[]))?;
+    /// Init a new [`FSRSScheduler`] using the scheduling options of the given [`Folder`]
+    pub fn new(folder: &Folder) -> Result<Self, anywho::Error> {
+        // Empty parameters mean the default parameters (works well for most users)
+        let parameters = folder.fsrs_parameters.clone().unwrap_or_default();
+        let fsrs = FSRS::new(Some(parameters.as_slice()))?;
 
         Ok(Self {
             fsrs,
-            desired_retention,
+            desired_retention: folder.desired_retention,
+            learning_steps: parse_steps(&folder.learning_steps).unwrap_or_default(),
+            relearning_steps: parse_steps(&folder.relearning_steps).unwrap_or_default(),
         })
     }
 
@@ -30,4 +53,256 @@ impl FSRSScheduler {
             .fsrs
             .next_states(memory_state, self.desired_retention, days_elapsed)?)
     }
+
+    /// Decides the (re)learning step of a flashcard after being rated, [`None`] means the flashcard goes (or stays) in review
+    pub fn next_step(
+        &self,
+        flashcard: &Flashcard,
+        rating: &FlashcardStatus,
+    ) -> Option<LearningStep> {
+        let (steps, relearning) = match flashcard.learning_step {
+            Some(_) if flashcard.relearning => (&self.relearning_steps, true),
+            Some(_) => (&self.learning_steps, false),
+            // New flashcards start at the first learning step
+            None if flashcard.fsrs_state.is_none() => (&self.learning_steps, false),
+            // Flashcards in review only go back to the relearning steps when forgotten
+            None => {
+                return match rating {
+                    FlashcardStatus::Bad => {
+                        self.relearning_steps.first().map(|delay| LearningStep {
+                            index: 0,
+                            relearning: true,
+                            delay: *delay,
+                        })
+                    }
+                    _ => None,
+                };
+            }
+        };
+
+        if steps.is_empty() {
+            return None;
+        }
+
+        // The steps may have been modified since the flashcard was last studied
+        let current = (flashcard.learning_step.unwrap_or(0) as usize).min(steps.len() - 1);
+        let step = |index: usize, delay: i64| {
+            Some(LearningStep {
+                index: index as u32,
+                relearning,
+                delay,
+            })
+        };
+
+        match rating {
+            FlashcardStatus::Bad => step(0, steps[0]),
+            FlashcardStatus::Ok => {
+                // Hard repeats the current step, on the first step it waits a bit longer (like Anki)
+                let delay = match (current, steps.get(1)) {
+                    (0, Some(next)) => (steps[0] + next) / 2,
+                    (0, None) => steps[0] * 3 / 2,
+                    _ => steps[current],
+                };
+                step(current, delay)
+            }
+            FlashcardStatus::Great => steps
+                .get(current + 1)
+                .and_then(|delay| step(current + 1, *delay)),
+            FlashcardStatus::Easy | FlashcardStatus::None => None,
+        }
+    }
+}
+
+/// Checks that the given FSRS parameters can be used by the scheduler, they must be as many as the default ones and finite
+pub fn validate_parameters(parameters: &[f32]) -> Result<(), anywho::Error> {
+    if parameters.len() != DEFAULT_PARAMETERS.len() {
+        return Err(anywho::anywho!(
+            "Invalid FSRS parameters: expected {} values, found {}",
+            DEFAULT_PARAMETERS.len(),
+            parameters.len()
+        ));
+    }
+
+    if parameters.iter().any(|parameter| !parameter.is_finite()) {
+        return Err(anywho::anywho!(
+            "Invalid FSRS parameters: all the values must be finite numbers"
+        ));
+    }
+
+    FSRS::new(Some(parameters))?;
+    Ok(())
+}
+
+/// Parses space separated (re)learning steps like "1m 10m 1d" into seconds, numbers without unit are minutes
+pub fn parse_steps(steps: &str) -> Option<Vec<i64>> {
+    steps
+        .split_whitespace()
+        .map(|step| {
+            let (value, multiplier) = match step.char_indices().last()? {
+                (index, 's') => (&step[..index], 1),
+                (index, 'm') => (&step[..index], 60),
+                (index, 'h') => (&step[..index], 60 * 60),
+                (index, 'd') => (&step[..index], 24 * 60 * 60),
+                _ => (step, 60),
+            };
+
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value > 0.0)
+                .map(|value| (value * multiplier as f64).round() as i64)
+        })
+        .collect()
+}
+
+/// Outcome of fitting the FSRS parameters of a [`Folder`] to it's review history
+#[derive(Debug, Clone)]
+pub struct OptimisationResult {
+    pub parameters: Vec<f32>,
+    pub old_log_loss: f32,
+    pub new_log_loss: f32,
+    pub review_count: usize,
+}
+
+/// Trains new FSRS parameters using the review history of the given [`Folder`], the result is not saved
+pub async fn optimise_parameters(
+    pool: Arc<Pool<Sqlite>>,
+    folder: Folder,
+) -> Result<OptimisationResult, anywho::Error> {
+    let folder_id = folder
+        .id
+        .ok_or_else(|| anywho::anywho!("Folder has not been saved yet"))?;
+    let revlog = RevlogEntry::get_all_by_folder(pool, folder_id).await?;
+    let current_parameters = folder.fsrs_parameters.unwrap_or_default();
+
+    // Training is CPU bound, keep it out of the async runtime
+    tokio::task::spawn_blocking(move || {
+        let items = revlog_to_items(&revlog);
+        if items.is_empty() {
+            return Err(anywho::anywho!(
+                "Not enough reviews to optimise this folder"
+            ));
+        }
+
+        let parameters = FSRS::new(None)?.compute_parameters(ComputeParametersInput {
+            train_set: items.clone(),
+            ..Default::default()
+        })?;
+
+        let old_log_loss = FSRS::new(Some(current_parameters.as_slice()))?
+            .evaluate(items.clone(), |_| true)?
+            .log_loss;
+        let new_log_loss = FSRS::new(Some(parameters.as_slice()))?
+            .evaluate(items, |_| true)?
+            .log_loss;
+
+        Ok(OptimisationResult {
+            parameters,
+            old_log_loss,
+            new_log_loss,
+            review_count: revlog.len(),
+        })
+    })
+    .await?
+}
+
+/// Converts the review history into FSRS training items, one item for every review after the first one of a card
+fn revlog_to_items(revlog: &[RevlogEntry]) -> Vec<FSRSItem> {
+    let mut reviews_by_card: BTreeMap<i32, Vec<&RevlogEntry>> = BTreeMap::new();
+    for entry in revlog {
+        reviews_by_card
+            .entry(entry.flashcard_id)
+            .or_default()
+            .push(entry);
+    }
+
+    let mut items = Vec::new();
+
+    for entries in reviews_by_card.values() {
+        // If the first logged review already had a memory state the card was studied before
+        // the history was recorded, so we can't know it's full history
+        if entries
+            .first()
+            .is_none_or(|first| first.previous_fsrs_state.is_some())
+        {
+            continue;
+        }
+
+        let reviews: Vec<FSRSReview> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| FSRSReview {
+                rating: rating_to_fsrs(entry.rating),
+                delta_t: if index == 0 { 0 } else { entry.elapsed_days },
+            })
+            .collect();
+
+        for (end, review) in reviews.iter().enumerate().skip(1) {
+            // Only reviews done on a different day carry long-term information
+            if review.delta_t > 0 {
+                items.push(FSRSItem {
+                    reviews: reviews[..=end].to_vec(),
+                });
+            }
+        }
+    }
+
+    items
+}
+
+/// Converts a [`FlashcardStatus`] into the rating the FSRS library expects (1 = Again ... 4 = Easy)
+fn rating_to_fsrs(status: FlashcardStatus) -> u32 {
+    match status {
+        FlashcardStatus::None | FlashcardStatus::Bad => 1,
+        FlashcardStatus::Ok => 2,
+        FlashcardStatus::Great => 3,
+        FlashcardStatus::Easy => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_steps_reads_every_unit() {
+        assert_eq!(
+            parse_steps("30s 1m 10 1.5h 1d"),
+            Some(vec![30, 60, 600, 5400, 86400])
+        );
+        assert_eq!(parse_steps("  "), Some(Vec::new()));
+    }
+
+    #[test]
+    fn parse_steps_rejects_malformed_steps() {
+        for steps in [
+            "abc", "1m x", "m", "1w", "0m", "-5m", "nan", "infm", "1m,10m",
+        ] {
+            assert_eq!(parse_steps(steps), None, "{steps}");
+        }
+    }
+
+    #[test]
+    fn validate_parameters_accepts_the_defaults() {
+        assert!(validate_parameters(&DEFAULT_PARAMETERS).is_ok());
+    }
+
+    #[test]
+    fn validate_parameters_rejects_the_wrong_length() {
+        assert!(validate_parameters(&[]).is_err());
+        assert!(validate_parameters(&DEFAULT_PARAMETERS[1..]).is_err());
+
+        let mut parameters = DEFAULT_PARAMETERS.to_vec();
+        parameters.push(1.0);
+        assert!(validate_parameters(&parameters).is_err());
+    }
+
+    #[test]
+    fn validate_parameters_rejects_non_finite_values() {
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let mut parameters = DEFAULT_PARAMETERS.to_vec();
+            parameters[0] = value;
+            assert!(validate_parameters(&parameters).is_err(), "{value}");
+        }
+    }
 }
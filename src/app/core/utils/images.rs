@@ -1,52 +1,18 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use std::{fs, path::Path};
+use super::media::{self, MediaKind};
 
 /// Returns true if the path corresponds to an image already saved in the Oboete directory
 pub fn check_path(image_path: &String) -> bool {
-    let Some(data_dir) = dirs::data_dir() else {
-        return false;
-    };
-
-    let output_path = data_dir.join(super::APP_ID).join("images");
-
-    let path = Path::new(image_path);
-    path.starts_with(&output_path)
+    media::is_managed(MediaKind::Image, image_path)
 }
 
 /// Attempts to save the image of the given path on the standard oboete output path
 pub fn save_image(image_path: &String) -> Result<String, anywho::Error> {
-    let output_path = dirs::data_dir()
-        .ok_or_else(|| anywho::anywho!("Failed to get data directory"))?
-        .join(super::APP_ID)
-        .join("images");
-
-    if !output_path.exists() {
-        fs::create_dir_all(&output_path)?;
-    }
-
-    let extension = Path::new(image_path)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .ok_or_else(|| anywho::anywho!("Failed to get file extension"))?;
-
-    let new_filename = format!("{}.{}", uuid::Uuid::new_v4(), extension);
-    let destination = output_path.join(&new_filename);
-
-    fs::copy(image_path, &destination)?;
-
-    Ok(destination.to_string_lossy().to_string())
+    media::save_media(MediaKind::Image, image_path)
 }
 
 /// Deletes the image of the given path
 pub async fn delete_image(image_path: String) -> Result<(), anywho::Error> {
-    let path = Path::new(&image_path);
-
-    if tokio::fs::metadata(path).await.is_err() {
-        return Err(anywho::anywho!("Image file does not exist: {}", image_path));
-    }
-
-    tokio::fs::remove_file(path).await?;
-
-    Ok(())
+    media::delete_media(MediaKind::Image, image_path).await
 }
@@ -0,0 +1,969 @@
+// SPDX-License-Identifier: GPL-3.0
+
+//! Math formulas (LaTeX) rendered to svg, they are cached on disk next to the managed images
+//!
+//! The formulas are converted to typst math and laid out by typst, so no TeX install (or JavaScript engine) is needed
+
+use std::{
+    collections::HashMap,
+    fs,
+    iter::Peekable,
+    path::PathBuf,
+    str::Chars,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use comemo::Prehashed;
+use pulldown_cmark::{Event, Options, Parser};
+use typst::{
+    Library, World,
+    diag::{FileError, FileResult},
+    eval::Tracer,
+    foundations::{Bytes, Datetime},
+    syntax::{FileId, Source},
+    text::{Font, FontBook},
+};
+
+/// Font size (in pt) the formulas are rendered with
+const FONT_SIZE: f32 = 10.0;
+
+/// A rendered formula, it's size is relative to the font size (em)
+#[derive(Debug, Clone)]
+pub struct MathSvg {
+    pub svg: Arc<str>,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Formulas already read from the disk cache, keyed by (latex, display)
+static RENDERED: LazyLock<Mutex<HashMap<(String, bool), MathSvg>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Fonts bundled with typst (New Computer Modern Math is used for the formulas)
+static FONTS: LazyLock<Vec<Font>> = LazyLock::new(|| {
+    typst_assets::fonts()
+        .flat_map(|data| Font::iter(Bytes::from_static(data)))
+        .collect()
+});
+
+static BOOK: LazyLock<Prehashed<FontBook>> =
+    LazyLock::new(|| Prehashed::new(FontBook::from_fonts(FONTS.iter())));
+
+static LIBRARY: LazyLock<Prehashed<Library>> = LazyLock::new(|| Prehashed::new(Library::default()));
+
+/// The typst world of a single formula, it has no files other than the formula itself
+struct MathWorld {
+    source: Source,
+}
+
+impl World for MathWorld {
+    fn library(&self) -> &Prehashed<Library> {
+        &LIBRARY
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        &BOOK
+    }
+
+    fn main(&self) -> Source {
+        self.source.clone()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.source.id() {
+            Ok(self.source.clone())
+        } else {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        FONTS.get(index).cloned()
+    }
+
+    fn today(&self, _offset: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+// Helper function to get the directory where the rendered formulas are cached
+fn math_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(super::APP_ID).join("math"))
+}
+
+// Helper function to get the cache file of a formula, the name is derived from it's content so it's stable between runs
+fn cache_path(latex: &str, display: bool) -> Option<PathBuf> {
+    let name = uuid::Uuid::new_v5(
+        &uuid::Uuid::NAMESPACE_OID,
+        format!("typst:{display}:{latex}").as_bytes(),
+    );
+    math_dir().map(|dir| dir.join(format!("{name}.svg")))
+}
+
+/// Every math formula (latex, display) of the given markdown, so they can be rendered before showing it
+pub fn math_formulas(content: &str) -> Vec<(String, bool)> {
+    Parser::new_ext(content, Options::ENABLE_MATH)
+        .filter_map(|event| match event {
+            Event::InlineMath(latex) => Some((latex.to_string(), false)),
+            Event::DisplayMath(latex) => Some((latex.to_string(), true)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the rendered formula if it has already been rendered, [`None`] if it still needs to be (see [`render_math`])
+pub fn cached_math(latex: &str, display: bool) -> Option<MathSvg> {
+    let key = (latex.to_string(), display);
+    if let Some(math) = RENDERED.lock().ok()?.get(&key) {
+        return Some(math.clone());
+    }
+
+    let svg = fs::read_to_string(cache_path(latex, display)?).ok()?;
+    let math = MathSvg {
+        width: svg_size(&svg, "width")?,
+        height: svg_size(&svg, "height")?,
+        svg: Arc::from(svg),
+    };
+
+    RENDERED.lock().ok()?.insert(key, math.clone());
+    Some(math)
+}
+
+/// Renders the given formulas (latex, display) that are not cached yet, formulas that fail to render are skipped
+pub async fn render_math(formulas: Vec<(String, bool)>) -> Result<(), anywho::Error> {
+    let output_path = math_dir().ok_or_else(|| anywho::anywho!("Failed to get data directory"))?;
+
+    // Laying out the formulas is cpu heavy, so it runs on a blocking thread
+    tokio::task::spawn_blocking(move || -> Result<(), anywho::Error> {
+        if !output_path.exists() {
+            fs::create_dir_all(&output_path)?;
+        }
+
+        for (latex, display) in formulas {
+            let Some(path) = cache_path(&latex, display) else {
+                continue;
+            };
+            if path.exists() {
+                continue;
+            }
+
+            match render_svg(&latex, display) {
+                Ok(svg) => fs::write(&path, svg)?,
+                Err(e) => eprintln!("Failed to render formula {latex:?}: {e}"),
+            }
+        }
+
+        // Typst memoizes the layout of every formula, those are not needed anymore
+        comemo::evict(0);
+
+        Ok(())
+    })
+    .await?
+}
+
+// Helper function to lay out a single formula with typst
+fn render_svg(latex: &str, display: bool) -> Result<String, anywho::Error> {
+    let math = latex_to_typst(latex).ok_or_else(|| anywho::anywho!("Unsupported formula"))?;
+    // The formula is always a block equation so nothing gets clipped, inline formulas keep the inline style
+    let math = if display {
+        math
+    } else {
+        format!("inline({math})")
+    };
+
+    let world = MathWorld {
+        source: Source::detached(format!(
+            "#set page(width: auto, height: auto, margin: 1pt)\n#set text(size: {FONT_SIZE}pt)\n$ {math} $"
+        )),
+    };
+    let document = typst::compile(&world, &mut Tracer::new()).map_err(|errors| {
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        anywho::anywho!("{}", messages.join(", "))
+    })?;
+    let page = document
+        .pages
+        .first()
+        .ok_or_else(|| anywho::anywho!("Formula has no content"))?;
+
+    // The glyphs are black, the current color lets the svg match the theme
+    Ok(typst_svg::svg(&page.frame).replace("#000000", "currentColor"))
+}
+
+// Helper function to read the size (relative to the font size) of the given attribute of the root svg element
+fn svg_size(svg: &str, attribute: &str) -> Option<f32> {
+    let root = &svg[..svg.find('>')?];
+    let start = root.find(&format!(" {attribute}=\""))? + attribute.len() + 3;
+    let end = root[start..].find('"')? + start;
+
+    let size: f32 = root[start..end].trim_end_matches("pt").parse().ok()?;
+    Some(size / FONT_SIZE)
+}
+
+/// Converts a LaTeX formula to typst math, [`None`] if it uses something that is not supported
+fn latex_to_typst(latex: &str) -> Option<String> {
+    let mut converter = Converter {
+        chars: latex.chars().peekable(),
+        depth: 0,
+    };
+
+    let mut rows: Vec<String> = Vec::new();
+    let mut cells: Vec<String> = Vec::new();
+    loop {
+        let (content, stop) = converter.sequence()?;
+        cells.push(content);
+        match stop {
+            Stop::Cell => {}
+            Stop::Row => rows.push(std::mem::take(&mut cells).join(" & ")),
+            Stop::End => {
+                rows.push(cells.join(" & "));
+                break;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(rows.join(" \\ "))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Char(char),
+    Number(String),
+    Command(String),
+}
+
+/// Why a sequence of the formula ended
+#[derive(Debug, PartialEq)]
+enum Stop {
+    End,
+    /// `}`
+    Group,
+    /// `&`
+    Cell,
+    /// `\\`
+    Row,
+    /// `\right` with it's delimiter
+    Right(String),
+    /// `\end` with the name of the environment
+    Environment(String),
+}
+
+#[derive(Clone)]
+struct Converter<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// How nested the converter is (function arguments, delimiters and environments)
+    depth: usize,
+}
+
+impl Converter<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        self.skip_whitespace();
+        let c = self.chars.next()?;
+
+        if c == '\\' {
+            let mut name = String::new();
+            while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+                name.push(c);
+            }
+            if name.is_empty() {
+                name.push(self.chars.next()?);
+            }
+            return Some(Token::Command(name));
+        }
+
+        if c.is_ascii_digit() {
+            let mut number = String::from(c);
+            loop {
+                if let Some(c) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                    number.push(c);
+                    continue;
+                }
+                // A dot is only part of the number if there are digits after it
+                let mut lookahead = self.chars.clone();
+                if lookahead.next() == Some('.')
+                    && lookahead.next().is_some_and(|c| c.is_ascii_digit())
+                {
+                    number.push(self.chars.next()?);
+                    continue;
+                }
+                break;
+            }
+            return Some(Token::Number(number));
+        }
+
+        Some(Token::Char(c))
+    }
+
+    fn peek_token(&self) -> Option<Token> {
+        self.clone().next_token()
+    }
+
+    /// Converts the formula until the end of the current group, cell, row, delimiter or environment
+    fn sequence(&mut self) -> Option<(String, Stop)> {
+        let mut atoms: Vec<String> = Vec::new();
+
+        let stop = loop {
+            let Some(token) = self.next_token() else {
+                break Stop::End;
+            };
+
+            match token {
+                Token::Char('}') => break Stop::Group,
+                Token::Char('&') => break Stop::Cell,
+                Token::Command(name) if name == "\\" || name == "cr" => break Stop::Row,
+                Token::Command(name) if name == "right" => break Stop::Right(self.delimiter()?),
+                Token::Command(name) if name == "end" => {
+                    break Stop::Environment(self.raw_group()?);
+                }
+                Token::Char(c @ ('^' | '_')) => {
+                    let script = self.argument()?;
+                    let base = atoms.pop().unwrap_or_else(|| String::from("\"\""));
+                    atoms.push(format!("{base}{c}({script})"));
+                }
+                Token::Char('\'') => match atoms.last_mut() {
+                    Some(last) => last.push('\''),
+                    None => atoms.push(String::from("prime")),
+                },
+                Token::Command(name) if name == "limits" || name == "nolimits" => {
+                    if let Some(last) = atoms.pop() {
+                        let function = if name == "limits" {
+                            "limits"
+                        } else {
+                            "scripts"
+                        };
+                        atoms.push(format!("{function}({last})"));
+                    }
+                }
+                token => {
+                    let atom = self.atom(token)?;
+                    if !atom.is_empty() {
+                        atoms.push(atom);
+                    }
+                }
+            }
+        };
+
+        Some((atoms.join(" "), stop))
+    }
+
+    /// Converts the content of a group, the opening `{` has already been read
+    fn group(&mut self) -> Option<String> {
+        self.depth += 1;
+        let (content, stop) = self.sequence()?;
+        self.depth -= 1;
+
+        (stop == Stop::Group).then_some(content)
+    }
+
+    /// Converts the argument of a command or script, a group or a single token
+    fn argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        // Only the first digit of a number is the argument (`\frac12`)
+        if let Some(digit) = self.chars.next_if(|c| c.is_ascii_digit()) {
+            return Some(String::from(digit));
+        }
+
+        match self.next_token()? {
+            Token::Char('{') => self.group(),
+            Token::Char('}' | '&' | '^' | '_') => None,
+            token => {
+                self.depth += 1;
+                let atom = self.atom(token);
+                self.depth -= 1;
+                atom
+            }
+        }
+    }
+
+    /// Converts an optional `[...]` argument
+    fn optional_argument(&mut self) -> Option<Option<String>> {
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'[').is_none() {
+            return Some(None);
+        }
+
+        let mut raw = String::new();
+        let mut braces = 0;
+        loop {
+            match self.chars.next()? {
+                ']' if braces == 0 => break,
+                c => {
+                    match c {
+                        '{' => braces += 1,
+                        '}' => braces -= 1,
+                        _ => {}
+                    }
+                    raw.push(c);
+                }
+            }
+        }
+
+        let mut converter = Converter {
+            chars: raw.chars().peekable(),
+            depth: self.depth + 1,
+        };
+        match converter.sequence()? {
+            (content, Stop::End) => Some(Some(content)),
+            _ => None,
+        }
+    }
+
+    /// Reads the unconverted content of a `{...}` group, used for text and environment names
+    fn raw_group(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        self.chars.next_if_eq(&'{')?;
+
+        let mut raw = String::new();
+        let mut braces = 0;
+        loop {
+            match self.chars.next()? {
+                '}' if braces == 0 => break,
+                '\\' => {
+                    // Escaped characters keep their escape, they are handled by the caller
+                    raw.push('\\');
+                    raw.push(self.chars.next()?);
+                }
+                c => {
+                    match c {
+                        '{' => braces += 1,
+                        '}' => braces -= 1,
+                        _ => {}
+                    }
+                    raw.push(c);
+                }
+            }
+        }
+
+        Some(raw)
+    }
+
+    /// Reads a `{...}` group of text as a typst string
+    fn text(&mut self) -> Option<String> {
+        let raw = self.raw_group()?;
+
+        let mut text = String::from("\"");
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' => {}
+                '~' => text.push(' '),
+                '\\' => match chars.next()? {
+                    c @ ('{' | '}' | '$' | '%' | '&' | '#' | '_' | ' ') => text.push(c),
+                    '\\' => text.push(' '),
+                    _ => return None,
+                },
+                '"' => text.push_str("\\\""),
+                c => text.push(c),
+            }
+        }
+        text.push('"');
+
+        Some(text)
+    }
+
+    /// Converts the delimiter after `\left`, `\right` or `\big`
+    fn delimiter(&mut self) -> Option<String> {
+        match self.next_token()? {
+            Token::Char('.') => Some(String::new()),
+            Token::Char('|') => Some(String::from("|")),
+            Token::Char(c @ ('(' | ')' | '[' | ']' | '/')) => Some(format!("\\{c}")),
+            Token::Command(name) => match name.as_str() {
+                "{" | "lbrace" => Some(String::from("\\{")),
+                "}" | "rbrace" => Some(String::from("\\}")),
+                name => symbol(name).map(String::from),
+            },
+            _ => None,
+        }
+    }
+
+    /// Converts a single token (and the arguments of it if it's a command)
+    fn atom(&mut self, token: Token) -> Option<String> {
+        match token {
+            Token::Number(number) => Some(number),
+            Token::Char('{') => {
+                let content = self.group()?;
+                // Groups with more than an atom are kept together for the scripts
+                Some(if content.is_empty() {
+                    String::from("\"\"")
+                } else if content.contains(' ') {
+                    format!("lr({content})")
+                } else {
+                    content
+                })
+            }
+            Token::Char('~') => Some(String::from("space")),
+            // Bars are ordinary symbols in LaTeX, typst spaces them as fences
+            Token::Char('|') => Some(String::from("class(\"normal\", |)")),
+            Token::Char(c @ ('+' | '-' | '=' | '<' | '>' | '!' | '*')) => Some(String::from(c)),
+            Token::Char(c) if c.is_ascii_alphabetic() || !c.is_ascii() => Some(String::from(c)),
+            Token::Char(c) if c.is_ascii_punctuation() => Some(format!("\\{c}")),
+            Token::Char(_) => None,
+            Token::Command(name) => self.command(&name),
+        }
+    }
+
+    /// Converts a command and it's arguments
+    fn command(&mut self, name: &str) -> Option<String> {
+        let converted = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" | "binom" | "dbinom" | "tbinom" => {
+                let first = self.argument()?;
+                let second = self.argument()?;
+                let function = if name.ends_with("frac") {
+                    "frac"
+                } else {
+                    "binom"
+                };
+                match name.chars().next() {
+                    Some('d') => format!("display({function}({first}, {second}))"),
+                    Some('t') => format!("inline({function}({first}, {second}))"),
+                    _ => format!("{function}({first}, {second})"),
+                }
+            }
+            "sqrt" => match self.optional_argument()? {
+                Some(index) => format!("root({index}, {})", self.argument()?),
+                None => format!("sqrt({})", self.argument()?),
+            },
+            "text" | "textrm" | "textnormal" | "mbox" | "hbox" => self.text()?,
+            "textbf" => format!("bold({})", self.text()?),
+            "textit" | "emph" => format!("italic({})", self.text()?),
+            "texttt" => format!("mono({})", self.text()?),
+            "operatorname" => {
+                let limits = self.chars.next_if_eq(&'*').is_some();
+                let text = self.text()?;
+                if limits {
+                    format!("op({text}, limits: #true)")
+                } else {
+                    format!("op({text})")
+                }
+            }
+            "mathbf" => format!("bold(upright({}))", self.argument()?),
+            "overbrace" | "underbrace" => {
+                let content = self.argument()?;
+                let script = if name == "overbrace" { '^' } else { '_' };
+                if self.peek_token() == Some(Token::Char(script)) {
+                    self.next_token();
+                    format!("{name}({content}, {})", self.argument()?)
+                } else {
+                    format!("{name}({content})")
+                }
+            }
+            "overset" | "stackrel" | "underset" => {
+                let script = self.argument()?;
+                let content = self.argument()?;
+                let c = if name == "underset" { '_' } else { '^' };
+                format!("limits({content}){c}({script})")
+            }
+            "left" => {
+                let open = self.delimiter()?;
+                self.depth += 1;
+                let (content, stop) = self.sequence()?;
+                self.depth -= 1;
+                let Stop::Right(close) = stop else {
+                    return None;
+                };
+                format!("lr({open} {content} {close})")
+            }
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "Bigl" | "biggl" | "Biggl" | "bigr"
+            | "Bigr" | "biggr" | "Biggr" | "bigm" | "Bigm" => self.delimiter()?,
+            "begin" => {
+                let environment = self.raw_group()?;
+                self.environment(&environment)?
+            }
+            "not" => match self.next_token()? {
+                Token::Char('=') => String::from("eq.not"),
+                Token::Command(name) if name == "in" => String::from("in.not"),
+                Token::Command(name) if name == "equiv" => String::from("equiv.not"),
+                Token::Command(name) if name == "subset" => String::from("subset.not"),
+                _ => return None,
+            },
+            "bmod" => String::from("mod"),
+            "pmod" => format!("\\( mod {} \\)", self.argument()?),
+            "displaystyle" | "textstyle" | "scriptstyle" | "!" | "hline" | "nonumber" | "notag" => {
+                String::new()
+            }
+            "{" | "}" | "$" | "%" | "#" | "&" | "_" => format!("\\{name}"),
+            "," => String::from("thin"),
+            ":" | ">" => String::from("med"),
+            ";" => String::from("thick"),
+            " " => String::from("space"),
+            "quad" => String::from("quad"),
+            "qquad" => String::from("wide"),
+            name if FUNCTIONS.contains(&name) => String::from(name),
+            name => {
+                if let Some(style) = style(name) {
+                    format!("{style}({})", self.argument()?)
+                } else {
+                    match symbol(name)? {
+                        bar if bar.starts_with("bar.v") => format!("class(\"normal\", {bar})"),
+                        symbol => String::from(symbol),
+                    }
+                }
+            }
+        };
+
+        Some(converted)
+    }
+
+    /// Converts an environment, the `\begin{...}` has already been read
+    fn environment(&mut self, name: &str) -> Option<String> {
+        if name == "array" {
+            // The column alignment is not supported, it's just skipped
+            self.raw_group()?;
+        }
+
+        self.depth += 1;
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut cells: Vec<String> = Vec::new();
+        loop {
+            let (content, stop) = self.sequence()?;
+            cells.push(content);
+            match stop {
+                Stop::Cell => {}
+                Stop::Row => rows.push(std::mem::take(&mut cells)),
+                Stop::Environment(end) if end == name => {
+                    rows.push(cells);
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        self.depth -= 1;
+
+        // A trailing `\\` doesn't start another row
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|row| row.len() == 1 && row[0].is_empty())
+        {
+            rows.pop();
+        }
+
+        let delimiter = match name {
+            "matrix" | "smallmatrix" | "array" => Some("#none"),
+            "pmatrix" => Some("\"(\""),
+            "bmatrix" => Some("\"[\""),
+            "Bmatrix" => Some("\"{\""),
+            "vmatrix" => Some("\"|\""),
+            "Vmatrix" => Some("\"||\""),
+            _ => None,
+        };
+        if let Some(delimiter) = delimiter {
+            let rows: Vec<String> = rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|cell| {
+                            if cell.is_empty() {
+                                String::from("\"\"")
+                            } else {
+                                cell
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            return Some(format!("mat(delim: {delimiter}, {})", rows.join("; ")));
+        }
+
+        let rows: Vec<String> = rows.into_iter().map(|row| row.join(" & ")).collect();
+        match name {
+            "cases" => Some(format!("cases({})", rows.join(", "))),
+            "aligned" | "align" | "align*" | "alignat" | "alignat*" | "gathered" | "gather"
+            | "gather*" | "split" | "equation" | "equation*" | "multline" | "multline*" => {
+                // Line breaks only work outside of functions, nested rows are stacked instead
+                if self.depth == 0 {
+                    Some(rows.join(" \\ "))
+                } else {
+                    Some(format!("vec(delim: #none, {})", rows.join(", ")))
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Operator names that are the same in typst
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "coth", "csc", "deg", "det", "dim",
+    "exp", "gcd", "hom", "inf", "ker", "lg", "lim", "liminf", "limsup", "ln", "log", "max", "min",
+    "mod", "Pr", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+// Helper function to get the typst function of a LaTeX style or accent command
+fn style(name: &str) -> Option<&'static str> {
+    let style = match name {
+        "mathrm" => "upright",
+        "mathit" => "italic",
+        "mathcal" | "mathscr" => "cal",
+        "mathbb" => "bb",
+        "mathfrak" => "frak",
+        "mathsf" => "sans",
+        "mathtt" => "mono",
+        "boldsymbol" | "bm" => "bold",
+        "hat" | "widehat" => "hat",
+        "tilde" | "widetilde" => "tilde",
+        "bar" => "macron",
+        "overline" => "overline",
+        "underline" => "underline",
+        "vec" | "overrightarrow" => "arrow",
+        "overleftarrow" => "arrow.l",
+        "dot" => "dot",
+        "ddot" => "dot.double",
+        "acute" => "acute",
+        "grave" => "grave",
+        "breve" => "breve",
+        "check" => "caron",
+        _ => return None,
+    };
+    Some(style)
+}
+
+// Helper function to get the typst symbol of a LaTeX symbol command
+fn symbol(name: &str) -> Option<&'static str> {
+    let symbol = match name {
+        // Greek letters
+        "alpha" => "alpha",
+        "beta" => "beta",
+        "gamma" => "gamma",
+        "delta" => "delta",
+        "epsilon" => "epsilon.alt",
+        "varepsilon" => "epsilon",
+        "zeta" => "zeta",
+        "eta" => "eta",
+        "theta" => "theta",
+        "vartheta" => "theta.alt",
+        "iota" => "iota",
+        "kappa" => "kappa",
+        "lambda" => "lambda",
+        "mu" => "mu",
+        "nu" => "nu",
+        "xi" => "xi",
+        "omicron" => "omicron",
+        "pi" => "pi",
+        "varpi" => "pi.alt",
+        "rho" => "rho",
+        "varrho" => "rho.alt",
+        "sigma" => "sigma",
+        "varsigma" => "sigma.alt",
+        "tau" => "tau",
+        "upsilon" => "upsilon",
+        "phi" => "phi.alt",
+        "varphi" => "phi",
+        "chi" => "chi",
+        "psi" => "psi",
+        "omega" => "omega",
+        "Gamma" => "Gamma",
+        "Delta" => "Delta",
+        "Theta" => "Theta",
+        "Lambda" => "Lambda",
+        "Xi" => "Xi",
+        "Pi" => "Pi",
+        "Sigma" => "Sigma",
+        "Upsilon" => "Upsilon",
+        "Phi" => "Phi",
+        "Psi" => "Psi",
+        "Omega" => "Omega",
+        // Binary operators
+        "pm" => "plus.minus",
+        "mp" => "minus.plus",
+        "times" => "times",
+        "div" => "div",
+        "cdot" => "dot.op",
+        "ast" => "ast",
+        "star" => "star",
+        "circ" => "compose",
+        "bullet" => "bullet",
+        "oplus" => "plus.circle",
+        "ominus" => "minus.circle",
+        "otimes" => "times.circle",
+        "odot" => "dot.circle",
+        "cap" => "sect",
+        "cup" => "union",
+        "setminus" => "without",
+        "wedge" | "land" => "and",
+        "vee" | "lor" => "or",
+        "neg" | "lnot" => "not",
+        // Relations
+        "le" | "leq" => "lt.eq",
+        "ge" | "geq" => "gt.eq",
+        "ne" | "neq" => "eq.not",
+        "equiv" => "equiv",
+        "approx" => "approx",
+        "sim" => "tilde.op",
+        "simeq" => "tilde.eq",
+        "cong" => "tilde.equiv",
+        "propto" => "prop",
+        "ll" => "lt.double",
+        "gg" => "gt.double",
+        "prec" => "prec",
+        "succ" => "succ",
+        "preceq" => "prec.eq",
+        "succeq" => "succ.eq",
+        "subset" => "subset",
+        "supset" => "supset",
+        "subseteq" => "subset.eq",
+        "supseteq" => "supset.eq",
+        "in" => "in",
+        "notin" => "in.not",
+        "ni" => "in.rev",
+        "mid" => "divides",
+        "parallel" => "parallel",
+        "perp" => "perp",
+        "vdash" => "tack.r",
+        "models" => "models",
+        "coloneqq" => "colon.eq",
+        // Arrows
+        "to" | "rightarrow" => "arrow.r",
+        "gets" | "leftarrow" => "arrow.l",
+        "leftrightarrow" => "arrow.l.r",
+        "Rightarrow" => "arrow.r.double",
+        "Leftarrow" => "arrow.l.double",
+        "Leftrightarrow" => "arrow.l.r.double",
+        "longrightarrow" => "arrow.r.long",
+        "longleftarrow" => "arrow.l.long",
+        "longleftrightarrow" => "arrow.l.r.long",
+        "Longrightarrow" | "implies" => "arrow.r.double.long",
+        "Longleftarrow" | "impliedby" => "arrow.l.double.long",
+        "Longleftrightarrow" | "iff" => "arrow.l.r.double.long",
+        "mapsto" => "arrow.r.bar",
+        "longmapsto" => "arrow.r.long.bar",
+        "hookrightarrow" => "arrow.r.hook",
+        "uparrow" => "arrow.t",
+        "downarrow" => "arrow.b",
+        "Uparrow" => "arrow.t.double",
+        "Downarrow" => "arrow.b.double",
+        "nearrow" => "arrow.tr",
+        "searrow" => "arrow.br",
+        "rightleftharpoons" => "harpoons.rtlb",
+        // Big operators
+        "sum" => "sum",
+        "prod" => "product",
+        "coprod" => "product.co",
+        "int" => "integral",
+        "iint" => "integral.double",
+        "iiint" => "integral.triple",
+        "oint" => "integral.cont",
+        "bigcup" => "union.big",
+        "bigcap" => "sect.big",
+        "bigoplus" => "plus.circle.big",
+        "bigotimes" => "times.circle.big",
+        "bigvee" => "or.big",
+        "bigwedge" => "and.big",
+        // Delimiters
+        "langle" => "angle.l",
+        "rangle" => "angle.r",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" | "lvert" | "rvert" => "bar.v",
+        "|" | "Vert" | "lVert" | "rVert" => "bar.v.double",
+        "backslash" => "backslash",
+        // Others
+        "infty" => "infinity",
+        "partial" => "diff",
+        "nabla" => "nabla",
+        "forall" => "forall",
+        "exists" => "exists",
+        "nexists" => "exists.not",
+        "emptyset" | "varnothing" => "nothing",
+        "ell" => "ell",
+        "hbar" => "planck.reduce",
+        "Re" => "Re",
+        "Im" => "Im",
+        "aleph" => "aleph",
+        "angle" => "angle",
+        "triangle" => "triangle",
+        "prime" => "prime",
+        "dagger" => "dagger",
+        "ldots" | "dots" => "dots.h",
+        "cdots" => "dots.h.c",
+        "vdots" => "dots.v",
+        "ddots" => "dots.down",
+        "therefore" => "therefore",
+        "because" => "because",
+        "top" => "top",
+        "bot" => "bot",
+        "checkmark" => "checkmark",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_scripts_and_fractions() {
+        assert_eq!(
+            latex_to_typst(r"\frac{-b \pm \sqrt{b^2 - 4ac}}{2a}").as_deref(),
+            Some("frac(- b plus.minus sqrt(b^(2) - 4 a c), 2 a)")
+        );
+        assert_eq!(
+            latex_to_typst(r"\sum_{i=0}^n x_i'").as_deref(),
+            Some("sum_(i = 0)^(n) x_(i)'")
+        );
+        assert_eq!(latex_to_typst(r"\frac12").as_deref(), Some("frac(1, 2)"));
+    }
+
+    #[test]
+    fn escapes_typst_syntax() {
+        // Commas, parentheses and slashes would otherwise be arguments, groups or fractions in typst
+        assert_eq!(
+            latex_to_typst(r"f(a, b) / 2").as_deref(),
+            Some(r"f \( a \, b \) \/ 2")
+        );
+        assert_eq!(
+            latex_to_typst(r#"\text{a "b"}"#).as_deref(),
+            Some(r#""a \"b\"""#)
+        );
+    }
+
+    #[test]
+    fn converts_delimiters_and_environments() {
+        assert_eq!(
+            latex_to_typst(r"\left( x \right]").as_deref(),
+            Some(r"lr(\( x \])")
+        );
+        assert_eq!(
+            latex_to_typst(r"\begin{pmatrix} a & b \\ c & d \end{pmatrix}").as_deref(),
+            Some(r#"mat(delim: "(", a, b; c, d)"#)
+        );
+        assert_eq!(
+            latex_to_typst(r"\begin{cases} 1 & x > 0 \\ 0 & \text{otherwise} \end{cases}")
+                .as_deref(),
+            Some(r#"cases(1 & x > 0, 0 & "otherwise")"#)
+        );
+        assert_eq!(
+            latex_to_typst(r"a &= b \\ c &= d").as_deref(),
+            Some(r"a & = b \ c & = d")
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_formulas() {
+        assert_eq!(latex_to_typst(r"\unknown{x}"), None);
+        assert_eq!(latex_to_typst(r"\frac{a}{b"), None);
+        assert_eq!(latex_to_typst(r"\left( x"), None);
+        assert_eq!(latex_to_typst(r"a } b"), None);
+        assert_eq!(latex_to_typst(r"\begin{pmatrix} a \end{bmatrix}"), None);
+    }
+
+    #[test]
+    fn renders_formulas() {
+        let svg = render_svg(r"\int_0^1 x^2 \, dx = \frac{1}{3}", false).unwrap();
+        assert!(svg.contains("currentColor"));
+        assert!(svg_size(&svg, "width").is_some_and(|width| width > 0.0));
+        assert!(render_svg(r"\unknown", true).is_err());
+    }
+}
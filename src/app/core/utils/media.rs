@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Kinds of files Oboete keeps it's own copy of, each one on it's own directory of the data path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Audio,
+}
+
+impl MediaKind {
+    // Helper function to get the directory where Oboete keeps the files of this kind
+    fn dir(self) -> Option<PathBuf> {
+        let name = match self {
+            Self::Image => "images",
+            Self::Audio => "audio",
+        };
+        dirs::data_dir().map(|dir| dir.join(super::APP_ID).join(name))
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Image => "Image",
+            Self::Audio => "Audio",
+        }
+    }
+}
+
+/// Returns true if the path corresponds to a file of the given kind already saved in the Oboete directory,
+/// the file has to be directly inside of it (so a path with `..` can't point outside of it)
+pub fn is_managed(kind: MediaKind, path: &str) -> bool {
+    let Some(output_path) = kind.dir() else {
+        return false;
+    };
+
+    let path = Path::new(path);
+    path.file_name().is_some() && path.parent() == Some(output_path.as_path())
+}
+
+/// Attempts to save a copy of the file of the given path on the Oboete directory of it's kind, returns the path of the copy
+pub fn save_media(kind: MediaKind, path: &str) -> Result<String, anywho::Error> {
+    let output_path = kind
+        .dir()
+        .ok_or_else(|| anywho::anywho!("Failed to get data directory"))?;
+
+    if !output_path.exists() {
+        fs::create_dir_all(&output_path)?;
+    }
+
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .ok_or_else(|| anywho::anywho!("Failed to get file extension"))?;
+
+    let new_filename = format!("{}.{}", uuid::Uuid::new_v4(), extension);
+    let destination = output_path.join(&new_filename);
+
+    fs::copy(path, &destination)?;
+
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// Deletes the file of the given path
+pub async fn delete_media(kind: MediaKind, path: String) -> Result<(), anywho::Error> {
+    if tokio::fs::metadata(&path).await.is_err() {
+        return Err(anywho::anywho!(
+            "{} file does not exist: {}",
+            kind.label(),
+            path
+        ));
+    }
+
+    tokio::fs::remove_file(&path).await?;
+
+    Ok(())
+}
@@ -10,10 +10,13 @@ use crate::{
         Message, StudySet,
         core::{
             models::{
-                flashcard::{Flashcard, FlashcardField},
+                bulk::BulkEdit,
+                flashcard::Flashcard,
                 folder::Folder,
+                note::NoteType,
+                trash::{TrashItem, TrashKind},
             },
-            utils::{self, OboeteToast},
+            utils::{Clock, OboeteToast},
         },
     },
     fl,
@@ -30,10 +33,36 @@ pub enum DialogPage {
     DeleteStudySet,
     /// Dialog for confirming the deletion of a [`Folder`]
     DeleteFolder(i32),
-    /// Dialog for creating a new [`Folder`]
-    NewFolder(String),
+    /// Dialog for creating a new [`Folder`] (optionally inside of the given parent folder)
+    NewFolder {
+        name: String,
+        parent_id: Option<i32>,
+    },
     /// Dialog for confirming the deletion of a [`Flashcard`]
     DeleteFlashcard(Flashcard),
+    /// Dialog for choosing the folder (of any [`StudySet`]) some flashcards are moved or copied to
+    TransferFlashcards {
+        flashcards: Vec<Flashcard>,
+        /// Copy the flashcards instead of moving them
+        copy: bool,
+        folder_ids: Vec<i32>,
+        /// Path (study set / folder) of each folder, same order as the ids
+        folder_paths: Vec<String>,
+        selected: Option<usize>,
+        /// Copies keep the progress of the flashcards (moved flashcards always keep it)
+        keep_progress: bool,
+    },
+    /// Dialog for confirming a [`BulkEdit`] of the flashcards with the given ids
+    BulkEdit {
+        flashcard_ids: Vec<i32>,
+        edit: BulkEdit,
+    },
+    /// Dialog for confirming the deletion of a [`NoteType`]
+    DeleteNoteType(i32),
+    /// Dialog for confirming the purge of a [`TrashItem`]
+    PurgeTrashItem(TrashItem),
+    /// Dialog for confirming the purge of everything on the trash
+    EmptyTrash,
 }
 
 impl DialogPage {
@@ -42,6 +71,7 @@ impl DialogPage {
         &self,
         database: &Arc<Pool<Sqlite>>,
         nav: &cosmic::widget::nav_bar::Model,
+        clock: &dyn Clock,
     ) -> Task<cosmic::Action<crate::app::Message>> {
         match &self {
             DialogPage::NewStudySet(studyset_name) => {
@@ -66,6 +96,7 @@ impl DialogPage {
                                 StudySet {
                                     id: Some(*set_id),
                                     name: studyset_name.to_string(),
+                                    ..Default::default()
                                 },
                             ),
                             move |result| match result {
@@ -80,7 +111,7 @@ impl DialogPage {
             DialogPage::DeleteStudySet => {
                 if let Some(set_id) = nav.active_data::<i32>() {
                     return Task::perform(
-                        StudySet::delete(Arc::clone(database), *set_id),
+                        StudySet::delete(Arc::clone(database), *set_id, clock.now()),
                         move |result| match result {
                             Ok(_) => cosmic::action::app(Message::FetchStudySets),
                             Err(_) => cosmic::action::none(),
@@ -90,7 +121,7 @@ impl DialogPage {
                 Task::none()
             }
             DialogPage::DeleteFolder(folder_id) => Task::perform(
-                Folder::delete(Arc::clone(database), *folder_id),
+                Folder::delete(Arc::clone(database), *folder_id, clock.now()),
                 move |result| match result {
                     Ok(_) => cosmic::action::app(Message::Folders(
                         super::screen::folders::Message::LoadFolders,
@@ -98,7 +129,10 @@ impl DialogPage {
                     Err(_) => cosmic::action::none(),
                 },
             ),
-            DialogPage::NewFolder(folder_name) => {
+            DialogPage::NewFolder {
+                name: folder_name,
+                parent_id,
+            } => {
                 #[allow(clippy::collapsible_if)]
                 if let Some(set_id) = nav.active_data::<i32>() {
                     if !folder_name.is_empty() {
@@ -109,6 +143,7 @@ impl DialogPage {
                                 set_id_clone,
                                 Folder {
                                     name: folder_name.to_string(),
+                                    parent_id: *parent_id,
                                     ..Default::default()
                                 },
                             ),
@@ -123,51 +158,108 @@ impl DialogPage {
                 }
                 Task::none()
             }
-            DialogPage::DeleteFlashcard(flashcard) => {
-                let front_task = if let FlashcardField::Image { path, .. } = &flashcard.front {
-                    if !path.is_empty() && flashcard.id.is_some() {
-                        Task::perform(utils::delete_image(path.to_string()), |res| match res {
-                            Ok(_) => cosmic::action::none(),
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
-                            }
-                        })
-                    } else {
-                        Task::none()
-                    }
-                } else {
-                    Task::none()
+            // The images and audios of the flashcard are kept until it's purged from the trash
+            DialogPage::DeleteFlashcard(flashcard) => Task::perform(
+                Flashcard::delete(
+                    Arc::clone(database),
+                    flashcard.id.unwrap_or_default(),
+                    clock.now(),
+                ),
+                move |result| match result {
+                    Ok(_) => cosmic::action::app(Message::Flashcards(
+                        super::screen::flashcards::Message::LoadFlashcards,
+                    )),
+                    Err(_) => cosmic::action::none(),
+                },
+            ),
+            DialogPage::TransferFlashcards {
+                flashcards,
+                copy,
+                folder_ids,
+                selected,
+                keep_progress,
+                ..
+            } => {
+                let Some(folder_id) = selected.and_then(|index| folder_ids.get(index)).copied()
+                else {
+                    return Task::none();
                 };
 
-                let back_task = if let FlashcardField::Image { path, .. } = &flashcard.back {
-                    if !path.is_empty() && flashcard.id.is_some() {
-                        Task::perform(utils::delete_image(path.to_string()), |res| match res {
-                            Ok(_) => cosmic::action::none(),
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
-                            }
-                        })
-                    } else {
-                        Task::none()
+                let on_result = |result: Result<(), anywho::Error>| match result {
+                    Ok(_) => cosmic::action::app(Message::Flashcards(
+                        super::screen::flashcards::Message::LoadFlashcards,
+                    )),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
                     }
-                } else {
-                    Task::none()
                 };
 
-                Task::perform(
-                    Flashcard::delete(Arc::clone(database), flashcard.id.unwrap_or_default()),
-                    move |result| match result {
-                        Ok(_) => cosmic::action::app(Message::Flashcards(
-                            super::screen::flashcards::Message::LoadFlashcards,
-                        )),
-                        Err(_) => cosmic::action::none(),
-                    },
-                )
-                .chain(front_task)
-                .chain(back_task)
+                if *copy {
+                    Task::perform(
+                        Flashcard::copy_to(
+                            Arc::clone(database),
+                            flashcards.clone(),
+                            folder_id,
+                            *keep_progress,
+                        ),
+                        on_result,
+                    )
+                } else {
+                    Task::perform(
+                        Flashcard::move_to(Arc::clone(database), flashcards.clone(), folder_id),
+                        on_result,
+                    )
+                }
             }
+            DialogPage::BulkEdit {
+                flashcard_ids,
+                edit,
+            } => Task::perform(
+                edit.clone()
+                    .apply(Arc::clone(database), flashcard_ids.clone(), clock.now()),
+                move |result| match result {
+                    Ok(_) => cosmic::action::app(Message::Flashcards(
+                        super::screen::flashcards::Message::BulkEditApplied,
+                    )),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                    }
+                },
+            ),
+            DialogPage::DeleteNoteType(note_type_id) => Task::perform(
+                NoteType::delete(Arc::clone(database), *note_type_id),
+                move |result| match result {
+                    Ok(_) => cosmic::action::app(Message::NoteTypes(
+                        super::screen::note_types::Message::LoadNoteTypes,
+                    )),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                    }
+                },
+            ),
+            DialogPage::PurgeTrashItem(item) => Task::perform(
+                TrashItem::purge(Arc::clone(database), item.clone()),
+                move |result| match result {
+                    Ok(folder_ids) => cosmic::action::app(Message::FoldersPurged(folder_ids)),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                    }
+                },
+            ),
+            DialogPage::EmptyTrash => Task::perform(
+                TrashItem::purge_all(Arc::clone(database), None),
+                move |result| match result {
+                    Ok(folder_ids) => cosmic::action::app(Message::FoldersPurged(folder_ids)),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        cosmic::action::app(Message::AddToast(OboeteToast::new(e)))
+                    }
+                },
+            ),
         }
     }
 
@@ -228,7 +320,7 @@ impl DialogPage {
                 ),
             DialogPage::DeleteStudySet => widget::dialog()
                 .title(fl!("delete-studyset"))
-                .body(fl!("confirm-delete"))
+                .body(fl!("confirm-move-to-trash"))
                 .primary_action(
                     widget::button::suggested(fl!("ok"))
                         .on_press_maybe(Some(Message::DialogAction(DialogAction::DialogComplete))),
@@ -239,7 +331,7 @@ impl DialogPage {
                 ),
             DialogPage::DeleteFolder(_folder_id) => widget::dialog()
                 .title(fl!("delete-folder"))
-                .body(fl!("confirm-delete"))
+                .body(fl!("confirm-move-to-trash"))
                 .primary_action(
                     widget::button::suggested(fl!("ok"))
                         .on_press_maybe(Some(Message::DialogAction(DialogAction::DialogComplete))),
@@ -248,8 +340,15 @@ impl DialogPage {
                     widget::button::standard(fl!("cancel"))
                         .on_press(Message::DialogAction(DialogAction::DialogCancel)),
                 ),
-            DialogPage::NewFolder(folder_name) => widget::dialog()
-                .title(fl!("create-folder"))
+            DialogPage::NewFolder {
+                name: folder_name,
+                parent_id,
+            } => widget::dialog()
+                .title(if parent_id.is_some() {
+                    fl!("create-subfolder")
+                } else {
+                    fl!("create-folder")
+                })
                 .primary_action(
                     widget::button::suggested(fl!("save"))
                         .on_press_maybe(Some(Message::DialogAction(DialogAction::DialogComplete))),
@@ -265,7 +364,10 @@ impl DialogPage {
                             .id(dialog_state.dialog_text_input.clone())
                             .on_input(move |name| {
                                 Message::DialogAction(DialogAction::DialogUpdate(
-                                    DialogPage::NewFolder(name),
+                                    DialogPage::NewFolder {
+                                        name,
+                                        parent_id: *parent_id,
+                                    },
                                 ))
                             })
                             .on_submit(|_x| Message::DialogAction(DialogAction::DialogComplete))
@@ -275,6 +377,128 @@ impl DialogPage {
                 ),
             DialogPage::DeleteFlashcard(_flashcard) => widget::dialog()
                 .title(fl!("delete-flashcard"))
+                .body(fl!("confirm-move-to-trash"))
+                .primary_action(
+                    widget::button::suggested(fl!("ok"))
+                        .on_press_maybe(Some(Message::DialogAction(DialogAction::DialogComplete))),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::DialogAction(DialogAction::DialogCancel)),
+                ),
+            DialogPage::TransferFlashcards {
+                flashcards,
+                copy,
+                folder_paths,
+                selected,
+                keep_progress,
+                ..
+            } => {
+                let page = self.clone();
+                let folder_dropdown = widget::dropdown(folder_paths, *selected, move |index| {
+                    let mut page = page.clone();
+                    if let DialogPage::TransferFlashcards { selected, .. } = &mut page {
+                        *selected = Some(index);
+                    }
+                    Message::DialogAction(DialogAction::DialogUpdate(page))
+                });
+
+                let page = self.clone();
+                let keep_progress_toggler = copy.then(|| {
+                    widget::row::with_children(vec![
+                        widget::text::body(fl!("keep-progress"))
+                            .width(cosmic::iced::Length::Fill)
+                            .into(),
+                        widget::toggler(*keep_progress)
+                            .on_toggle(move |value| {
+                                let mut page = page.clone();
+                                if let DialogPage::TransferFlashcards { keep_progress, .. } =
+                                    &mut page
+                                {
+                                    *keep_progress = value;
+                                }
+                                Message::DialogAction(DialogAction::DialogUpdate(page))
+                            })
+                            .into(),
+                    ])
+                    .align_y(cosmic::iced::Alignment::Center)
+                });
+
+                widget::dialog()
+                    .title(if *copy {
+                        fl!("copy-flashcards", count = flashcards.len())
+                    } else {
+                        fl!("move-flashcards", count = flashcards.len())
+                    })
+                    .primary_action(
+                        widget::button::suggested(if *copy { fl!("copy") } else { fl!("move") })
+                            .on_press_maybe(
+                                selected
+                                    .map(|_| Message::DialogAction(DialogAction::DialogComplete)),
+                            ),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::DialogAction(DialogAction::DialogCancel)),
+                    )
+                    .control(
+                        widget::column::with_capacity(3)
+                            .push(widget::text::body(fl!("destination-folder")))
+                            .push(folder_dropdown)
+                            .push_maybe(keep_progress_toggler)
+                            .spacing(spacing.space_xxs),
+                    )
+            }
+            DialogPage::BulkEdit {
+                flashcard_ids,
+                edit,
+            } => {
+                let count = flashcard_ids.len();
+                let dialog = widget::dialog()
+                    .title(match edit {
+                        BulkEdit::Delete => fl!("delete-flashcards", count = count),
+                        BulkEdit::ResetStatus => fl!("reset-flashcards", count = count),
+                        BulkEdit::Suspend(true) => fl!("suspend-flashcards", count = count),
+                        BulkEdit::Suspend(false) => fl!("unsuspend-flashcards", count = count),
+                        BulkEdit::AddTags(_) => fl!("tag-flashcards", count = count),
+                    })
+                    .primary_action(
+                        widget::button::suggested(fl!("ok")).on_press_maybe(
+                            (!matches!(edit, BulkEdit::AddTags(tags) if tags.trim().is_empty()))
+                                .then_some(Message::DialogAction(DialogAction::DialogComplete)),
+                        ),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::DialogAction(DialogAction::DialogCancel)),
+                    );
+
+                match edit {
+                    BulkEdit::Delete => dialog.body(fl!("confirm-move-to-trash")),
+                    BulkEdit::ResetStatus => dialog.body(fl!("confirm-bulk-reset")),
+                    BulkEdit::Suspend(_) => dialog,
+                    BulkEdit::AddTags(tags) => dialog.control(
+                        widget::column::with_children(vec![
+                            widget::text::body(fl!("tags")).into(),
+                            widget::text_input(fl!("new-tag-placeholder"), tags.as_str())
+                                .id(dialog_state.dialog_text_input.clone())
+                                .on_input(move |tags| {
+                                    Message::DialogAction(DialogAction::DialogUpdate(
+                                        DialogPage::BulkEdit {
+                                            flashcard_ids: flashcard_ids.clone(),
+                                            edit: BulkEdit::AddTags(tags),
+                                        },
+                                    ))
+                                })
+                                .on_submit(|_x| Message::DialogAction(DialogAction::DialogComplete))
+                                .into(),
+                        ])
+                        .spacing(spacing.space_xxs),
+                    ),
+                }
+            }
+            DialogPage::DeleteNoteType(_note_type_id) => widget::dialog()
+                .title(fl!("delete-note-type"))
                 .body(fl!("confirm-delete"))
                 .primary_action(
                     widget::button::suggested(fl!("ok"))
@@ -284,6 +508,32 @@ impl DialogPage {
                     widget::button::standard(fl!("cancel"))
                         .on_press(Message::DialogAction(DialogAction::DialogCancel)),
                 ),
+            DialogPage::PurgeTrashItem(item) => widget::dialog()
+                .title(match item.kind {
+                    TrashKind::StudySet => fl!("delete-studyset"),
+                    TrashKind::Folder => fl!("delete-folder"),
+                    TrashKind::Flashcard => fl!("delete-flashcard"),
+                })
+                .body(fl!("confirm-purge"))
+                .primary_action(
+                    widget::button::destructive(fl!("delete-permanently"))
+                        .on_press(Message::DialogAction(DialogAction::DialogComplete)),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::DialogAction(DialogAction::DialogCancel)),
+                ),
+            DialogPage::EmptyTrash => widget::dialog()
+                .title(fl!("empty-trash"))
+                .body(fl!("confirm-purge"))
+                .primary_action(
+                    widget::button::destructive(fl!("delete-permanently"))
+                        .on_press(Message::DialogAction(DialogAction::DialogComplete)),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::DialogAction(DialogAction::DialogCancel)),
+                ),
         };
 
         Some(dialog.into())
@@ -299,12 +549,26 @@ pub enum DialogAction {
     OpenRenameStudySetDialog,
     /// Asks to open the [`DialogPage`] for confirming the deletion of a [`StudySet`]
     OpenDeleteStudySetDialog,
-    /// Asks to open the [`DialogPage`] for creating a new [`Folder`]
-    OpenCreateFolderDialog,
+    /// Asks to open the [`DialogPage`] for creating a new [`Folder`] (optionally inside of the given parent folder)
+    OpenCreateFolderDialog(Option<i32>),
     /// Asks to open the [`DialogPage`] for confirming the deletion of a [`Folder`]
     OpenDeleteFolderDialog(i32),
     /// Asks to open the [`DialogPage`] for confirming the deletion of a [`Flashcard`]
     OpenDeleteFlashcardDialog(Flashcard),
+    /// Asks to open the [`DialogPage`] for moving (or copying) some flashcards to one of the given folders (id, path)
+    OpenTransferFlashcardsDialog {
+        flashcards: Vec<Flashcard>,
+        copy: bool,
+        folders: Vec<(i32, String)>,
+    },
+    /// Asks to open the [`DialogPage`] for confirming a [`BulkEdit`] of the flashcards with the given ids
+    OpenBulkEditDialog(Vec<i32>, BulkEdit),
+    /// Asks to open the [`DialogPage`] for confirming the deletion of a [`NoteType`]
+    OpenDeleteNoteTypeDialog(i32),
+    /// Asks to open the [`DialogPage`] for confirming the purge of a [`TrashItem`]
+    OpenPurgeTrashItemDialog(TrashItem),
+    /// Asks to open the [`DialogPage`] for confirming the purge of everything on the trash
+    OpenEmptyTrashDialog,
     /// Action after user confirms/ok's/accepts the action of a Dialog
     DialogComplete,
     /// Action after user cancels the action of a Dialog
@@ -321,6 +585,7 @@ impl DialogAction {
         dialog_state: &DialogState,
         database: &Arc<Pool<Sqlite>>,
         nav: &cosmic::widget::nav_bar::Model,
+        clock: &dyn Clock,
     ) -> Task<cosmic::Action<Message>> {
         match self {
             DialogAction::OpenNewStudySetDialog => {
@@ -342,8 +607,11 @@ impl DialogAction {
                 }
                 Task::none()
             }
-            DialogAction::OpenCreateFolderDialog => {
-                dialog_pages.push_back(DialogPage::NewFolder(String::new()));
+            DialogAction::OpenCreateFolderDialog(parent_id) => {
+                dialog_pages.push_back(DialogPage::NewFolder {
+                    name: String::new(),
+                    parent_id,
+                });
                 widget::text_input::focus(dialog_state.dialog_text_input.clone())
             }
             DialogAction::OpenDeleteFolderDialog(folder_id) => {
@@ -354,9 +622,48 @@ impl DialogAction {
                 dialog_pages.push_back(DialogPage::DeleteFlashcard(flashcard));
                 Task::none()
             }
+            DialogAction::OpenTransferFlashcardsDialog {
+                flashcards,
+                copy,
+                folders,
+            } => {
+                let (folder_ids, folder_paths) = folders.into_iter().unzip();
+                dialog_pages.push_back(DialogPage::TransferFlashcards {
+                    flashcards,
+                    copy,
+                    folder_ids,
+                    folder_paths,
+                    selected: None,
+                    keep_progress: false,
+                });
+                Task::none()
+            }
+            DialogAction::OpenBulkEditDialog(flashcard_ids, edit) => {
+                let focus = matches!(edit, BulkEdit::AddTags(_));
+                dialog_pages.push_back(DialogPage::BulkEdit {
+                    flashcard_ids,
+                    edit,
+                });
+                if focus {
+                    return widget::text_input::focus(dialog_state.dialog_text_input.clone());
+                }
+                Task::none()
+            }
+            DialogAction::OpenDeleteNoteTypeDialog(note_type_id) => {
+                dialog_pages.push_back(DialogPage::DeleteNoteType(note_type_id));
+                Task::none()
+            }
+            DialogAction::OpenPurgeTrashItemDialog(item) => {
+                dialog_pages.push_back(DialogPage::PurgeTrashItem(item));
+                Task::none()
+            }
+            DialogAction::OpenEmptyTrashDialog => {
+                dialog_pages.push_back(DialogPage::EmptyTrash);
+                Task::none()
+            }
             DialogAction::DialogComplete => {
                 if let Some(dialog_page) = dialog_pages.pop_front() {
-                    return dialog_page.complete(database, nav);
+                    return dialog_page.complete(database, nav, clock);
                 }
                 Task::none()
             }
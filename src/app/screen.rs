@@ -2,11 +2,17 @@
 
 pub mod flashcards;
 pub mod folders;
+pub mod note_types;
+pub mod search;
 pub mod study;
+pub mod trash;
 
 pub use flashcards::FlashcardsScreen;
 pub use folders::FoldersScreen;
+pub use note_types::NoteTypesScreen;
+pub use search::SearchScreen;
 pub use study::StudyScreen;
+pub use trash::TrashScreen;
 
 /// Represents a [`Screen`] of the application
 #[allow(clippy::large_enum_variant)]
@@ -14,4 +20,7 @@ pub enum Screen {
     Folders(FoldersScreen),
     Flashcards(FlashcardsScreen),
     Study(StudyScreen),
+    NoteTypes(NoteTypesScreen),
+    Trash(TrashScreen),
+    Search(SearchScreen),
 }
@@ -1,28 +1,77 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use cosmic::cosmic_theme::Spacing;
 use cosmic::dialog::ashpd::desktop::file_chooser::SelectedFiles;
 use cosmic::dialog::file_chooser::FileFilter;
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{Alignment, ContentFit, Length, Subscription};
+use cosmic::iced::keyboard::{self, Modifiers};
+use cosmic::iced::{Alignment, Color, ContentFit, Font, Length, Subscription, event};
 use cosmic::iced_core::text::Wrapping;
-use cosmic::iced_widget::{column, pick_list, row, stack};
-use cosmic::widget::{Row, button, container, image, list, scrollable, settings, text, text_input};
+use cosmic::iced_widget::{column, pick_list, row, stack, text_editor};
+use cosmic::widget::{
+    Row, button, checkbox, container, dropdown, flex_row, image, list, scrollable, settings, text,
+    text_input, toggler,
+};
 use cosmic::{Element, Task, theme};
 use percent_encoding::percent_decode;
 use sqlx::{Pool, Sqlite};
 
 use crate::app::context_page::ContextPage;
-use crate::app::core::models::flashcard::{Flashcard, FlashcardField, FlashcardStatus};
-use crate::app::core::utils::{self, OboeteToast};
+use crate::app::core::models::bulk::BulkEdit;
+use crate::app::core::models::duplicates::{self, DuplicateAction, DuplicateScope, ImportSummary};
+use crate::app::core::models::flashcard::{
+    Flashcard, FlashcardField, FlashcardStatus, LEECH_TAG, parse_tags,
+};
+use crate::app::core::models::folder::Folder;
+use crate::app::core::models::list_options::{
+    DueFilter, FieldKind, FlashcardListOptions, FlashcardSort,
+};
+use crate::app::core::models::note::{Note, NoteType};
+use crate::app::core::models::revision::FlashcardRevision;
+use crate::app::core::utils::diff::{self, DiffPart};
+use crate::app::core::utils::{self, Clock, OboeteToast, cloze, math};
+use crate::app::widgets::code::{CODE_LANGUAGES, code};
+use crate::app::widgets::markdown::markdown;
 use crate::app::widgets::pill::pill;
 use crate::{fl, icons};
 
 /// Screen [`State`] holder
 pub struct FlashcardsScreen {
     current_folder_id: i32,
+    /// Only show the flashcards with this tag
+    tag_filter: Option<String>,
+    /// Sort and filters of the flashcards list (remembered per folder)
+    list_options: FlashcardListOptions,
+    /// Options of the sort selector (same order as [`FlashcardSort::ALL`])
+    sort_options: Vec<String>,
+    /// Options of the status filter, the first one means no filter
+    status_options: Vec<String>,
+    /// Options of the due filter, the first one means no filter
+    due_options: Vec<String>,
+    /// Options of the field type filter, the first one means no filter
+    field_kind_options: Vec<String>,
+    /// Note types that can be used to add new flashcards
+    note_types: Vec<NoteType>,
+    /// Options of the note type selector, the first one is a plain flashcard
+    note_type_options: Vec<String>,
+    /// Show the rendered markdown instead of the raw text on the upsert flashcard [`ContextPage`]
+    markdown_preview: bool,
+    /// Where flashcards with the same front are considered duplicates
+    duplicate_scope: DuplicateScope,
+    /// Options of the duplicate action selector (same order as [`DuplicateAction::ALL`])
+    duplicate_action_options: Vec<String>,
+    /// Flashcard whose upsert [`ContextPage`] gets opened once the flashcards are loaded
+    open_flashcard: Option<i32>,
+    /// Ids of the selected flashcards, bulk actions are applied to them
+    selected: HashSet<i32>,
+    /// Last flashcard (un)selected by hand, shift-clicking another one selects everything in between
+    selection_anchor: Option<i32>,
+    /// Keyboard modifiers currently pressed
+    modifiers: Modifiers,
+    clock: Arc<dyn Clock>,
     state: State,
 }
 
@@ -31,8 +80,23 @@ enum State {
     Loading,
     Ready {
         add_edit_flashcard: Box<Flashcard>,
+        /// [`Note`] being added or edited, [`None`] for plain flashcards
+        add_edit_note: Option<Note>,
         flashcards: Vec<Flashcard>,
         options: FolderOptions,
+        /// Tag being typed on the upsert flashcard [`ContextPage`]
+        new_tag: String,
+        /// Text to wrap as a cloze deletion on the upsert flashcard [`ContextPage`]
+        cloze_selection: String,
+        code_editors: CodeEditors,
+        /// Options of the tag filter, the first one means no filter
+        tag_options: Vec<String>,
+        /// Previous versions of the flashcard being edited, the newest ones go first
+        revisions: Vec<FlashcardRevision>,
+        /// Why the flashcard is being edited, saved with it's revision
+        edit_reason: String,
+        /// Id and normalized front of the flashcards a new front can be a duplicate of
+        duplicate_fronts: Vec<(i32, String)>,
     },
 }
 
@@ -48,9 +112,22 @@ pub enum Message {
     LoadFlashcards,
     /// Callback after asking to load the flashcards into state
     FlashcardsLoaded(Result<Vec<Flashcard>, anywho::Error>),
+    /// Callback after asking to load the note types into state
+    NoteTypesLoaded(Result<Vec<NoteType>, anywho::Error>),
+    /// Callback after asking to load the fronts the flashcards could be duplicates of
+    DuplicateFrontsLoaded(Result<Vec<(i32, String)>, anywho::Error>),
+    /// Callback after importing flashcards, shows how many have been added, updated and skipped
+    FlashcardsImported(ImportSummary),
 
     /// Ask to open the given [`ContextPage`] for the given [`Flashcard`] (if necessary)
     OpenContextPage(ContextPage, Option<Flashcard>),
+    /// Callback after loading the [`Note`] of a [`Flashcard`] before opening the given [`ContextPage`]
+    NoteLoaded(ContextPage, Box<Flashcard>, Result<Note, anywho::Error>),
+    /// Callback after loading the revisions of the [`Flashcard`] being edited, opens the given [`ContextPage`] (if any)
+    RevisionsLoaded(
+        Option<ContextPage>,
+        Result<Vec<FlashcardRevision>, anywho::Error>,
+    ),
 
     /// Ask to edit a [`Flashcard`] in the database
     EditFlashcard,
@@ -58,14 +135,41 @@ pub enum Message {
     AddFlashcard,
     /// Callback after user input that's either adding or editing a [`Flashcard`]
     AddEditFlashcardInput(AddEditFlashcardInput),
+    /// Callback after the user types the reason of the edit on the upsert flashcard [`ContextPage`]
+    EditReasonInput(String),
+    /// Ask to bring back the front and back of the given [`FlashcardRevision`]
+    RevertFlashcard(FlashcardRevision),
+    /// Toggle between the raw text and the rendered markdown on the upsert flashcard [`ContextPage`]
+    ToggleMarkdownPreview(bool),
     /// Resets the given [`Flashcard`] status, it also resets it's FSRS Data
     ResetFlashcardStatus(i32),
+    /// Suspends (or unsuspends) the given [`Flashcard`]
+    SetFlashcardSuspended(i32, bool),
 
     /// Delete the given [`Flashcard`] from the database, also deletes any image the [`Flashcard`] may have
     DeleteFlashcard(Flashcard),
+    /// Ask to move (or copy, if true) the given flashcards to another folder
+    TransferFlashcards(Vec<Flashcard>, bool),
+    /// Callback after loading the folders (id, path) the given flashcards can be moved (or copied, if true) to
+    TransferFoldersLoaded(
+        Vec<Flashcard>,
+        bool,
+        Result<Vec<(i32, String)>, anywho::Error>,
+    ),
+
+    /// Callback after the pressed keyboard modifiers change
+    ModifiersChanged(Modifiers),
+    /// User input on the selection of flashcards and it's bulk actions
+    SelectionInput(SelectionInput),
+    /// Callback after applying a [`BulkEdit`] to the selected flashcards
+    BulkEditApplied,
 
     /// User input on the folder options [`ContextPage`]
     FolderOptionsInput(FolderOptionsInput),
+    /// Callback after selecting a tag (by it's index on the options) to filter the flashcards
+    TagFilterChanged(usize),
+    /// User input on the sort and filters of the flashcards list
+    ListOptionsInput(ListOptionsInput),
 
     /// Ask to open the study page of the current folder
     Study,
@@ -80,6 +184,15 @@ pub enum AddEditFlashcardInput {
     FrontImageSelected(String),
     DeleteFrontSelectedImage,
     FrontAltTextInput(String),
+    FrontSelectAudio,
+    FrontAudioSelected(String),
+    DeleteFrontSelectedAudio,
+    FrontTranscriptInput(String),
+    FrontCodeLanguageSelected(usize),
+    FrontCodeEdit(text_editor::Action),
+    ClozeTextInput(String),
+    ClozeSelectionInput(String),
+    WrapClozeSelection,
 
     BackFieldTypeChanged(FlashcardField),
     BackTextInput(String),
@@ -87,6 +200,77 @@ pub enum AddEditFlashcardInput {
     BackImageSelected(String),
     DeleteBackSelectedImage,
     BackAltTextInput(String),
+    BackSelectAudio,
+    BackAudioSelected(String),
+    DeleteBackSelectedAudio,
+    BackTranscriptInput(String),
+    BackCodeLanguageSelected(usize),
+    BackCodeEdit(text_editor::Action),
+
+    /// Adds an empty text block at the end of the given side
+    AddBlock(Side),
+    /// Removes the block of the given index, it's image or audio file is kept until the flashcard is purged
+    RemoveBlock(Side, usize),
+    /// Moves the block of the first index to the second one
+    MoveBlock(Side, usize, usize),
+    BlockTypeChanged(Side, usize, FlashcardField),
+    BlockTextInput(Side, usize, String),
+    BlockSelectImage(Side, usize),
+    BlockImageSelected(Side, usize, String),
+    DeleteBlockSelectedImage(Side, usize),
+    BlockAltTextInput(Side, usize, String),
+    BlockSelectAudio(Side, usize),
+    BlockAudioSelected(Side, usize, String),
+    DeleteBlockSelectedAudio(Side, usize),
+    BlockTranscriptInput(Side, usize, String),
+
+    /// Listen to the audio of the given path
+    PlayAudio(String),
+
+    NewTagInput(String),
+    AddTag,
+    RemoveTag(String),
+
+    NoteTypeSelected(usize),
+    NoteFieldInput(usize, String),
+}
+
+/// Represents the different inputs the user can perform on the sort and filters of the flashcards list,
+/// selectors give the index of the chosen option
+#[derive(Debug, Clone)]
+pub enum ListOptionsInput {
+    SortSelected(usize),
+    ToggleDescending,
+    StatusSelected(usize),
+    DueSelected(usize),
+    FieldKindSelected(usize),
+    TextInput(String),
+    /// Removes every filter (keeping the sort)
+    ClearFilters,
+}
+
+/// Represents the different inputs the user can perform on the selection of flashcards
+#[derive(Debug, Clone)]
+pub enum SelectionInput {
+    /// (Un)selects the given [`Flashcard`], with shift it selects every flashcard since the last one clicked
+    Toggle(i32),
+    /// Selects every flashcard shown (with the current tag filter)
+    SelectAll,
+    Clear,
+
+    /// Ask to apply the given [`BulkEdit`] to the selected flashcards (after confirming it)
+    Edit(BulkEdit),
+    /// Ask to move the selected flashcards to another folder
+    Move,
+    Export,
+    CompleteExport(String),
+}
+
+/// Side of the flashcard being added or edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Front,
+    Back,
 }
 
 /// Represents the different inputs the user can perfrom on the folder options [`ContextPage`]
@@ -95,12 +279,14 @@ pub enum FolderOptionsInput {
     ImportContentInput(String),
     BetweenCardsInput(String),
     BetweenTermsInput(String),
+    DuplicateActionSelected(usize),
     CustomImport,
 
     AnkiImport,
     CompleteAnkiImport(String),
 
     ResetAllStatus,
+    UnsuspendAll,
 
     Export,
     CompleteExport(String),
@@ -115,9 +301,17 @@ pub enum Action {
     AddToast(OboeteToast),
 
     OpenDeleteFlashcardDialog(Flashcard),
+    OpenTransferFlashcardsDialog {
+        flashcards: Vec<Flashcard>,
+        copy: bool,
+        folders: Vec<(i32, String)>,
+    },
+    OpenBulkEditDialog(Vec<i32>, BulkEdit),
+    /// Remember the sort and filters of the flashcards list of the given folder
+    SaveListOptions(i32, FlashcardListOptions),
     OpenContextPage(ContextPage),
 
-    StudyFolder(i32),
+    StudyFolder(i32, Option<String>),
 }
 
 /// State holder for the folder options [`ContextPage`]
@@ -126,6 +320,30 @@ struct FolderOptions {
     import_content: String,
     between_cards: String,
     between_terms: String,
+    /// What to do with the imported flashcards whose front already exists
+    duplicate_action: DuplicateAction,
+}
+
+/// State holder for the multiline editors of the code fields on the upsert flashcard [`ContextPage`]
+#[derive(Default)]
+struct CodeEditors {
+    front: text_editor::Content,
+    back: text_editor::Content,
+}
+
+impl CodeEditors {
+    /// Editors filled with the code of the given [`Flashcard`] (empty for other field types)
+    fn new(flashcard: &Flashcard) -> Self {
+        let content = |field: &FlashcardField| match field {
+            FlashcardField::Code { source, .. } => text_editor::Content::with_text(source),
+            _ => text_editor::Content::new(),
+        };
+
+        Self {
+            front: content(&flashcard.front),
+            back: content(&flashcard.back),
+        }
+    }
 }
 
 impl FolderOptions {
@@ -139,16 +357,71 @@ impl FolderOptions {
 
 impl FlashcardsScreen {
     /// Init the screen
-    pub fn new(database: &Arc<Pool<Sqlite>>, folder_id: i32) -> (Self, Task<Message>) {
+    pub fn new(
+        database: &Arc<Pool<Sqlite>>,
+        folder_id: i32,
+        clock: Arc<dyn Clock>,
+        duplicate_scope: DuplicateScope,
+        list_options: FlashcardListOptions,
+        open_flashcard: Option<i32>,
+    ) -> (Self, Task<Message>) {
         (
             Self {
                 current_folder_id: folder_id,
+                tag_filter: None,
+                list_options,
+                sort_options: vec![
+                    fl!("sort-created"),
+                    fl!("sort-alphabetical"),
+                    fl!("sort-due-date"),
+                    fl!("sort-last-reviewed"),
+                    fl!("sort-difficulty"),
+                    fl!("sort-stability"),
+                ],
+                status_options: std::iter::once(fl!("all-statuses"))
+                    .chain(FlashcardStatus::ALL.iter().map(|s| s.to_string()))
+                    .collect(),
+                due_options: vec![
+                    fl!("all-flashcards"),
+                    fl!("due-flashcards"),
+                    fl!("overdue-flashcards"),
+                    fl!("new-flashcards"),
+                ],
+                field_kind_options: vec![
+                    fl!("all-field-types"),
+                    fl!("field-text"),
+                    fl!("field-image"),
+                    fl!("field-audio"),
+                    fl!("field-cloze"),
+                    fl!("field-code"),
+                    fl!("field-blocks"),
+                ],
+                note_types: Vec::new(),
+                note_type_options: Vec::new(),
+                markdown_preview: false,
+                duplicate_scope,
+                duplicate_action_options: vec![
+                    fl!("duplicate-skip"),
+                    fl!("duplicate-update"),
+                    fl!("duplicate-keep-both"),
+                ],
+                open_flashcard,
+                selected: HashSet::new(),
+                selection_anchor: None,
+                modifiers: Modifiers::default(),
+                clock,
                 state: State::Loading,
             },
-            Task::perform(
-                Flashcard::get_all(Arc::clone(database), folder_id),
-                Message::FlashcardsLoaded,
-            ),
+            Task::batch([
+                Task::perform(
+                    Flashcard::get_all(Arc::clone(database), folder_id),
+                    Message::FlashcardsLoaded,
+                ),
+                Task::perform(
+                    NoteType::get_all(Arc::clone(database)),
+                    Message::NoteTypesLoaded,
+                ),
+            ]),
         )
     }
 
@@ -156,14 +429,45 @@ impl FlashcardsScreen {
     pub fn view(&self) -> Element<'_, Message> {
         match &self.state {
             State::Loading => container(text(fl!("loading"))).center(Length::Fill).into(),
-            State::Ready { flashcards, .. } => {
+            State::Ready {
+                flashcards,
+                tag_options,
+                ..
+            } => {
                 let spacing = theme::active().cosmic().spacing;
 
-                let header = header_view(spacing, flashcards);
-                let content = folders_view(&spacing, flashcards);
+                let selected_tag = self
+                    .tag_filter
+                    .as_ref()
+                    .and_then(|tag| tag_option_index(tag_options, flashcards, tag))
+                    .unwrap_or(0);
+
+                // Filters are only useful once the folder has flashcards
+                let list_options = (!flashcards.is_empty() || self.list_options.is_filtering())
+                    .then(|| self.list_options_view(spacing));
+                let empty_text = if flashcards.is_empty() {
+                    fl!("empty-flashcards-page")
+                } else {
+                    fl!("no-matching-flashcards")
+                };
+
+                let flashcards = self.visible_flashcards(flashcards);
+
+                let header = header_view(spacing, flashcards.len(), tag_options, selected_tag);
+                let selection_bar = selection_view(spacing, &flashcards, &self.selected);
+                let content = folders_view(
+                    &spacing,
+                    &flashcards,
+                    &self.selected,
+                    empty_text,
+                    self.clock.as_ref(),
+                );
 
                 container(
-                    column![header, content]
+                    column![header]
+                        .push_maybe(list_options)
+                        .push_maybe(selection_bar)
+                        .push(content)
                         .width(Length::Fill)
                         .height(Length::Fill)
                         .spacing(spacing.space_s),
@@ -211,12 +515,159 @@ impl FlashcardsScreen {
                             _ => Flashcard::default(),
                         };
 
+                        // Same for the note, after adding one we keep it's type so the next one is quicker to add
+                        let add_edit_note = match &self.state {
+                            State::Ready {
+                                add_edit_note: Some(note),
+                                ..
+                            } if add_edit_flashcard.id.is_some() || note.id.is_none() => {
+                                match note.id {
+                                    Some(_) => Some(note.clone()),
+                                    None => Some(Note {
+                                        fields: vec![String::new(); note.fields.len()],
+                                        ..note.clone()
+                                    }),
+                                }
+                            }
+                            _ => None,
+                        };
+
+                        let mut tag_options: Vec<String> = Vec::new();
+                        for tag in flashcards.iter().flat_map(|f| &f.tags) {
+                            if !tag.eq_ignore_ascii_case(LEECH_TAG)
+                                && !tag_options.iter().any(|t| t.eq_ignore_ascii_case(tag))
+                            {
+                                tag_options.push(tag.clone());
+                            }
+                        }
+                        tag_options.sort_by_key(|t| t.to_lowercase());
+                        // The leech tag gets it's own option (always the second one)
+                        if flashcards.iter().any(Flashcard::is_leech) {
+                            tag_options.insert(0, fl!("leeches"));
+                        }
+                        tag_options.insert(0, fl!("all-tags"));
+
+                        // Selected flashcards may have been deleted or moved
+                        self.selected
+                            .retain(|id| flashcards.iter().any(|f| f.id == Some(*id)));
+
+                        // The filtered tag may not exist anymore
+                        if let Some(tag) = &self.tag_filter
+                            && tag_option_index(&tag_options, &flashcards, tag).is_none()
+                        {
+                            self.tag_filter = None;
+                        }
+
+                        // The revisions of the flashcard being edited may have changed too
+                        // The selected duplicate action and the known fronts are kept until they are reloaded
+                        let (duplicate_action, duplicate_fronts) = match &mut self.state {
+                            State::Ready {
+                                options,
+                                duplicate_fronts,
+                                ..
+                            } => (options.duplicate_action, std::mem::take(duplicate_fronts)),
+                            State::Loading => (DuplicateAction::default(), Vec::new()),
+                        };
+                        let duplicates_task = Task::perform(
+                            duplicates::get_normalized_fronts(
+                                Arc::clone(database),
+                                self.current_folder_id,
+                                self.duplicate_scope,
+                            ),
+                            Message::DuplicateFrontsLoaded,
+                        );
+
+                        let (revisions, revisions_task) =
+                            match (&mut self.state, add_edit_flashcard.id) {
+                                (State::Ready { revisions, .. }, Some(id))
+                                    if add_edit_note.is_none() =>
+                                {
+                                    (
+                                        std::mem::take(revisions),
+                                        Task::perform(
+                                            FlashcardRevision::get_all_by_flashcard(
+                                                Arc::clone(database),
+                                                id,
+                                            ),
+                                            |res| Message::RevisionsLoaded(None, res),
+                                        ),
+                                    )
+                                }
+                                _ => (Vec::new(), Task::none()),
+                            };
+
+                        let render_task =
+                            render_math(flashcards.iter().flat_map(|f| [&f.front, &f.back]));
+
+                        // The flashcard we've been asked to open (ej: from a search)
+                        let open_task = match self
+                            .open_flashcard
+                            .take()
+                            .and_then(|id| flashcards.iter().find(|f| f.id == Some(id)))
+                        {
+                            Some(flashcard) => Task::done(Message::OpenContextPage(
+                                ContextPage::AddEditFlashcard,
+                                Some(flashcard.clone()),
+                            )),
+                            None => Task::none(),
+                        };
+
                         self.state = State::Ready {
                             flashcards,
+                            code_editors: CodeEditors::new(&add_edit_flashcard),
                             add_edit_flashcard: Box::from(add_edit_flashcard),
-                            options: FolderOptions::default(),
+                            add_edit_note,
+                            options: FolderOptions {
+                                duplicate_action,
+                                ..Default::default()
+                            },
+                            new_tag: String::new(),
+                            cloze_selection: String::new(),
+                            tag_options,
+                            revisions,
+                            edit_reason: String::new(),
+                            duplicate_fronts,
                         };
+
+                        return Action::Run(Task::batch([
+                            render_task,
+                            revisions_task,
+                            duplicates_task,
+                            open_task,
+                        ]));
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
                     }
+                }
+            }
+
+            Message::NoteTypesLoaded(res) => {
+                match res {
+                    Ok(note_types) => {
+                        self.note_type_options = std::iter::once(fl!("basic-flashcard"))
+                            .chain(note_types.iter().map(|n| n.name.clone()))
+                            .collect();
+                        self.note_types = note_types;
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
+                    }
+                }
+                Action::None
+            }
+            Message::DuplicateFrontsLoaded(res) => {
+                let State::Ready {
+                    duplicate_fronts, ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(fronts) => *duplicate_fronts = fronts,
                     Err(e) => {
                         eprintln!("{}", e);
                         return Action::AddToast(OboeteToast::new(e));
@@ -224,60 +675,158 @@ impl FlashcardsScreen {
                 }
                 Action::None
             }
+            Message::FlashcardsImported(summary) => Action::Run(Task::batch([
+                Task::done(Message::LoadFlashcards),
+                Task::done(Message::AddToast(OboeteToast::new(fl!(
+                    "import-summary",
+                    added = summary.added,
+                    updated = summary.updated,
+                    skipped = summary.skipped
+                )))),
+            ])),
 
             Message::OpenContextPage(context_page, flashcard) => {
                 if let Some(flashcard) = flashcard {
                     let State::Ready {
-                        add_edit_flashcard, ..
+                        add_edit_flashcard,
+                        add_edit_note,
+                        code_editors,
+                        revisions,
+                        edit_reason,
+                        ..
                     } = &mut self.state
                     else {
                         return Action::None;
                     };
 
+                    revisions.clear();
+                    edit_reason.clear();
+
+                    // Flashcards generated by a note are edited through the note
+                    if let Some(note_id) = flashcard.note_id {
+                        let flashcard = Box::from(flashcard);
+                        return Action::Run(Task::perform(
+                            Note::get(Arc::clone(database), note_id),
+                            move |res| Message::NoteLoaded(context_page, flashcard, res),
+                        ));
+                    }
+
+                    *code_editors = CodeEditors::new(&flashcard);
                     *add_edit_flashcard = Box::from(flashcard);
+                    *add_edit_note = None;
+
+                    // The history of existing flashcards is loaded before opening the page
+                    if let Some(id) = add_edit_flashcard.id {
+                        return Action::Run(Task::perform(
+                            FlashcardRevision::get_all_by_flashcard(Arc::clone(database), id),
+                            move |res| Message::RevisionsLoaded(Some(context_page), res),
+                        ));
+                    }
                 }
 
                 Action::OpenContextPage(context_page)
             }
+            Message::NoteLoaded(context_page, flashcard, res) => {
+                let State::Ready {
+                    add_edit_flashcard,
+                    add_edit_note,
+                    code_editors,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(note) => {
+                        *code_editors = CodeEditors::new(&flashcard);
+                        *add_edit_flashcard = flashcard;
+                        *add_edit_note = Some(note);
+                        Action::OpenContextPage(context_page)
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        Action::AddToast(OboeteToast::new(e))
+                    }
+                }
+            }
+            Message::RevisionsLoaded(context_page, res) => {
+                let State::Ready { revisions, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(loaded) => *revisions = loaded,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
+                    }
+                }
+
+                match context_page {
+                    Some(context_page) => Action::OpenContextPage(context_page),
+                    None => Action::None,
+                }
+            }
 
             Message::EditFlashcard => {
                 let State::Ready {
-                    add_edit_flashcard, ..
+                    add_edit_flashcard,
+                    add_edit_note,
+                    edit_reason,
+                    ..
                 } = &mut self.state
                 else {
                     return Action::None;
                 };
 
-                #[allow(clippy::collapsible_if)]
-                if let FlashcardField::Image { path, .. } = &mut add_edit_flashcard.front {
-                    // If the image path is not in the Oboete data path we know it has not been modified so we don't need to save the image again
-                    if !utils::check_path(path) {
-                        let new_path = utils::save_image(path);
-                        match new_path {
-                            Ok(new_path) => *path = new_path,
+                // Editing the note updates every flashcard generated by it
+                if let Some(note) = add_edit_note {
+                    return Action::Run(Task::perform(
+                        Note::edit(
+                            Arc::clone(database),
+                            note.clone(),
+                            add_edit_flashcard.tags.clone(),
+                            self.clock.now(),
+                            Some(edit_reason.clone()),
+                        ),
+                        |res| match res {
+                            Ok(_) => Message::LoadFlashcards,
                             Err(e) => {
-                                return Action::AddToast(OboeteToast::new(e));
+                                eprintln!("{}", e);
+                                Message::AddToast(OboeteToast::new(e))
                             }
-                        }
-                    }
+                        },
+                    ));
                 }
 
-                #[allow(clippy::collapsible_if)]
-                if let FlashcardField::Image { path, .. } = &mut add_edit_flashcard.back {
-                    // If the image path is not in the Oboete data path we know it has not been modified so we don't need to save the image again
-                    if !utils::check_path(path) {
-                        let new_path = utils::save_image(path);
-                        match new_path {
-                            Ok(new_path) => *path = new_path,
-                            Err(e) => {
-                                return Action::AddToast(OboeteToast::new(e));
-                            }
+                for field in [&mut add_edit_flashcard.front, &mut add_edit_flashcard.back]
+                    .into_iter()
+                    .flat_map(|field| field.blocks_mut())
+                {
+                    // If the image (or audio) path is not in the Oboete data path we know it has not been modified so we don't need to save it again
+                    let saved = match field {
+                        FlashcardField::Image { path, .. } if !utils::check_path(path) => {
+                            utils::save_image(path).map(|new_path| *path = new_path)
+                        }
+                        FlashcardField::Audio { path, .. } if !utils::check_audio_path(path) => {
+                            utils::save_audio(path).map(|new_path| *path = new_path)
                         }
+                        _ => Ok(()),
+                    };
+
+                    if let Err(e) = saved {
+                        return Action::AddToast(OboeteToast::new(e));
                     }
                 }
 
                 Action::Run(Task::perform(
-                    Flashcard::edit(Arc::clone(database), *add_edit_flashcard.clone()),
+                    Flashcard::edit(
+                        Arc::clone(database),
+                        *add_edit_flashcard.clone(),
+                        self.clock.now(),
+                        Some(edit_reason.clone()),
+                    ),
                     |res| match res {
                         Ok(_) => Message::LoadFlashcards,
                         Err(e) => {
@@ -289,31 +838,50 @@ impl FlashcardsScreen {
             }
             Message::AddFlashcard => {
                 let State::Ready {
-                    add_edit_flashcard, ..
+                    add_edit_flashcard,
+                    add_edit_note,
+                    ..
                 } = &mut self.state
                 else {
                     return Action::None;
                 };
 
-                // save the front flashcard image if any
-                if let FlashcardField::Image { path, .. } = &mut add_edit_flashcard.front {
-                    let new_path = utils::save_image(path);
-                    match new_path {
-                        Ok(new_path) => *path = new_path,
-                        Err(e) => {
-                            return Action::AddToast(OboeteToast::new(e));
-                        }
-                    }
+                if let Some(note) = add_edit_note {
+                    return Action::Run(Task::perform(
+                        Note::add(
+                            Arc::clone(database),
+                            note.clone(),
+                            add_edit_flashcard.tags.clone(),
+                            self.current_folder_id,
+                            self.clock.now(),
+                        ),
+                        |res| match res {
+                            Ok(_) => Message::LoadFlashcards,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                Message::AddToast(OboeteToast::new(e))
+                            }
+                        },
+                    ));
                 }
 
-                // save the back flashcard image if any
-                if let FlashcardField::Image { path, .. } = &mut add_edit_flashcard.back {
-                    let new_path = utils::save_image(path);
-                    match new_path {
-                        Ok(new_path) => *path = new_path,
-                        Err(e) => {
-                            return Action::AddToast(OboeteToast::new(e));
+                // save the flashcard images and audio of both sides if any
+                for field in [&mut add_edit_flashcard.front, &mut add_edit_flashcard.back]
+                    .into_iter()
+                    .flat_map(|field| field.blocks_mut())
+                {
+                    let saved = match field {
+                        FlashcardField::Image { path, .. } => {
+                            utils::save_image(path).map(|new_path| *path = new_path)
+                        }
+                        FlashcardField::Audio { path, .. } => {
+                            utils::save_audio(path).map(|new_path| *path = new_path)
                         }
+                        _ => Ok(()),
+                    };
+
+                    if let Err(e) = saved {
+                        return Action::AddToast(OboeteToast::new(e));
                     }
                 }
 
@@ -334,13 +902,79 @@ impl FlashcardsScreen {
             }
             Message::AddEditFlashcardInput(input) => {
                 let State::Ready {
-                    add_edit_flashcard, ..
+                    add_edit_flashcard,
+                    add_edit_note,
+                    new_tag,
+                    cloze_selection,
+                    code_editors,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                apply_flashcard_add_edit_input(
+                    input,
+                    add_edit_flashcard,
+                    add_edit_note,
+                    &self.note_types,
+                    new_tag,
+                    cloze_selection,
+                    code_editors,
+                )
+            }
+            Message::EditReasonInput(input) => {
+                if let State::Ready { edit_reason, .. } = &mut self.state {
+                    *edit_reason = input;
+                }
+                Action::None
+            }
+            Message::RevertFlashcard(revision) => {
+                let State::Ready {
+                    add_edit_flashcard,
+                    code_editors,
+                    ..
                 } = &mut self.state
                 else {
                     return Action::None;
                 };
 
-                apply_flashcard_add_edit_input(input, add_edit_flashcard)
+                add_edit_flashcard.front = revision.front;
+                add_edit_flashcard.back = revision.back;
+                *code_editors = CodeEditors::new(add_edit_flashcard);
+
+                let reason = fl!(
+                    "reverted-revision",
+                    date = format_timestamp(revision.edited_at)
+                );
+                Action::Run(Task::perform(
+                    Flashcard::edit(
+                        Arc::clone(database),
+                        *add_edit_flashcard.clone(),
+                        self.clock.now(),
+                        Some(reason),
+                    ),
+                    |res| match res {
+                        Ok(_) => Message::LoadFlashcards,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            Message::AddToast(OboeteToast::new(e))
+                        }
+                    },
+                ))
+            }
+            Message::ToggleMarkdownPreview(value) => {
+                self.markdown_preview = value;
+
+                // The formulas of the flashcard being edited may not have been rendered yet
+                match &self.state {
+                    State::Ready {
+                        add_edit_flashcard, ..
+                    } if value => Action::Run(render_math(
+                        [&add_edit_flashcard.front, &add_edit_flashcard.back].into_iter(),
+                    )),
+                    _ => Action::None,
+                }
             }
             Message::ResetFlashcardStatus(flashcard_id) => Action::Run(Task::perform(
                 Flashcard::reset_single_status(Arc::clone(database), flashcard_id),
@@ -350,9 +984,39 @@ impl FlashcardsScreen {
                 },
             )),
 
+            Message::SetFlashcardSuspended(flashcard_id, suspended) => Action::Run(Task::perform(
+                Flashcard::set_suspended(Arc::clone(database), flashcard_id, suspended),
+                |res| match res {
+                    Ok(_) => Message::LoadFlashcards,
+                    Err(e) => Message::AddToast(OboeteToast::new(e)),
+                },
+            )),
+
             Message::DeleteFlashcard(flashcard_id) => {
                 Action::OpenDeleteFlashcardDialog(flashcard_id)
             }
+            Message::TransferFlashcards(flashcards, copy) => Action::Run(Task::perform(
+                Folder::get_all_paths(Arc::clone(database)),
+                move |res| Message::TransferFoldersLoaded(flashcards, copy, res),
+            )),
+            Message::TransferFoldersLoaded(flashcards, copy, res) => match res {
+                Ok(mut folders) => {
+                    // Moving flashcards to the folder they are already in does nothing
+                    if !copy {
+                        folders.retain(|(id, _)| *id != self.current_folder_id);
+                    }
+
+                    Action::OpenTransferFlashcardsDialog {
+                        flashcards,
+                        copy,
+                        folders,
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    Action::AddToast(OboeteToast::new(e))
+                }
+            },
 
             Message::FolderOptionsInput(input) => {
                 let State::Ready {
@@ -370,16 +1034,256 @@ impl FlashcardsScreen {
                     self.current_folder_id,
                     database,
                     flashcards,
+                    self.duplicate_scope,
+                    self.clock.as_ref(),
                 )
             }
 
-            Message::Study => Action::StudyFolder(self.current_folder_id),
+            Message::TagFilterChanged(index) => {
+                let State::Ready {
+                    tag_options,
+                    flashcards,
+                    ..
+                } = &self.state
+                else {
+                    return Action::None;
+                };
+
+                // The first option means no filter, the second one may be the leeches
+                self.tag_filter = match index {
+                    0 => None,
+                    1 if flashcards.iter().any(Flashcard::is_leech) => {
+                        Some(String::from(LEECH_TAG))
+                    }
+                    _ => tag_options.get(index).cloned(),
+                };
+                Action::None
+            }
+
+            Message::ListOptionsInput(input) => self.apply_list_options_input(input),
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Action::None
+            }
+            Message::SelectionInput(input) => self.apply_selection_input(input),
+            Message::BulkEditApplied => {
+                self.selected.clear();
+                self.selection_anchor = None;
+                Action::Run(Task::done(Message::LoadFlashcards))
+            }
+
+            Message::Study => Action::StudyFolder(self.current_folder_id, self.tag_filter.clone()),
         }
     }
 
     /// Subscriptions of this screen
     pub fn subscription(&self) -> Subscription<Message> {
-        Subscription::none()
+        event::listen_with(|event, _, _| match event {
+            event::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
+            _ => None,
+        })
+    }
+
+    /// Flashcards shown on the list (with the filtered tag, if any), filtered and sorted with the [`FlashcardListOptions`]
+    fn visible_flashcards<'a>(&self, flashcards: &'a [Flashcard]) -> Vec<&'a Flashcard> {
+        self.list_options.apply(
+            flashcards
+                .iter()
+                .filter(|f| self.tag_filter.as_ref().is_none_or(|tag| f.has_tag(tag))),
+            self.clock.as_ref(),
+        )
+    }
+
+    /// Handles the user input on the sort and filters of the flashcards list
+    fn apply_list_options_input(&mut self, input: ListOptionsInput) -> Action {
+        let options = &mut self.list_options;
+
+        match input {
+            ListOptionsInput::SortSelected(index) => {
+                options.sort = FlashcardSort::ALL.get(index).copied().unwrap_or_default();
+            }
+            ListOptionsInput::ToggleDescending => options.descending = !options.descending,
+            // The first option of the filters means no filter
+            ListOptionsInput::StatusSelected(index) => {
+                options.status = index
+                    .checked_sub(1)
+                    .and_then(|i| FlashcardStatus::ALL.get(i).copied());
+            }
+            ListOptionsInput::DueSelected(index) => {
+                options.due = index
+                    .checked_sub(1)
+                    .and_then(|i| DueFilter::ALL.get(i).copied());
+            }
+            ListOptionsInput::FieldKindSelected(index) => {
+                options.field_kind = index
+                    .checked_sub(1)
+                    .and_then(|i| FieldKind::ALL.get(i).copied());
+            }
+            ListOptionsInput::TextInput(text) => options.text = text,
+            ListOptionsInput::ClearFilters => {
+                *options = FlashcardListOptions {
+                    sort: options.sort,
+                    descending: options.descending,
+                    ..Default::default()
+                };
+            }
+        }
+
+        Action::SaveListOptions(self.current_folder_id, self.list_options.clone())
+    }
+
+    /// Handles the user input on the selection of flashcards
+    fn apply_selection_input(&mut self, input: SelectionInput) -> Action {
+        let State::Ready { flashcards, .. } = &self.state else {
+            return Action::None;
+        };
+        let visible = self.visible_flashcards(flashcards);
+        let selected: Vec<Flashcard> = visible
+            .iter()
+            .filter(|f| f.id.is_some_and(|id| self.selected.contains(&id)))
+            .map(|f| (*f).clone())
+            .collect();
+
+        match input {
+            SelectionInput::Toggle(flashcard_id) => {
+                let position = |id: i32| visible.iter().position(|f| f.id == Some(id));
+
+                match self
+                    .selection_anchor
+                    .filter(|_| self.modifiers.shift())
+                    .and_then(position)
+                    .zip(position(flashcard_id))
+                {
+                    Some((anchor, clicked)) => {
+                        let range = anchor.min(clicked)..=anchor.max(clicked);
+                        self.selected
+                            .extend(visible[range].iter().filter_map(|f| f.id));
+                    }
+                    None => {
+                        if !self.selected.remove(&flashcard_id) {
+                            self.selected.insert(flashcard_id);
+                        }
+                    }
+                }
+                self.selection_anchor = Some(flashcard_id);
+            }
+            SelectionInput::SelectAll => {
+                self.selected.extend(visible.iter().filter_map(|f| f.id));
+            }
+            SelectionInput::Clear => {
+                self.selected.clear();
+                self.selection_anchor = None;
+            }
+
+            SelectionInput::Edit(edit) => {
+                return Action::OpenBulkEditDialog(
+                    selected.iter().filter_map(|f| f.id).collect(),
+                    edit,
+                );
+            }
+            SelectionInput::Move => {
+                return Action::Run(Task::done(Message::TransferFlashcards(selected, false)));
+            }
+            SelectionInput::Export => {
+                return Action::Run(Task::perform(select_export_file(), |res| {
+                    Message::SelectionInput(SelectionInput::CompleteExport(res))
+                }));
+            }
+            SelectionInput::CompleteExport(file_path) => {
+                if let Err(e) = utils::export_flashcards(&file_path, &selected) {
+                    eprintln!("{}", e);
+                    return Action::AddToast(OboeteToast::new(e));
+                }
+            }
+        }
+
+        Action::None
+    }
+
+    /// View of the sort and filters of the flashcards list
+    fn list_options_view(&self, spacing: Spacing) -> Element<'_, Message> {
+        let options = &self.list_options;
+        // The first option of the filters means no filter
+        let filter_index = |position: Option<usize>| position.map_or(0, |i| i + 1);
+
+        let text_filter = text_input(fl!("filter-flashcards"), &options.text)
+            .on_input(|text| Message::ListOptionsInput(ListOptionsInput::TextInput(text)))
+            .width(Length::Fixed(200.0));
+
+        let status_filter = dropdown(
+            &self.status_options,
+            Some(filter_index(options.status.and_then(|status| {
+                FlashcardStatus::ALL.iter().position(|s| *s == status)
+            }))),
+            |index| Message::ListOptionsInput(ListOptionsInput::StatusSelected(index)),
+        );
+
+        let due_filter = dropdown(
+            &self.due_options,
+            Some(filter_index(options.due.and_then(|due| {
+                DueFilter::ALL.iter().position(|d| *d == due)
+            }))),
+            |index| Message::ListOptionsInput(ListOptionsInput::DueSelected(index)),
+        );
+
+        let field_kind_filter = dropdown(
+            &self.field_kind_options,
+            Some(filter_index(options.field_kind.and_then(|kind| {
+                FieldKind::ALL.iter().position(|k| *k == kind)
+            }))),
+            |index| Message::ListOptionsInput(ListOptionsInput::FieldKindSelected(index)),
+        );
+
+        let sort = dropdown(
+            &self.sort_options,
+            FlashcardSort::ALL.iter().position(|s| *s == options.sort),
+            |index| Message::ListOptionsInput(ListOptionsInput::SortSelected(index)),
+        );
+
+        let sort_direction = button::icon(icons::get_handle(
+            if options.descending {
+                "view-sort-descending-symbolic"
+            } else {
+                "view-sort-ascending-symbolic"
+            },
+            18,
+        ))
+        .class(theme::Button::Standard)
+        .on_press(Message::ListOptionsInput(
+            ListOptionsInput::ToggleDescending,
+        ));
+
+        let clear_filters = options.is_filtering().then(|| {
+            button::text(fl!("clear-filters"))
+                .on_press(Message::ListOptionsInput(ListOptionsInput::ClearFilters))
+        });
+
+        cosmic::widget::row::with_capacity(3)
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs)
+            .padding([spacing.space_none, spacing.space_xxs])
+            .push(
+                container(
+                    flex_row(vec![
+                        text_filter.into(),
+                        status_filter.into(),
+                        due_filter.into(),
+                        field_kind_filter.into(),
+                    ])
+                    .column_spacing(spacing.space_xxs)
+                    .row_spacing(spacing.space_xxs),
+                )
+                .width(Length::Fill),
+            )
+            .push_maybe(clear_filters)
+            .push(
+                row![text::body(fl!("sort-by")), sort, sort_direction]
+                    .align_y(Alignment::Center)
+                    .spacing(spacing.space_xxs),
+            )
+            .into()
     }
 
     //
@@ -398,6 +1302,32 @@ impl FlashcardsScreen {
         };
 
         column![
+            // DUPLICATES SECTION
+            settings::view_column(vec![
+                settings::section()
+                    .title(fl!("duplicates"))
+                    .add(
+                        settings::item::builder(fl!("duplicate-action"))
+                            .description(match self.duplicate_scope {
+                                DuplicateScope::Folder => fl!("duplicate-scope-folder-description"),
+                                DuplicateScope::StudySet => {
+                                    fl!("duplicate-scope-studyset-description")
+                                }
+                            })
+                            .control(dropdown(
+                                &self.duplicate_action_options,
+                                DuplicateAction::ALL
+                                    .iter()
+                                    .position(|a| *a == options.duplicate_action),
+                                |index| {
+                                    Message::FolderOptionsInput(
+                                        FolderOptionsInput::DuplicateActionSelected(index),
+                                    )
+                                },
+                            )),
+                    )
+                    .into(),
+            ]),
             // CUSTOM IMPORT SECTION
             settings::view_column(vec![
                 settings::section()
@@ -487,6 +1417,20 @@ impl FlashcardsScreen {
                     ),)
             ]
             .spacing(spacing.space_xxxs),
+            // SUSPENDED SECTION
+            column![
+                settings::view_column(vec![
+                    settings::section()
+                        .title(fl!("unsuspend-folder-flashcards-title"))
+                        .into(),
+                ]),
+                button::text(fl!("unsuspend-folder-flashcards-button"))
+                    .class(theme::Button::Standard)
+                    .on_press_maybe(flashcards.iter().any(|f| f.suspended).then_some(
+                        Message::FolderOptionsInput(FolderOptionsInput::UnsuspendAll)
+                    ))
+            ]
+            .spacing(spacing.space_xxxs),
             // EXPORT SECTION
             column![
                 settings::view_column(vec![
@@ -516,35 +1460,97 @@ impl FlashcardsScreen {
     /// View of the upsert flashcard [`ContextPage`] of the application
     pub fn add_edit_contextpage<'a>(&'a self, spacing: Spacing) -> Element<'a, Message> {
         let State::Ready {
-            add_edit_flashcard, ..
+            add_edit_flashcard,
+            add_edit_note,
+            new_tag,
+            cloze_selection,
+            code_editors,
+            flashcards,
+            revisions,
+            edit_reason,
+            duplicate_fronts,
+            ..
         } = &self.state
         else {
             return text("Error").into(); // It's theoretically impossible to be here.
         };
 
+        let note_type = add_edit_note.as_ref().and_then(|note| {
+            self.note_types
+                .iter()
+                .find(|t| t.id == Some(note.note_type_id))
+        });
+
+        let is_valid = match (add_edit_note, note_type) {
+            (Some(note), Some(note_type)) => note.is_valid(note_type),
+            (Some(_), None) => false,
+            (None, _) => add_edit_flashcard.is_valid(),
+        };
+
         let add_edit_button = if add_edit_flashcard.id.is_some() {
             button::text(fl!("edit"))
-                .on_press_maybe(
-                    add_edit_flashcard
-                        .is_valid()
-                        .then_some(Message::EditFlashcard),
-                )
+                .on_press_maybe(is_valid.then_some(Message::EditFlashcard))
                 .class(theme::Button::Suggested)
         } else {
             button::text(fl!("create"))
-                .on_press_maybe(
-                    add_edit_flashcard
-                        .is_valid()
-                        .then_some(Message::AddFlashcard),
-                )
+                .on_press_maybe(is_valid.then_some(Message::AddFlashcard))
                 .class(theme::Button::Suggested)
         };
 
-        let front_input = {
-            let type_selector =
-                pick_list(FlashcardField::ALL, Some(&add_edit_flashcard.front), |x| {
-                    Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontFieldTypeChanged(x))
-                })
+        // The type can only be choosen when adding, existing flashcards keep the one they have
+        let note_type_input = (add_edit_flashcard.id.is_none() && self.note_type_options.len() > 1)
+            .then(|| {
+                let selected = note_type
+                    .and_then(|t| self.note_types.iter().position(|x| x.id == t.id))
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+
+                row![
+                    text::body(fl!("note-type")).width(Length::Fill),
+                    dropdown(&self.note_type_options, Some(selected), |index| {
+                        Message::AddEditFlashcardInput(AddEditFlashcardInput::NoteTypeSelected(
+                            index,
+                        ))
+                    })
+                ]
+                .spacing(spacing.space_s)
+                .align_y(Alignment::Center)
+            });
+
+        let note_fields_input = match (add_edit_note, note_type) {
+            (Some(note), Some(note_type)) => Some(
+                cosmic::widget::column::with_children(
+                    note_type
+                        .fields
+                        .iter()
+                        .enumerate()
+                        .map(|(index, name)| {
+                            let value = note.fields.get(index).map(String::as_str).unwrap_or("");
+                            cosmic::widget::column::with_children(vec![
+                                text::body(name.as_str()).into(),
+                                text_input(name.as_str(), value)
+                                    .on_input(move |input| {
+                                        Message::AddEditFlashcardInput(
+                                            AddEditFlashcardInput::NoteFieldInput(index, input),
+                                        )
+                                    })
+                                    .into(),
+                            ])
+                            .spacing(spacing.space_xxs)
+                            .into()
+                        })
+                        .collect::<Vec<Element<Message>>>(),
+                )
+                .spacing(spacing.space_s),
+            ),
+            _ => None,
+        };
+
+        let front_input = {
+            let type_selector =
+                pick_list(FlashcardField::ALL, Some(&add_edit_flashcard.front), |x| {
+                    Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontFieldTypeChanged(x))
+                })
                 .width(Length::Shrink);
 
             let header = row![
@@ -555,6 +1561,9 @@ impl FlashcardsScreen {
             .align_y(Alignment::Center);
 
             let content = match &add_edit_flashcard.front {
+                FlashcardField::Text(text) if self.markdown_preview => {
+                    markdown(text, 14.0, Message::LaunchUrl)
+                }
                 FlashcardField::Text(text) => text_input(fl!("flashcard-front-placeholder"), text)
                     .on_input(|input| {
                         Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontTextInput(input))
@@ -608,27 +1617,120 @@ impl FlashcardsScreen {
                     .spacing(spacing.space_xxs)
                     .into()
                 }
+                FlashcardField::Audio { path, transcript } => audio_input(
+                    spacing,
+                    path,
+                    transcript,
+                    AddEditFlashcardInput::FrontSelectAudio,
+                    AddEditFlashcardInput::DeleteFrontSelectedAudio,
+                    AddEditFlashcardInput::FrontTranscriptInput,
+                ),
+                FlashcardField::Code { language, source } if self.markdown_preview => {
+                    code(language, source, 14.0)
+                }
+                FlashcardField::Code { language, .. } => code_input(
+                    spacing,
+                    language,
+                    &code_editors.front,
+                    AddEditFlashcardInput::FrontCodeLanguageSelected,
+                    AddEditFlashcardInput::FrontCodeEdit,
+                ),
+                FlashcardField::Blocks(blocks) => {
+                    blocks_input(spacing, Side::Front, blocks, self.markdown_preview)
+                }
+                FlashcardField::Cloze {
+                    text: cloze_text, ..
+                } => {
+                    // Live preview of every flashcard the text generates
+                    let preview = cloze::indexes(cloze_text)
+                        .into_iter()
+                        .map(|index| {
+                            text::caption(format!(
+                                "{} → {}",
+                                cloze::front_text(cloze_text, index),
+                                cloze::back_text(cloze_text)
+                            ))
+                            .into()
+                        })
+                        .collect::<Vec<Element<Message>>>();
+
+                    cosmic::widget::column::with_children(vec![
+                        text_input(fl!("cloze-placeholder"), cloze_text)
+                            .on_input(|input| {
+                                Message::AddEditFlashcardInput(
+                                    AddEditFlashcardInput::ClozeTextInput(input),
+                                )
+                            })
+                            .into(),
+                        row![
+                            text_input(fl!("cloze-selection-placeholder"), cloze_selection)
+                                .on_input(|input| {
+                                    Message::AddEditFlashcardInput(
+                                        AddEditFlashcardInput::ClozeSelectionInput(input),
+                                    )
+                                })
+                                .on_submit(|_| Message::AddEditFlashcardInput(
+                                    AddEditFlashcardInput::WrapClozeSelection
+                                )),
+                            button::text(fl!("wrap-as-cloze"))
+                                .class(theme::Button::Standard)
+                                .on_press_maybe((!cloze_selection.is_empty()).then_some(
+                                    Message::AddEditFlashcardInput(
+                                        AddEditFlashcardInput::WrapClozeSelection
+                                    )
+                                ))
+                        ]
+                        .align_y(Alignment::Center)
+                        .spacing(spacing.space_xxs)
+                        .into(),
+                        text::caption(fl!("cloze-description")).into(),
+                        text::body(fl!("cloze-preview")).into(),
+                        cosmic::widget::column::with_children(preview)
+                            .spacing(spacing.space_xxxs)
+                            .into(),
+                    ])
+                    .spacing(spacing.space_xxs)
+                    .into()
+                }
+                FlashcardField::Unsupported(_) => text::body(fl!("unsupported-field")).into(),
             };
 
             cosmic::widget::column::with_children(vec![header.into(), content])
                 .spacing(spacing.space_xxs)
         };
 
+        let is_cloze = matches!(add_edit_flashcard.front, FlashcardField::Cloze { .. });
+
         let back_input = {
-            let type_selector =
-                pick_list(FlashcardField::ALL, Some(&add_edit_flashcard.back), |x| {
-                    Message::AddEditFlashcardInput(AddEditFlashcardInput::BackFieldTypeChanged(x))
-                })
-                .width(Length::Shrink);
+            // The back of a cloze is an optional extra text
+            let type_selector = (!is_cloze).then(|| {
+                pick_list(
+                    FlashcardField::ALL_BACK,
+                    Some(&add_edit_flashcard.back),
+                    |x| {
+                        Message::AddEditFlashcardInput(AddEditFlashcardInput::BackFieldTypeChanged(
+                            x,
+                        ))
+                    },
+                )
+                .width(Length::Shrink)
+            });
 
-            let header = row![
-                text::body(fl!("flashcard-back-title")).width(Length::Fill),
-                type_selector
-            ]
-            .spacing(spacing.space_s)
-            .align_y(Alignment::Center);
+            let title = if is_cloze {
+                fl!("cloze-back-extra-title")
+            } else {
+                fl!("flashcard-back-title")
+            };
+
+            let header = row![text::body(title).width(Length::Fill)]
+                .push_maybe(type_selector)
+                .spacing(spacing.space_s)
+                .align_y(Alignment::Center);
 
             let content = match &add_edit_flashcard.back {
+                FlashcardField::Text(text) if self.markdown_preview => {
+                    markdown(text, 14.0, Message::LaunchUrl)
+                }
                 FlashcardField::Text(text) => text_input(fl!("flashcard-back-placeholder"), text)
                     .on_input(|input| {
                         Message::AddEditFlashcardInput(AddEditFlashcardInput::BackTextInput(input))
@@ -682,12 +1784,96 @@ impl FlashcardsScreen {
                     .spacing(spacing.space_xxs)
                     .into()
                 }
+                FlashcardField::Audio { path, transcript } => audio_input(
+                    spacing,
+                    path,
+                    transcript,
+                    AddEditFlashcardInput::BackSelectAudio,
+                    AddEditFlashcardInput::DeleteBackSelectedAudio,
+                    AddEditFlashcardInput::BackTranscriptInput,
+                ),
+                FlashcardField::Code { language, source } if self.markdown_preview => {
+                    code(language, source, 14.0)
+                }
+                FlashcardField::Code { language, .. } => code_input(
+                    spacing,
+                    language,
+                    &code_editors.back,
+                    AddEditFlashcardInput::BackCodeLanguageSelected,
+                    AddEditFlashcardInput::BackCodeEdit,
+                ),
+                FlashcardField::Blocks(blocks) => {
+                    blocks_input(spacing, Side::Back, blocks, self.markdown_preview)
+                }
+                FlashcardField::Cloze { .. } | FlashcardField::Unsupported(_) => {
+                    text::body(fl!("unsupported-field")).into()
+                }
             };
 
             cosmic::widget::column::with_children(vec![header.into(), content])
                 .spacing(spacing.space_xxs)
         };
 
+        // Warn about (but allow) flashcards with the same front as another one
+        let duplicate_warning = (add_edit_note.is_none()
+            && duplicates::normalized_front(add_edit_flashcard).is_some_and(|front| {
+                duplicate_fronts
+                    .iter()
+                    .any(|(id, f)| *f == front && Some(*id) != add_edit_flashcard.id)
+            }))
+        .then(|| {
+            text::caption(fl!("duplicate-flashcard-warning")).class(theme::Text::Color(
+                Color::from(theme::active().cosmic().warning_color()),
+            ))
+        });
+
+        let tags_input = {
+            let tag_chips = add_edit_flashcard
+                .tags
+                .iter()
+                .map(|tag| {
+                    row![
+                        pill(tag.as_str()),
+                        button::icon(icons::get_handle("window-close-symbolic", 14))
+                            .on_press(Message::AddEditFlashcardInput(
+                                AddEditFlashcardInput::RemoveTag(tag.clone())
+                            ))
+                            .padding(4)
+                    ]
+                    .align_y(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<Message>>>();
+
+            cosmic::widget::column::with_children(vec![
+                text::body(fl!("tags")).into(),
+                flex_row(tag_chips)
+                    .row_spacing(spacing.space_xxs)
+                    .column_spacing(spacing.space_xxs)
+                    .into(),
+                row![
+                    text_input(fl!("new-tag-placeholder"), new_tag)
+                        .on_input(|input| {
+                            Message::AddEditFlashcardInput(AddEditFlashcardInput::NewTagInput(
+                                input,
+                            ))
+                        })
+                        .on_submit(|_| Message::AddEditFlashcardInput(
+                            AddEditFlashcardInput::AddTag
+                        )),
+                    button::icon(icons::get_handle("list-add-symbolic", 18))
+                        .class(theme::Button::Standard)
+                        .on_press_maybe((!new_tag.trim().is_empty()).then_some(
+                            Message::AddEditFlashcardInput(AddEditFlashcardInput::AddTag)
+                        ))
+                ]
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_xxs)
+                .into(),
+            ])
+            .spacing(spacing.space_xxs)
+        };
+
         let current_status_content: Element<Message> =
             if add_edit_flashcard.status == FlashcardStatus::None {
                 text(format!(
@@ -714,15 +1900,38 @@ impl FlashcardsScreen {
                 settings::section()
                     .title(fl!("flashcard-options"))
                     .add(
-                        cosmic::widget::column::with_children(vec![
-                            front_input.into(),
-                            back_input.into(),
-                        ])
-                        .spacing(spacing.space_s)
+                        cosmic::widget::column::with_capacity(5)
+                            .push(
+                                row![
+                                    text::body(fl!("markdown-preview")).width(Length::Fill),
+                                    toggler(self.markdown_preview)
+                                        .on_toggle(Message::ToggleMarkdownPreview)
+                                ]
+                                .align_y(Alignment::Center),
+                            )
+                            .push_maybe(note_type_input)
+                            .push_maybe(note_fields_input.is_none().then_some(front_input))
+                            .push_maybe(duplicate_warning)
+                            .push_maybe(note_fields_input.is_none().then_some(back_input))
+                            .push_maybe(note_fields_input)
+                            .push(tags_input)
+                            .spacing(spacing.space_s)
                     )
                     .into(),
             ]),
-            row![cosmic::widget::space::horizontal(), add_edit_button],
+            row![
+                if add_edit_flashcard.id.is_some() {
+                    Element::from(
+                        text_input(fl!("edit-reason-placeholder"), edit_reason)
+                            .on_input(Message::EditReasonInput),
+                    )
+                } else {
+                    cosmic::widget::space::horizontal().into()
+                },
+                add_edit_button
+            ]
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xxs),
             settings::view_column(vec![
                 settings::section()
                     .title(fl!("reset-flashcard-title"))
@@ -738,6 +1947,11 @@ impl FlashcardsScreen {
             ]
             .align_y(Alignment::Center)
         ]
+        .push_maybe((add_edit_note.is_none() && !revisions.is_empty()).then(|| {
+            // The version that replaced each revision is the one before it, the newest is the saved flashcard
+            let current = flashcards.iter().find(|f| f.id == add_edit_flashcard.id);
+            history_view(spacing, revisions, current)
+        }))
         .spacing(spacing.space_xxs)
         .into()
     }
@@ -748,7 +1962,12 @@ impl FlashcardsScreen {
 //
 
 /// View of the header of this screen
-fn header_view<'a>(spacing: Spacing, flashcards: &'a [Flashcard]) -> Element<'a, Message> {
+fn header_view<'a>(
+    spacing: Spacing,
+    flashcards_count: usize,
+    tag_options: &'a [String],
+    selected_tag: usize,
+) -> Element<'a, Message> {
     let new_flashcard_button = button::icon(icons::get_handle("list-add-symbolic", 18))
         .class(theme::Button::Suggested)
         .on_press(Message::OpenContextPage(
@@ -767,57 +1986,208 @@ fn header_view<'a>(spacing: Spacing, flashcards: &'a [Flashcard]) -> Element<'a,
             None,
         ));
 
-    let title = if !flashcards.is_empty() {
-        format!("{} ({})", fl!("flashcards"), &flashcards.len())
+    let title = if flashcards_count > 0 {
+        format!("{} ({})", fl!("flashcards"), flashcards_count)
     } else {
         fl!("flashcards")
     };
 
-    cosmic::widget::row::with_capacity(2)
+    // The first option means no filter, so there's nothing to filter if it's the only one
+    let tag_filter_dropdown = (tag_options.len() > 1)
+        .then(|| dropdown(tag_options, Some(selected_tag), Message::TagFilterChanged));
+
+    cosmic::widget::row::with_capacity(3)
         .align_y(Alignment::Center)
         .spacing(spacing.space_s)
         .padding([spacing.space_none, spacing.space_xxs])
         .push(text::title3(title).width(Length::Fill))
+        .push_maybe(tag_filter_dropdown)
         .push(row![options_button, study_button, new_flashcard_button].spacing(spacing.space_xxs))
         .into()
 }
 
+/// View of the bulk actions that can be applied to the selected flashcards, [`None`] if nothing is selected
+fn selection_view<'a>(
+    spacing: Spacing,
+    flashcards: &[&Flashcard],
+    selected: &HashSet<i32>,
+) -> Option<Element<'a, Message>> {
+    let selected_count = flashcards
+        .iter()
+        .filter(|f| f.id.is_some_and(|id| selected.contains(&id)))
+        .count();
+    if selected_count == 0 {
+        return None;
+    }
+
+    let action = |label: String, input: SelectionInput| -> Element<'a, Message> {
+        button::text(label)
+            .class(theme::Button::Standard)
+            .on_press(Message::SelectionInput(input))
+            .into()
+    };
+
+    let actions: Vec<Element<'a, Message>> = vec![
+        action(fl!("move-to"), SelectionInput::Move),
+        action(
+            fl!("add-tags"),
+            SelectionInput::Edit(BulkEdit::AddTags(String::new())),
+        ),
+        action(
+            fl!("suspend"),
+            SelectionInput::Edit(BulkEdit::Suspend(true)),
+        ),
+        action(
+            fl!("unsuspend"),
+            SelectionInput::Edit(BulkEdit::Suspend(false)),
+        ),
+        action(
+            fl!("reset-status"),
+            SelectionInput::Edit(BulkEdit::ResetStatus),
+        ),
+        action(fl!("export"), SelectionInput::Export),
+        button::text(fl!("delete"))
+            .class(theme::Button::Destructive)
+            .on_press(Message::SelectionInput(SelectionInput::Edit(
+                BulkEdit::Delete,
+            )))
+            .into(),
+    ];
+
+    Some(
+        container(
+            row![
+                text::heading(fl!("selected-flashcards", count = selected_count)),
+                button::text(fl!("select-all")).on_press_maybe(
+                    (selected_count < flashcards.len())
+                        .then_some(Message::SelectionInput(SelectionInput::SelectAll))
+                ),
+                button::text(fl!("clear-selection"))
+                    .on_press(Message::SelectionInput(SelectionInput::Clear)),
+                container(
+                    flex_row(actions)
+                        .column_spacing(spacing.space_xxs)
+                        .row_spacing(spacing.space_xxs)
+                )
+                .align_x(Horizontal::Right)
+                .width(Length::Fill)
+            ]
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xs),
+        )
+        .padding(spacing.space_xs)
+        .class(theme::Container::Card)
+        .into(),
+    )
+}
+
 /// View of the contents of this screen
-fn folders_view<'a>(spacing: &Spacing, flashcards: &'a [Flashcard]) -> Element<'a, Message> {
+fn folders_view<'a>(
+    spacing: &Spacing,
+    flashcards: &[&'a Flashcard],
+    selected: &HashSet<i32>,
+    empty_text: String,
+    clock: &dyn Clock,
+) -> Element<'a, Message> {
     let content: Element<'a, Message> = if flashcards.is_empty() {
-        text(fl!("empty-flashcards-page")).into()
+        text(empty_text).into()
     } else {
         let mut flashcards_list = list::list_column().style(theme::Container::Card);
 
         for flashcard in flashcards {
-            let front_text = match &flashcard.front {
-                FlashcardField::Text(t) => t,
-                FlashcardField::Image { alt_text, .. } => alt_text,
+            let front_content: Element<'a, Message> = match &flashcard.front {
+                FlashcardField::Text(t) => container(markdown(t, 14.0, Message::LaunchUrl))
+                    .align_y(Vertical::Center)
+                    .width(Length::Fill)
+                    .into(),
+                field => {
+                    let front_text = match field {
+                        FlashcardField::Unsupported(_) => fl!("unsupported-field"),
+                        field => field.plain_text().to_string(),
+                    };
+                    text(front_text)
+                        .align_y(Vertical::Center)
+                        .align_x(Horizontal::Left)
+                        .width(Length::Fill)
+                        .wrapping(Wrapping::WordOrGlyph)
+                        .into()
+                }
             };
 
+            let flashcard_id = flashcard.id.unwrap_or_default();
+
             flashcards_list = flashcards_list.add(
                 row![
+                    checkbox("", selected.contains(&flashcard_id)).on_toggle(move |_| {
+                        Message::SelectionInput(SelectionInput::Toggle(flashcard_id))
+                    }),
                     button::icon(icons::get_handle("edit-symbolic", 18))
                         .class(theme::Button::Standard)
                         .width(Length::Shrink)
                         .on_press(Message::OpenContextPage(
                             ContextPage::AddEditFlashcard,
-                            Some(flashcard.clone())
+                            Some((*flashcard).clone())
                         )),
-                    text(front_text)
-                        .align_y(Vertical::Center)
-                        .align_x(Horizontal::Left)
-                        .width(Length::Fill)
-                        .wrapping(Wrapping::WordOrGlyph),
+                    front_content,
+                    flex_row(
+                        flashcard
+                            .tags
+                            .iter()
+                            .map(|tag| pill(tag.as_str()).font_size(12.0).into())
+                            .collect::<Vec<Element<Message>>>()
+                    )
+                    .column_spacing(spacing.space_xxxs)
+                    .row_spacing(spacing.space_xxxs),
                     container(
-                        pill(flashcard.status.to_string()).color(flashcard.status.get_color())
+                        row![
+                            pill(flashcard.status.to_string()).color(flashcard.status.get_color())
+                        ]
+                        .push_maybe(
+                            flashcard
+                                .suspended
+                                .then(|| pill(fl!("suspended")).font_size(12.0))
+                        )
+                        .push_maybe(
+                            flashcard
+                                .is_buried(clock)
+                                .then(|| pill(fl!("buried")).font_size(12.0))
+                        )
+                        .spacing(spacing.space_xxxs)
+                        .align_y(Alignment::Center)
                     )
                     .align_y(Vertical::Center)
                     .align_x(Horizontal::Right)
                     .width(Length::Fill),
+                    button::icon(icons::get_handle(
+                        if flashcard.suspended {
+                            "media-playback-start-symbolic"
+                        } else {
+                            "media-playback-pause-symbolic"
+                        },
+                        18
+                    ))
+                    .class(theme::Button::Standard)
+                    .on_press(Message::SetFlashcardSuspended(
+                        flashcard.id.unwrap_or_default(),
+                        !flashcard.suspended
+                    )),
+                    button::icon(icons::get_handle("go-next-symbolic", 18))
+                        .class(theme::Button::Standard)
+                        .tooltip(fl!("move-to"))
+                        .on_press(Message::TransferFlashcards(
+                            vec![(*flashcard).clone()],
+                            false
+                        )),
+                    button::icon(icons::get_handle("edit-copy-symbolic", 18))
+                        .class(theme::Button::Standard)
+                        .tooltip(fl!("copy-to"))
+                        .on_press(Message::TransferFlashcards(
+                            vec![(*flashcard).clone()],
+                            true
+                        )),
                     button::icon(icons::get_handle("user-trash-full-symbolic", 18))
                         .class(theme::Button::Destructive)
-                        .on_press(Message::DeleteFlashcard(flashcard.clone()))
+                        .on_press(Message::DeleteFlashcard((*flashcard).clone()))
                 ]
                 .width(Length::Fill)
                 .align_y(Alignment::Center)
@@ -837,6 +2207,212 @@ fn folders_view<'a>(spacing: &Spacing, flashcards: &'a [Flashcard]) -> Element<'
 }
 
 //
+/// View of the audio input of a side of the upsert flashcard [`ContextPage`]
+fn audio_input<'a>(
+    spacing: Spacing,
+    path: &'a str,
+    transcript: &'a str,
+    select: AddEditFlashcardInput,
+    delete: AddEditFlashcardInput,
+    transcript_input: impl Fn(String) -> AddEditFlashcardInput + 'a,
+) -> Element<'a, Message> {
+    let audio_input: Element<Message> = if path.is_empty() {
+        row![
+            text::body(fl!("select-audio")).width(Length::Fill),
+            button::text(fl!("browse"))
+                .on_press(Message::AddEditFlashcardInput(select))
+                .class(theme::Button::Standard)
+                .width(Length::Shrink)
+        ]
+        .align_y(Alignment::Center)
+        .into()
+    } else {
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        row![
+            button::icon(icons::get_handle("media-playback-start-symbolic", 18))
+                .class(theme::Button::Standard)
+                .on_press(Message::AddEditFlashcardInput(
+                    AddEditFlashcardInput::PlayAudio(path.to_string())
+                )),
+            text::body(file_name).width(Length::Fill),
+            button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                .class(theme::Button::Destructive)
+                .on_press(Message::AddEditFlashcardInput(delete))
+        ]
+        .spacing(spacing.space_xxs)
+        .align_y(Alignment::Center)
+        .into()
+    };
+
+    cosmic::widget::column::with_children(vec![
+        audio_input,
+        text_input(fl!("audio-transcript"), transcript)
+            .on_input(move |input| Message::AddEditFlashcardInput(transcript_input(input)))
+            .into(),
+    ])
+    .spacing(spacing.space_xxs)
+    .into()
+}
+
+/// View of the input of a side made of blocks, every block can change it's type, be moved or removed
+fn blocks_input<'a>(
+    spacing: Spacing,
+    side: Side,
+    blocks: &'a [FlashcardField],
+    markdown_preview: bool,
+) -> Element<'a, Message> {
+    let last_index = blocks.len().saturating_sub(1);
+
+    let blocks_inputs = blocks.iter().enumerate().map(|(index, block)| {
+        let header = row![
+            text::body(fl!("block-title", number = (index as i64 + 1))).width(Length::Fill),
+            pick_list(FlashcardField::BLOCK_TYPES, Some(block), move |x| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::BlockTypeChanged(
+                    side, index, x,
+                ))
+            })
+            .width(Length::Shrink),
+            button::icon(icons::get_handle("go-up-symbolic", 18))
+                .class(theme::Button::Standard)
+                .on_press_maybe((index > 0).then_some(Message::AddEditFlashcardInput(
+                    AddEditFlashcardInput::MoveBlock(side, index, index.saturating_sub(1))
+                ))),
+            button::icon(icons::get_handle("go-down-symbolic", 18))
+                .class(theme::Button::Standard)
+                .on_press_maybe(
+                    (index < last_index).then_some(Message::AddEditFlashcardInput(
+                        AddEditFlashcardInput::MoveBlock(side, index, index + 1)
+                    ))
+                ),
+            button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                .class(theme::Button::Destructive)
+                .on_press(Message::AddEditFlashcardInput(
+                    AddEditFlashcardInput::RemoveBlock(side, index)
+                )),
+        ]
+        .spacing(spacing.space_xxs)
+        .align_y(Alignment::Center);
+
+        let content: Element<Message> = match block {
+            FlashcardField::Text(text) if markdown_preview => {
+                markdown(text, 14.0, Message::LaunchUrl)
+            }
+            FlashcardField::Text(text) => text_input(fl!("block-text-placeholder"), text)
+                .on_input(move |input| {
+                    Message::AddEditFlashcardInput(AddEditFlashcardInput::BlockTextInput(
+                        side, index, input,
+                    ))
+                })
+                .into(),
+            FlashcardField::Image { path, alt_text } => {
+                let image_input: Element<Message> = if path.is_empty() {
+                    row![
+                        text::body(fl!("select-image")).width(Length::Fill),
+                        button::text(fl!("browse"))
+                            .on_press(Message::AddEditFlashcardInput(
+                                AddEditFlashcardInput::BlockSelectImage(side, index)
+                            ))
+                            .class(theme::Button::Standard)
+                            .width(Length::Shrink)
+                    ]
+                    .align_y(Alignment::Center)
+                    .into()
+                } else {
+                    row![
+                        container(image(path).content_fit(ContentFit::Contain))
+                            .center_x(Length::Fill)
+                            .max_height(150.),
+                        button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                            .class(theme::Button::Destructive)
+                            .on_press(Message::AddEditFlashcardInput(
+                                AddEditFlashcardInput::DeleteBlockSelectedImage(side, index),
+                            ))
+                    ]
+                    .spacing(spacing.space_xxs)
+                    .align_y(Alignment::End)
+                    .into()
+                };
+
+                cosmic::widget::column::with_children(vec![
+                    image_input,
+                    text_input(fl!("block-image-alt"), alt_text)
+                        .on_input(move |input| {
+                            Message::AddEditFlashcardInput(
+                                AddEditFlashcardInput::BlockAltTextInput(side, index, input),
+                            )
+                        })
+                        .into(),
+                ])
+                .spacing(spacing.space_xxs)
+                .into()
+            }
+            FlashcardField::Audio { path, transcript } => audio_input(
+                spacing,
+                path,
+                transcript,
+                AddEditFlashcardInput::BlockSelectAudio(side, index),
+                AddEditFlashcardInput::DeleteBlockSelectedAudio(side, index),
+                move |input| AddEditFlashcardInput::BlockTranscriptInput(side, index, input),
+            ),
+            _ => text::body(fl!("unsupported-field")).into(),
+        };
+
+        cosmic::widget::column::with_children(vec![header.into(), content])
+            .spacing(spacing.space_xxs)
+            .into()
+    });
+
+    cosmic::widget::column::with_children(
+        blocks_inputs
+            .chain(std::iter::once(
+                button::text(fl!("add-block"))
+                    .class(theme::Button::Standard)
+                    .on_press(Message::AddEditFlashcardInput(
+                        AddEditFlashcardInput::AddBlock(side),
+                    ))
+                    .into(),
+            ))
+            .collect::<Vec<Element<Message>>>(),
+    )
+    .spacing(spacing.space_s)
+    .into()
+}
+
+/// View of the input of a code field, a language picker and a multiline editor
+fn code_input<'a>(
+    spacing: Spacing,
+    language: &str,
+    content: &'a text_editor::Content,
+    language_selected: fn(usize) -> AddEditFlashcardInput,
+    edit: fn(text_editor::Action) -> AddEditFlashcardInput,
+) -> Element<'a, Message> {
+    let selected = CODE_LANGUAGES.iter().position(|l| *l == language);
+
+    cosmic::widget::column::with_children(vec![
+        row![
+            text::body(fl!("code-language")).width(Length::Fill),
+            dropdown(CODE_LANGUAGES, selected, move |index| {
+                Message::AddEditFlashcardInput(language_selected(index))
+            })
+        ]
+        .spacing(spacing.space_s)
+        .align_y(Alignment::Center)
+        .into(),
+        text_editor(content)
+            .placeholder(fl!("code-placeholder"))
+            .font(Font::MONOSPACE)
+            .height(Length::Fixed(200.))
+            .on_action(move |action| Message::AddEditFlashcardInput(edit(action)))
+            .into(),
+    ])
+    .spacing(spacing.space_xxs)
+    .into()
+}
+
 // HELPERS
 //
 
@@ -844,50 +2420,46 @@ fn folders_view<'a>(spacing: &Spacing, flashcards: &'a [Flashcard]) -> Element<'
 fn apply_flashcard_add_edit_input(
     input: AddEditFlashcardInput,
     flashcard: &mut Flashcard,
+    note: &mut Option<Note>,
+    note_types: &[NoteType],
+    new_tag: &mut String,
+    cloze_selection: &mut String,
+    code_editors: &mut CodeEditors,
 ) -> Action {
     match input {
         AddEditFlashcardInput::FrontFieldTypeChanged(flashcard_field) => {
+            code_editors.front = text_editor::Content::new();
+            // The back of a cloze can only be text
+            if matches!(flashcard_field, FlashcardField::Cloze { .. })
+                && !matches!(flashcard.back, FlashcardField::Text(_))
+            {
+                flashcard.back = FlashcardField::default();
+            }
             flashcard.front = flashcard_field;
         }
+        AddEditFlashcardInput::ClozeTextInput(input) => {
+            if let FlashcardField::Cloze { text, .. } = &mut flashcard.front {
+                *text = input;
+            }
+        }
+        AddEditFlashcardInput::ClozeSelectionInput(input) => {
+            *cloze_selection = input;
+        }
+        AddEditFlashcardInput::WrapClozeSelection => {
+            if let FlashcardField::Cloze { text, .. } = &mut flashcard.front
+                && let Some(wrapped) = cloze::wrap_selection(text, cloze_selection)
+            {
+                *text = wrapped;
+                cloze_selection.clear();
+            }
+        }
         AddEditFlashcardInput::FrontTextInput(input) => {
             flashcard.front = FlashcardField::Text(input);
         }
         AddEditFlashcardInput::FrontSelectImage => {
-            return Action::Run(Task::perform(
-                async move {
-                    let result = SelectedFiles::open_file()
-                        .title("Select Image")
-                        .accept_label("Open")
-                        .modal(true)
-                        .multiple(false)
-                        .filter(
-                            FileFilter::new("Image Files")
-                                .glob("*.png")
-                                .glob("*.jpeg")
-                                .glob("*.jpg"),
-                        )
-                        .send()
-                        .await
-                        .unwrap()
-                        .response();
-
-                    if let Ok(result) = result {
-                        result
-                            .uris()
-                            .iter()
-                            .map(|file| file.path().to_string())
-                            .collect::<Vec<String>>()
-                            .first()
-                            .cloned()
-                            .unwrap_or(String::new())
-                    } else {
-                        String::new()
-                    }
-                },
-                |res| {
-                    Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontImageSelected(res))
-                },
-            ));
+            return Action::Run(Task::perform(select_image_file(), |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontImageSelected(res))
+            }));
         }
         AddEditFlashcardInput::FrontImageSelected(selected_path) =>
         {
@@ -901,21 +2473,9 @@ fn apply_flashcard_add_edit_input(
             }
         }
         AddEditFlashcardInput::DeleteFrontSelectedImage => {
-            #[allow(clippy::collapsible_if)]
             if let FlashcardField::Image { path, .. } = &mut flashcard.front {
-                if flashcard.id.is_some() && utils::check_path(path) {
-                    let path_clone = path.clone();
-                    *path = String::new();
-                    return Action::Run(Task::perform(utils::delete_image(path_clone), |res| {
-                        match res {
-                            Ok(_) => Message::None,
-                            Err(e) => Message::AddToast(OboeteToast::new(e)),
-                        }
-                    }));
-                } else {
-                    *path = String::new();
-                }
-            };
+                path.clear();
+            }
         }
         AddEditFlashcardInput::FrontAltTextInput(input) => {
             if let FlashcardField::Image { alt_text, .. } = &mut flashcard.front {
@@ -924,45 +2484,16 @@ fn apply_flashcard_add_edit_input(
         }
 
         AddEditFlashcardInput::BackFieldTypeChanged(flashcard_field) => {
+            code_editors.back = text_editor::Content::new();
             flashcard.back = flashcard_field;
         }
         AddEditFlashcardInput::BackTextInput(input) => {
             flashcard.back = FlashcardField::Text(input);
         }
         AddEditFlashcardInput::BackSelectImage => {
-            return Action::Run(Task::perform(
-                async move {
-                    let result = SelectedFiles::open_file()
-                        .title("Select Image")
-                        .accept_label("Open")
-                        .modal(true)
-                        .multiple(false)
-                        .filter(
-                            FileFilter::new("Image Files")
-                                .glob("*.png")
-                                .glob("*.jpeg")
-                                .glob("*.jpg"),
-                        )
-                        .send()
-                        .await
-                        .unwrap()
-                        .response();
-
-                    if let Ok(result) = result {
-                        result
-                            .uris()
-                            .iter()
-                            .map(|file| file.path().to_string())
-                            .collect::<Vec<String>>()
-                            .first()
-                            .cloned()
-                            .unwrap_or(String::new())
-                    } else {
-                        String::new()
-                    }
-                },
-                |res| Message::AddEditFlashcardInput(AddEditFlashcardInput::BackImageSelected(res)),
-            ));
+            return Action::Run(Task::perform(select_image_file(), |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::BackImageSelected(res))
+            }));
         }
         AddEditFlashcardInput::BackImageSelected(selected_path) =>
         {
@@ -976,32 +2507,322 @@ fn apply_flashcard_add_edit_input(
             }
         }
         AddEditFlashcardInput::DeleteBackSelectedImage => {
-            #[allow(clippy::collapsible_if)]
             if let FlashcardField::Image { path, .. } = &mut flashcard.back {
-                if flashcard.id.is_some() && utils::check_path(path) {
-                    let path_clone = path.clone();
-                    *path = String::new();
-                    return Action::Run(Task::perform(utils::delete_image(path_clone), |res| {
-                        match res {
-                            Ok(_) => Message::None,
-                            Err(e) => Message::AddToast(OboeteToast::new(e)),
-                        }
-                    }));
-                } else {
-                    *path = String::new();
-                }
-            };
+                path.clear();
+            }
         }
         AddEditFlashcardInput::BackAltTextInput(input) => {
             if let FlashcardField::Image { alt_text, .. } = &mut flashcard.back {
                 *alt_text = input;
             };
         }
+
+        AddEditFlashcardInput::FrontSelectAudio => {
+            return Action::Run(Task::perform(select_audio_file(), |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::FrontAudioSelected(res))
+            }));
+        }
+        AddEditFlashcardInput::FrontAudioSelected(selected_path) => {
+            if let FlashcardField::Audio { path, .. } = &mut flashcard.front
+                && !selected_path.is_empty()
+            {
+                *path = percent_decode(selected_path.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string();
+            }
+        }
+        AddEditFlashcardInput::DeleteFrontSelectedAudio => {
+            if let FlashcardField::Audio { path, .. } = &mut flashcard.front {
+                path.clear();
+            }
+        }
+        AddEditFlashcardInput::FrontTranscriptInput(input) => {
+            if let FlashcardField::Audio { transcript, .. } = &mut flashcard.front {
+                *transcript = input;
+            }
+        }
+        AddEditFlashcardInput::BackSelectAudio => {
+            return Action::Run(Task::perform(select_audio_file(), |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::BackAudioSelected(res))
+            }));
+        }
+        AddEditFlashcardInput::BackAudioSelected(selected_path) => {
+            if let FlashcardField::Audio { path, .. } = &mut flashcard.back
+                && !selected_path.is_empty()
+            {
+                *path = percent_decode(selected_path.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string();
+            }
+        }
+        AddEditFlashcardInput::DeleteBackSelectedAudio => {
+            if let FlashcardField::Audio { path, .. } = &mut flashcard.back {
+                path.clear();
+            }
+        }
+        AddEditFlashcardInput::BackTranscriptInput(input) => {
+            if let FlashcardField::Audio { transcript, .. } = &mut flashcard.back {
+                *transcript = input;
+            }
+        }
+        AddEditFlashcardInput::FrontCodeLanguageSelected(index) => {
+            if let FlashcardField::Code { language, .. } = &mut flashcard.front
+                && let Some(selected) = CODE_LANGUAGES.get(index)
+            {
+                *language = selected.to_string();
+            }
+        }
+        AddEditFlashcardInput::FrontCodeEdit(action) => {
+            if let FlashcardField::Code { source, .. } = &mut flashcard.front {
+                code_editors.front.perform(action);
+                *source = code_editors.front.text().trim_end_matches('\n').to_string();
+            }
+        }
+        AddEditFlashcardInput::BackCodeLanguageSelected(index) => {
+            if let FlashcardField::Code { language, .. } = &mut flashcard.back
+                && let Some(selected) = CODE_LANGUAGES.get(index)
+            {
+                *language = selected.to_string();
+            }
+        }
+        AddEditFlashcardInput::BackCodeEdit(action) => {
+            if let FlashcardField::Code { source, .. } = &mut flashcard.back {
+                code_editors.back.perform(action);
+                *source = code_editors.back.text().trim_end_matches('\n').to_string();
+            }
+        }
+        AddEditFlashcardInput::AddBlock(side) => {
+            if let FlashcardField::Blocks(blocks) = side_field(flashcard, side) {
+                blocks.push(FlashcardField::default());
+            }
+        }
+        AddEditFlashcardInput::RemoveBlock(side, index) => {
+            if let FlashcardField::Blocks(blocks) = side_field(flashcard, side)
+                && index < blocks.len()
+            {
+                blocks.remove(index);
+            }
+        }
+        AddEditFlashcardInput::MoveBlock(side, from, to) => {
+            if let FlashcardField::Blocks(blocks) = side_field(flashcard, side)
+                && from < blocks.len()
+                && to < blocks.len()
+            {
+                blocks.swap(from, to);
+            }
+        }
+        AddEditFlashcardInput::BlockTypeChanged(side, index, block_type) => {
+            if let Some(block) = side_block(flashcard, side, index) {
+                *block = block_type;
+            }
+        }
+        AddEditFlashcardInput::BlockTextInput(side, index, input) => {
+            if let Some(block) = side_block(flashcard, side, index) {
+                *block = FlashcardField::Text(input);
+            }
+        }
+        AddEditFlashcardInput::BlockSelectImage(side, index) => {
+            return Action::Run(Task::perform(select_image_file(), move |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::BlockImageSelected(
+                    side, index, res,
+                ))
+            }));
+        }
+        AddEditFlashcardInput::BlockImageSelected(side, index, selected_path) => {
+            if let Some(FlashcardField::Image { path, .. }) = side_block(flashcard, side, index)
+                && !selected_path.is_empty()
+            {
+                *path = percent_decode(selected_path.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string();
+            }
+        }
+        AddEditFlashcardInput::DeleteBlockSelectedImage(side, index) => {
+            if let Some(FlashcardField::Image { path, .. }) = side_block(flashcard, side, index) {
+                path.clear();
+            }
+        }
+        AddEditFlashcardInput::BlockAltTextInput(side, index, input) => {
+            if let Some(FlashcardField::Image { alt_text, .. }) = side_block(flashcard, side, index)
+            {
+                *alt_text = input;
+            }
+        }
+        AddEditFlashcardInput::BlockSelectAudio(side, index) => {
+            return Action::Run(Task::perform(select_audio_file(), move |res| {
+                Message::AddEditFlashcardInput(AddEditFlashcardInput::BlockAudioSelected(
+                    side, index, res,
+                ))
+            }));
+        }
+        AddEditFlashcardInput::BlockAudioSelected(side, index, selected_path) => {
+            if let Some(FlashcardField::Audio { path, .. }) = side_block(flashcard, side, index)
+                && !selected_path.is_empty()
+            {
+                *path = percent_decode(selected_path.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string();
+            }
+        }
+        AddEditFlashcardInput::DeleteBlockSelectedAudio(side, index) => {
+            if let Some(FlashcardField::Audio { path, .. }) = side_block(flashcard, side, index) {
+                path.clear();
+            }
+        }
+        AddEditFlashcardInput::BlockTranscriptInput(side, index, input) => {
+            if let Some(FlashcardField::Audio { transcript, .. }) =
+                side_block(flashcard, side, index)
+            {
+                *transcript = input;
+            }
+        }
+        AddEditFlashcardInput::PlayAudio(path) => {
+            return Action::Run(Task::perform(utils::play_audio(path), |res| match res {
+                Ok(_) => Message::None,
+                Err(e) => Message::AddToast(OboeteToast::new(e)),
+            }));
+        }
+
+        AddEditFlashcardInput::NewTagInput(input) => {
+            *new_tag = input;
+        }
+        AddEditFlashcardInput::AddTag => {
+            // Tags can't contain whitespace, so typing "a b" adds two tags
+            for tag in parse_tags(new_tag) {
+                if !flashcard.has_tag(&tag) {
+                    flashcard.tags.push(tag);
+                }
+            }
+            new_tag.clear();
+        }
+        AddEditFlashcardInput::RemoveTag(tag) => {
+            flashcard.tags.retain(|t| t != &tag);
+        }
+
+        AddEditFlashcardInput::NoteTypeSelected(index) => {
+            // The first option is a plain flashcard
+            *note = index
+                .checked_sub(1)
+                .and_then(|index| note_types.get(index))
+                .map(Note::new);
+        }
+        AddEditFlashcardInput::NoteFieldInput(index, input) => {
+            if let Some(field) = note.as_mut().and_then(|n| n.fields.get_mut(index)) {
+                *field = input;
+            }
+        }
     }
 
     Action::None
 }
 
+/// Opens a file chooser to select an audio file, returns it's path (empty if none was selected)
+async fn select_audio_file() -> String {
+    let filter = utils::AUDIO_EXTENSIONS
+        .iter()
+        .fold(FileFilter::new("Audio Files"), |filter, extension| {
+            filter.glob(&format!("*.{extension}"))
+        });
+
+    let result = SelectedFiles::open_file()
+        .title("Select Audio")
+        .accept_label("Open")
+        .modal(true)
+        .multiple(false)
+        .filter(filter)
+        .send()
+        .await
+        .unwrap()
+        .response();
+
+    if let Ok(result) = result {
+        result
+            .uris()
+            .iter()
+            .map(|file| file.path().to_string())
+            .collect::<Vec<String>>()
+            .first()
+            .cloned()
+            .unwrap_or(String::new())
+    } else {
+        String::new()
+    }
+}
+
+/// Opens a file chooser to select where the flashcards are exported, returns it's path (empty if none was selected)
+async fn select_export_file() -> String {
+    let result = SelectedFiles::save_file()
+        .title("Save Export File")
+        .accept_label("Save")
+        .modal(true)
+        .filter(FileFilter::new("TXT File").glob("*.txt"))
+        .send()
+        .await
+        .unwrap()
+        .response();
+
+    if let Ok(result) = result {
+        result
+            .uris()
+            .iter()
+            .map(|file| file.path().to_string())
+            .collect::<Vec<String>>()
+            .first()
+            .cloned()
+            .unwrap_or(String::new())
+    } else {
+        String::new()
+    }
+}
+
+// Helper function to get the field of the given side of the flashcard
+fn side_field(flashcard: &mut Flashcard, side: Side) -> &mut FlashcardField {
+    match side {
+        Side::Front => &mut flashcard.front,
+        Side::Back => &mut flashcard.back,
+    }
+}
+
+// Helper function to get the block of the given index of a side made of blocks
+fn side_block(flashcard: &mut Flashcard, side: Side, index: usize) -> Option<&mut FlashcardField> {
+    match side_field(flashcard, side) {
+        FlashcardField::Blocks(blocks) => blocks.get_mut(index),
+        _ => None,
+    }
+}
+
+/// Opens the file chooser to select an image, returns an empty string if none was selected
+async fn select_image_file() -> String {
+    let result = SelectedFiles::open_file()
+        .title("Select Image")
+        .accept_label("Open")
+        .modal(true)
+        .multiple(false)
+        .filter(
+            FileFilter::new("Image Files")
+                .glob("*.png")
+                .glob("*.jpeg")
+                .glob("*.jpg"),
+        )
+        .send()
+        .await
+        .unwrap()
+        .response();
+
+    if let Ok(result) = result {
+        result
+            .uris()
+            .iter()
+            .map(|file| file.path().to_string())
+            .collect::<Vec<String>>()
+            .first()
+            .cloned()
+            .unwrap_or(String::new())
+    } else {
+        String::new()
+    }
+}
+
 /// Given the [`FolderOptionsInput`] apply the appropiate state changes
 fn apply_folder_options_input(
     input: FolderOptionsInput,
@@ -1009,6 +2830,8 @@ fn apply_folder_options_input(
     folder_id: i32,
     database: &Arc<Pool<Sqlite>>,
     flashcards: &[Flashcard],
+    duplicate_scope: DuplicateScope,
+    clock: &dyn Clock,
 ) -> Action {
     match input {
         FolderOptionsInput::ImportContentInput(input) => {
@@ -1020,6 +2843,9 @@ fn apply_folder_options_input(
         FolderOptionsInput::BetweenTermsInput(input) => {
             options.between_terms = input;
         }
+        FolderOptionsInput::DuplicateActionSelected(index) => {
+            options.duplicate_action = DuplicateAction::ALL.get(index).copied().unwrap_or_default();
+        }
         FolderOptionsInput::CustomImport => {
             let content = utils::parse_import_content(
                 &options.between_cards,
@@ -1027,9 +2853,16 @@ fn apply_folder_options_input(
                 &options.import_content,
             );
             return Action::Run(Task::perform(
-                Flashcard::add_bulk(Arc::clone(database), content, folder_id),
+                Flashcard::add_bulk(
+                    Arc::clone(database),
+                    content,
+                    folder_id,
+                    duplicate_scope,
+                    options.duplicate_action,
+                    clock.now(),
+                ),
                 |res| match res {
-                    Ok(_) => Message::LoadFlashcards,
+                    Ok(summary) => Message::FlashcardsImported(summary),
                     Err(e) => Message::AddToast(OboeteToast::new(e)),
                 },
             ));
@@ -1070,9 +2903,16 @@ fn apply_folder_options_input(
 
             if let Ok(content) = parsed_content_res {
                 return Action::Run(Task::perform(
-                    Flashcard::add_bulk(Arc::clone(database), content, folder_id),
+                    utils::import_anki_flashcards(
+                        Arc::clone(database),
+                        content,
+                        folder_id,
+                        duplicate_scope,
+                        options.duplicate_action,
+                        clock.now(),
+                    ),
                     |res| match res {
-                        Ok(_) => Message::LoadFlashcards,
+                        Ok(summary) => Message::FlashcardsImported(summary),
                         Err(e) => Message::AddToast(OboeteToast::new(e)),
                     },
                 ));
@@ -1090,36 +2930,21 @@ fn apply_folder_options_input(
                 },
             ));
         }
-
-        FolderOptionsInput::Export => {
+        FolderOptionsInput::UnsuspendAll => {
             return Action::Run(Task::perform(
-                async move {
-                    let result = SelectedFiles::save_file()
-                        .title("Save Export File")
-                        .accept_label("Save")
-                        .modal(true)
-                        .filter(FileFilter::new("TXT File").glob("*.txt"))
-                        .send()
-                        .await
-                        .unwrap()
-                        .response();
-
-                    if let Ok(result) = result {
-                        result
-                            .uris()
-                            .iter()
-                            .map(|file| file.path().to_string())
-                            .collect::<Vec<String>>()
-                            .first()
-                            .cloned()
-                            .unwrap_or(String::new())
-                    } else {
-                        String::new()
-                    }
+                Flashcard::unsuspend_all(Arc::clone(database), folder_id),
+                |res| match res {
+                    Ok(_) => Message::LoadFlashcards,
+                    Err(e) => Message::AddToast(OboeteToast::new(e)),
                 },
-                |res| Message::FolderOptionsInput(FolderOptionsInput::CompleteExport(res)),
             ));
         }
+
+        FolderOptionsInput::Export => {
+            return Action::Run(Task::perform(select_export_file(), |res| {
+                Message::FolderOptionsInput(FolderOptionsInput::CompleteExport(res))
+            }));
+        }
         FolderOptionsInput::CompleteExport(file_path) => {
             let res = utils::export_flashcards(&file_path, flashcards);
             if let Err(e) = res {
@@ -1168,3 +2993,133 @@ fn apply_folder_options_input(
     }
     Action::None
 }
+
+// Helper function to render the math of the given fields, once it's done the screen gets redrawn showing it
+fn render_math<'a>(fields: impl Iterator<Item = &'a FlashcardField>) -> Task<Message> {
+    let formulas: Vec<(String, bool)> = fields.flat_map(|f| f.math_formulas()).collect();
+    if formulas.is_empty() {
+        return Task::none();
+    }
+
+    Task::perform(math::render_math(formulas), |res| match res {
+        Ok(_) => Message::None,
+        Err(e) => {
+            eprintln!("{}", e);
+            Message::AddToast(OboeteToast::new(e))
+        }
+    })
+}
+
+// Helper function to get the index of the tag filter option of the given tag, the leech tag is the leeches option
+fn tag_option_index(tag_options: &[String], flashcards: &[Flashcard], tag: &str) -> Option<usize> {
+    if tag.eq_ignore_ascii_case(LEECH_TAG) {
+        return flashcards.iter().any(Flashcard::is_leech).then_some(1);
+    }
+
+    tag_options
+        .iter()
+        .skip(1)
+        .position(|t| t.eq_ignore_ascii_case(tag))
+        .map(|index| index + 1)
+}
+
+/// View of the revisions of a [`Flashcard`], each one with the changes made after it
+fn history_view<'a>(
+    spacing: Spacing,
+    revisions: &'a [FlashcardRevision],
+    current: Option<&'a Flashcard>,
+) -> Element<'a, Message> {
+    let theme = theme::active();
+    let removed_color = Color::from(theme.cosmic().destructive_color());
+    let added_color = Color::from(theme.cosmic().success_color());
+
+    let mut history = list::list_column().style(theme::Container::Card);
+
+    for (index, revision) in revisions.iter().enumerate() {
+        let (next_front, next_back) = match index {
+            0 => match current {
+                Some(flashcard) => (&flashcard.front, &flashcard.back),
+                None => (&revision.front, &revision.back),
+            },
+            _ => (&revisions[index - 1].front, &revisions[index - 1].back),
+        };
+
+        let mut changes = cosmic::widget::column::with_capacity(2).spacing(spacing.space_xxs);
+        for (title, old, new) in [
+            (fl!("flashcard-front-title"), &revision.front, next_front),
+            (fl!("flashcard-back-title"), &revision.back, next_back),
+        ] {
+            let (old, new) = (old.plain_text(), new.plain_text());
+            let parts = diff::diff(&old, &new);
+            if diff::is_same(&parts) {
+                continue;
+            }
+
+            let words = parts
+                .into_iter()
+                .map(|part| match part {
+                    DiffPart::Same(word) => text::body(word.trim_end().to_string()).into(),
+                    DiffPart::Removed(word) => text::body(word.trim_end().to_string())
+                        .class(theme::Text::Color(removed_color))
+                        .into(),
+                    DiffPart::Added(word) => text::body(word.trim_end().to_string())
+                        .class(theme::Text::Color(added_color))
+                        .into(),
+                })
+                .collect::<Vec<Element<Message>>>();
+
+            changes = changes.push(
+                column![
+                    text::caption(title),
+                    flex_row(words)
+                        .row_spacing(spacing.space_xxxs)
+                        .column_spacing(spacing.space_xxs)
+                ]
+                .spacing(spacing.space_xxxs),
+            );
+        }
+
+        let details = match &revision.reason {
+            Some(reason) => format!("{} · {}", format_timestamp(revision.edited_at), reason),
+            None => format_timestamp(revision.edited_at),
+        };
+
+        history = history.add(
+            column![
+                row![
+                    text::caption(details)
+                        .wrapping(Wrapping::WordOrGlyph)
+                        .width(Length::Fill),
+                    button::text(fl!("revert"))
+                        .class(theme::Button::Standard)
+                        .on_press(Message::RevertFlashcard(revision.clone()))
+                ]
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_s),
+                changes
+            ]
+            .spacing(spacing.space_xxs),
+        );
+    }
+
+    cosmic::widget::column::with_children(vec![
+        settings::view_column(vec![
+            settings::section().title(fl!("flashcard-history")).into(),
+        ])
+        .into(),
+        history.into(),
+    ])
+    .spacing(spacing.space_xxs)
+    .into()
+}
+
+// Helper function to show a timestamp (seconds since epoch) in local time
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| {
+            date.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
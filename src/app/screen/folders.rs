@@ -1,26 +1,37 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use cosmic::cosmic_theme::Spacing;
 use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::clipboard::mime::{AllowedMimeTypes, AsMimeTypes};
 use cosmic::iced::{Alignment, Length, Subscription};
 use cosmic::iced_core::text::Wrapping;
 use cosmic::iced_widget::{column, row};
+use cosmic::widget::dnd_destination::dnd_destination_for_data;
 use cosmic::widget::{
-    Row, button, container, list, scrollable, settings, slider, text, text_input,
+    Row, button, container, dnd_source, dropdown, list, scrollable, settings, slider, text,
+    text_input,
 };
 use cosmic::{Element, Task, theme};
 use sqlx::{Pool, Sqlite};
 
 use crate::app::context_page::ContextPage;
-use crate::app::core::models::folder::Folder;
-use crate::app::core::utils::OboeteToast;
+use crate::app::core::models::folder::{self, Folder, FolderCounts};
+use crate::app::core::models::ordering::OrderMode;
+use crate::app::core::utils::fsrs_scheduler::{self, OptimisationResult};
+use crate::app::core::utils::{Clock, OboeteToast};
 use crate::{fl, icons};
 
 /// Screen [`State`] holder
 pub struct FoldersScreen {
     current_set_id: Option<i32>,
+    clock: Arc<dyn Clock>,
+    order: OrderMode,
+    /// The study set can be dragged onto the nav bar to reorder the study sets (only when they are ordered manually)
+    studyset_draggable: bool,
     state: State,
 }
 
@@ -31,9 +42,125 @@ enum State {
     Ready {
         edit_folder: Folder,
         folders: Vec<Folder>,
+        counts: HashMap<i32, FolderCounts>,
+        collapsed: HashSet<i32>,
+        parent_options: ParentOptions,
+        optimisation: Optimisation,
     },
 }
 
+/// Folders the currently editing [`Folder`] can be moved into (itself and it's subfolders excluded)
+#[derive(Default)]
+struct ParentOptions {
+    ids: Vec<Option<i32>>,
+    names: Vec<String>,
+}
+
+impl ParentOptions {
+    fn new(folders: &[Folder], edit_folder: &Folder) -> Self {
+        let excluded = edit_folder
+            .id
+            .map(|id| folder::folder_descendants(folders, id))
+            .unwrap_or_default();
+
+        let mut options = Self {
+            ids: vec![None],
+            names: vec![fl!("no-parent-folder")],
+        };
+
+        for (depth, folder) in folder::folder_tree(folders) {
+            if folder.id.is_some_and(|id| excluded.contains(&id)) {
+                continue;
+            }
+            options.ids.push(folder.id);
+            options
+                .names
+                .push(format!("{}{}", "    ".repeat(depth), folder.name));
+        }
+
+        options
+    }
+}
+
+/// Mime type of a [`Folder`] being dragged to reorder the folders
+const FOLDER_MIME_TYPE: &str = "application/x-oboete-folder";
+
+/// Id of the [`Folder`] being dragged to reorder the folders
+#[derive(Debug, Clone, Copy)]
+pub struct DraggedFolder(i32);
+
+impl AsMimeTypes for DraggedFolder {
+    fn available(&self) -> Cow<'static, [String]> {
+        Cow::Owned(vec![FOLDER_MIME_TYPE.to_string()])
+    }
+
+    fn as_bytes(&self, mime_type: &str) -> Option<Cow<'static, [u8]>> {
+        (mime_type == FOLDER_MIME_TYPE).then(|| Cow::Owned(self.0.to_string().into_bytes()))
+    }
+}
+
+impl AllowedMimeTypes for DraggedFolder {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![FOLDER_MIME_TYPE.to_string()])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for DraggedFolder {
+    type Error = anywho::Error;
+
+    fn try_from((bytes, _mime_type): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|id| id.parse().ok())
+            .map(Self)
+            .ok_or_else(|| anywho::anywho!("Invalid dragged folder"))
+    }
+}
+
+/// Mime type of a [`StudySet`] being dragged onto the nav bar to reorder the study sets
+const STUDYSET_MIME_TYPE: &str = "application/x-oboete-studyset";
+
+/// Id of the [`StudySet`] being dragged onto the nav bar to reorder the study sets
+#[derive(Debug, Clone, Copy)]
+pub struct DraggedStudySet(pub i32);
+
+impl AsMimeTypes for DraggedStudySet {
+    fn available(&self) -> Cow<'static, [String]> {
+        Cow::Owned(vec![STUDYSET_MIME_TYPE.to_string()])
+    }
+
+    fn as_bytes(&self, mime_type: &str) -> Option<Cow<'static, [u8]>> {
+        (mime_type == STUDYSET_MIME_TYPE).then(|| Cow::Owned(self.0.to_string().into_bytes()))
+    }
+}
+
+impl AllowedMimeTypes for DraggedStudySet {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![STUDYSET_MIME_TYPE.to_string()])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for DraggedStudySet {
+    type Error = anywho::Error;
+
+    fn try_from((bytes, _mime_type): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|id| id.parse().ok())
+            .map(Self)
+            .ok_or_else(|| anywho::anywho!("Invalid dragged study set"))
+    }
+}
+
+/// State of the FSRS parameters optimisation of the currently editing [`Folder`]
+#[derive(Default)]
+enum Optimisation {
+    #[default]
+    Idle,
+    Running,
+    Finished(OptimisationResult),
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     /// Show the user a toast
@@ -41,12 +168,16 @@ pub enum Message {
     /// Load the folders into state
     LoadFolders,
     /// Callback after asking to load the folders into state
-    FoldersLoaded(Result<Vec<Folder>, anywho::Error>),
+    FoldersLoaded(Result<(Vec<Folder>, HashMap<i32, FolderCounts>), anywho::Error>),
+    /// Ask to show or hide the subfolders of the given [`Folder`] id
+    ToggleFolderExpanded(i32),
+    /// Callback after dropping a dragged [`Folder`] (if it could be read) onto the given [`Folder`] id
+    FolderDropped(Option<DraggedFolder>, i32),
 
     /// Ask to open the [`DialogPage`] for creating a new studyset
     OpenCreateStudySetDialog,
-    /// Ask to open the [`DialogPage`] for creating a new folder
-    OpenCreateFolderDialog,
+    /// Ask to open the [`DialogPage`] for creating a new folder (optionally inside of the given parent folder)
+    OpenCreateFolderDialog(Option<i32>),
     /// Ask to open the given [`ContextPage`] for the given [`Folder`]
     OpenContextPage(ContextPage, Folder),
 
@@ -55,6 +186,17 @@ pub enum Message {
     /// Callback after some input has been modified for the currently editing folder
     EditFolderInput(EditFolderInput),
 
+    /// Ask to train the FSRS parameters of the currently editing folder with it's review history
+    OptimiseFolder,
+    /// Callback after training the FSRS parameters of the currently editing folder
+    FolderOptimised(Result<OptimisationResult, anywho::Error>),
+    /// Ask to save the trained FSRS parameters for the currently editing folder
+    AcceptOptimisation,
+    /// Ask to forget the trained FSRS parameters without saving them
+    DiscardOptimisation,
+    /// Ask to go back to the default FSRS parameters for the currently editing folder
+    ResetFsrsParameters,
+
     /// Ask to delete a [`Folder`] from the database
     DeleteFolder(i32),
 
@@ -69,7 +211,7 @@ pub enum Action {
     AddToast(OboeteToast),
 
     OpenCreateStudySetDialog,
-    OpenCreateFolderDialog,
+    OpenCreateFolderDialog(Option<i32>),
     OpenDeleteFolderDialog(i32),
     OpenContextPage(ContextPage),
 
@@ -81,19 +223,33 @@ pub enum Action {
 pub enum EditFolderInput {
     NameInput(String),
     DesiredRetentionInput(f32),
+    LearningStepsInput(String),
+    RelearningStepsInput(String),
+    ParentSelected(usize),
+    LeechThresholdInput(u32),
+    LeechSuspendToggled(bool),
 }
 
 impl FoldersScreen {
     /// Init the screen
-    pub fn new(database: &Arc<Pool<Sqlite>>, studyset_id: Option<i32>) -> (Self, Task<Message>) {
+    pub fn new(
+        database: &Arc<Pool<Sqlite>>,
+        studyset_id: Option<i32>,
+        clock: Arc<dyn Clock>,
+        order: OrderMode,
+        studyset_draggable: bool,
+    ) -> (Self, Task<Message>) {
         if let Some(set_id) = studyset_id {
             (
                 Self {
                     current_set_id: Some(set_id),
+                    clock: Arc::clone(&clock),
+                    order,
+                    studyset_draggable,
                     state: State::Loading,
                 },
                 Task::perform(
-                    Folder::get_all(Arc::clone(database), set_id),
+                    Folder::get_all_with_counts(Arc::clone(database), set_id, clock),
                     Message::FoldersLoaded,
                 ),
             )
@@ -101,6 +257,9 @@ impl FoldersScreen {
             (
                 Self {
                     current_set_id: None,
+                    clock,
+                    order,
+                    studyset_draggable,
                     state: State::NoStudySet,
                 },
                 Task::none(),
@@ -124,11 +283,23 @@ impl FoldersScreen {
             )
             .center(Length::Fill)
             .into(),
-            State::Ready { folders, .. } => {
+            State::Ready {
+                folders,
+                counts,
+                collapsed,
+                ..
+            } => {
                 let spacing = theme::active().cosmic().spacing;
 
-                let header = header_view(spacing, folders);
-                let content = folders_view(&spacing, folders);
+                let dragged_set = self.current_set_id.filter(|_| self.studyset_draggable);
+                let header = header_view(spacing, folders, dragged_set);
+                let content = folders_view(
+                    &spacing,
+                    folders,
+                    counts,
+                    collapsed,
+                    self.order == OrderMode::Manual,
+                );
 
                 container(
                     column![header, content]
@@ -149,7 +320,11 @@ impl FoldersScreen {
             Message::LoadFolders => {
                 if let Some(current_set_id) = self.current_set_id {
                     Action::Run(Task::perform(
-                        Folder::get_all(Arc::clone(database), current_set_id),
+                        Folder::get_all_with_counts(
+                            Arc::clone(database),
+                            current_set_id,
+                            Arc::clone(&self.clock),
+                        ),
                         Message::FoldersLoaded,
                     ))
                 } else {
@@ -158,16 +333,36 @@ impl FoldersScreen {
             }
             Message::FoldersLoaded(res) => {
                 match res {
-                    Ok(folders) => {
-                        if let State::Ready { edit_folder, .. } = &self.state {
-                            self.state = State::Ready {
-                                folders,
-                                edit_folder: edit_folder.clone(),
-                            }
+                    Ok((mut folders, counts)) => {
+                        self.order.sort(
+                            &mut folders,
+                            |f| &f.name,
+                            |f| {
+                                f.id.and_then(|id| counts.get(&id))
+                                    .map(|c| c.due)
+                                    .unwrap_or_default()
+                            },
+                        );
+
+                        if let State::Ready {
+                            folders: current_folders,
+                            counts: current_counts,
+                            edit_folder,
+                            parent_options,
+                            ..
+                        } = &mut self.state
+                        {
+                            *parent_options = ParentOptions::new(&folders, edit_folder);
+                            *current_folders = folders;
+                            *current_counts = counts;
                         } else {
                             self.state = State::Ready {
                                 folders,
+                                counts,
+                                collapsed: HashSet::new(),
+                                parent_options: ParentOptions::default(),
                                 edit_folder: Folder::default(),
+                                optimisation: Optimisation::default(),
                             }
                         }
                     }
@@ -181,14 +376,81 @@ impl FoldersScreen {
 
             Message::OpenCreateStudySetDialog => Action::OpenCreateStudySetDialog,
 
-            Message::OpenCreateFolderDialog => Action::OpenCreateFolderDialog,
+            Message::ToggleFolderExpanded(folder_id) => {
+                if let State::Ready { collapsed, .. } = &mut self.state
+                    && !collapsed.remove(&folder_id)
+                {
+                    collapsed.insert(folder_id);
+                }
+                Action::None
+            }
+
+            Message::FolderDropped(dragged, target_id) => {
+                let State::Ready { folders, .. } = &self.state else {
+                    return Action::None;
+                };
+                let Some(DraggedFolder(folder_id)) = dragged else {
+                    return Action::None;
+                };
+
+                // A folder can't be moved inside of itself or it's subfolders
+                if self.order != OrderMode::Manual
+                    || folder::folder_descendants(folders, folder_id).contains(&target_id)
+                {
+                    return Action::None;
+                }
+
+                let Some(target) = folders.iter().find(|f| f.id == Some(target_id)) else {
+                    return Action::None;
+                };
+
+                // The dropped folder takes the place of the target between it's siblings
+                let mut siblings: Vec<i32> = folders
+                    .iter()
+                    .filter(|f| f.parent_id == target.parent_id)
+                    .filter_map(|f| f.id)
+                    .collect();
+                let old_index = siblings.iter().position(|id| *id == folder_id);
+                siblings.retain(|id| *id != folder_id);
+                let target_index = siblings
+                    .iter()
+                    .position(|id| *id == target_id)
+                    .unwrap_or_default();
+                let new_index = match old_index {
+                    Some(index) if index <= target_index => target_index + 1,
+                    _ => target_index,
+                };
+                siblings.insert(new_index, folder_id);
+
+                Action::Run(Task::perform(
+                    Folder::reorder(Arc::clone(database), target.parent_id, siblings),
+                    |res| match res {
+                        Ok(_) => Message::LoadFolders,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            Message::AddToast(OboeteToast::new(e))
+                        }
+                    },
+                ))
+            }
+
+            Message::OpenCreateFolderDialog(parent_id) => Action::OpenCreateFolderDialog(parent_id),
 
             Message::OpenContextPage(context_page, folder) => {
-                let State::Ready { edit_folder, .. } = &mut self.state else {
+                let State::Ready {
+                    edit_folder,
+                    folders,
+                    parent_options,
+                    optimisation,
+                    ..
+                } = &mut self.state
+                else {
                     return Action::None;
                 };
 
+                *parent_options = ParentOptions::new(folders, &folder);
                 *edit_folder = folder;
+                *optimisation = Optimisation::Idle;
 
                 Action::OpenContextPage(context_page)
             }
@@ -210,7 +472,12 @@ impl FoldersScreen {
                 ))
             }
             Message::EditFolderInput(input) => {
-                let State::Ready { edit_folder, .. } = &mut self.state else {
+                let State::Ready {
+                    edit_folder,
+                    parent_options,
+                    ..
+                } = &mut self.state
+                else {
                     return Action::None;
                 };
 
@@ -219,11 +486,122 @@ impl FoldersScreen {
                     EditFolderInput::DesiredRetentionInput(value) => {
                         edit_folder.desired_retention = value
                     }
+                    EditFolderInput::LearningStepsInput(value) => {
+                        edit_folder.learning_steps = value
+                    }
+                    EditFolderInput::RelearningStepsInput(value) => {
+                        edit_folder.relearning_steps = value
+                    }
+                    EditFolderInput::ParentSelected(index) => {
+                        if let Some(parent_id) = parent_options.ids.get(index) {
+                            edit_folder.parent_id = *parent_id;
+                        }
+                    }
+                    EditFolderInput::LeechThresholdInput(value) => {
+                        edit_folder.leech_threshold = value
+                    }
+                    EditFolderInput::LeechSuspendToggled(value) => {
+                        edit_folder.leech_suspend = value
+                    }
                 };
 
                 Action::None
             }
 
+            Message::OptimiseFolder => {
+                let State::Ready {
+                    edit_folder,
+                    optimisation,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                *optimisation = Optimisation::Running;
+
+                Action::Run(Task::perform(
+                    fsrs_scheduler::optimise_parameters(Arc::clone(database), edit_folder.clone()),
+                    Message::FolderOptimised,
+                ))
+            }
+            Message::FolderOptimised(res) => {
+                let State::Ready { optimisation, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(result) => {
+                        *optimisation = Optimisation::Finished(result);
+                        Action::None
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        *optimisation = Optimisation::Idle;
+                        Action::AddToast(OboeteToast::new(e))
+                    }
+                }
+            }
+            Message::AcceptOptimisation => {
+                let State::Ready {
+                    edit_folder,
+                    optimisation,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                let Optimisation::Finished(result) = std::mem::take(optimisation) else {
+                    return Action::None;
+                };
+
+                edit_folder.fsrs_parameters = Some(result.parameters.clone());
+
+                Action::Run(Task::perform(
+                    Folder::set_fsrs_parameters(
+                        Arc::clone(database),
+                        edit_folder.id.unwrap_or_default(),
+                        Some(result.parameters),
+                    ),
+                    |res| match res {
+                        Ok(_) => Message::LoadFolders,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            Message::AddToast(OboeteToast::new(e))
+                        }
+                    },
+                ))
+            }
+            Message::DiscardOptimisation => {
+                if let State::Ready { optimisation, .. } = &mut self.state {
+                    *optimisation = Optimisation::Idle;
+                }
+                Action::None
+            }
+            Message::ResetFsrsParameters => {
+                let State::Ready { edit_folder, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                edit_folder.fsrs_parameters = None;
+
+                Action::Run(Task::perform(
+                    Folder::set_fsrs_parameters(
+                        Arc::clone(database),
+                        edit_folder.id.unwrap_or_default(),
+                        None,
+                    ),
+                    |res| match res {
+                        Ok(_) => Message::LoadFolders,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            Message::AddToast(OboeteToast::new(e))
+                        }
+                    },
+                ))
+            }
+
             Message::DeleteFolder(folder_id) => Action::OpenDeleteFolderDialog(folder_id),
 
             Message::OpenFolder(folder_id) => Action::OpenFolder(folder_id),
@@ -241,13 +619,29 @@ impl FoldersScreen {
 
     /// View of the folder settings [`ContextPage`] of the application
     pub fn folder_settings<'a>(&'a self, spacing: Spacing) -> Element<'a, Message> {
-        let State::Ready { edit_folder, .. } = &self.state else {
+        let State::Ready {
+            edit_folder,
+            parent_options,
+            optimisation,
+            ..
+        } = &self.state
+        else {
             return text("Error").into(); // It's theoretically impossible to be here.
         };
 
+        let selected_parent = parent_options
+            .ids
+            .iter()
+            .position(|id| *id == edit_folder.parent_id);
+
         let edit_button = Row::new().push(cosmic::widget::space::horizontal()).push(
             button::text(fl!("edit"))
-                .on_press_maybe((!edit_folder.name.is_empty()).then_some(Message::EditFolder))
+                .on_press_maybe(
+                    (!edit_folder.name.is_empty()
+                        && fsrs_scheduler::parse_steps(&edit_folder.learning_steps).is_some()
+                        && fsrs_scheduler::parse_steps(&edit_folder.relearning_steps).is_some())
+                    .then_some(Message::EditFolder),
+                )
                 .class(theme::Button::Suggested),
         );
 
@@ -263,6 +657,13 @@ impl FoldersScreen {
                     ])
                     .spacing(spacing.space_xxs),
                 )
+                .add(
+                    settings::item::builder(fl!("parent-folder")).control(dropdown(
+                        parent_options.names.as_slice(),
+                        selected_parent,
+                        |index| Message::EditFolderInput(EditFolderInput::ParentSelected(index)),
+                    )),
+                )
                 .add(
                     settings::item::builder(fl!("retention-rate"))
                         .description(format!(
@@ -277,10 +678,106 @@ impl FoldersScreen {
                             .step(0.01f32),
                         ),
                 )
+                .add(
+                    cosmic::widget::column::with_children(vec![
+                        text::body(fl!("learning-steps")).into(),
+                        text_input("1m 10m", &edit_folder.learning_steps)
+                            .on_input(|v| {
+                                Message::EditFolderInput(EditFolderInput::LearningStepsInput(v))
+                            })
+                            .into(),
+                        text::body(fl!("relearning-steps")).into(),
+                        text_input("10m", &edit_folder.relearning_steps)
+                            .on_input(|v| {
+                                Message::EditFolderInput(EditFolderInput::RelearningStepsInput(v))
+                            })
+                            .into(),
+                        text::caption(fl!("steps-description")).into(),
+                    ])
+                    .spacing(spacing.space_xxs),
+                )
+                .into(),
+            settings::section()
+                .title(fl!("leeches"))
+                .add(
+                    settings::item::builder(fl!("leech-threshold"))
+                        .description(if edit_folder.leech_threshold == 0 {
+                            fl!("leech-detection-disabled")
+                        } else {
+                            fl!(
+                                "leech-threshold-description",
+                                lapses = edit_folder.leech_threshold
+                            )
+                        })
+                        .control(
+                            slider(0..=20, edit_folder.leech_threshold, move |v| {
+                                Message::EditFolderInput(EditFolderInput::LeechThresholdInput(v))
+                            })
+                            .step(1u32),
+                        ),
+                )
+                .add(
+                    settings::item::builder(fl!("leech-suspend"))
+                        .toggler(edit_folder.leech_suspend, |v| {
+                            Message::EditFolderInput(EditFolderInput::LeechSuspendToggled(v))
+                        }),
+                )
                 .into(),
         ]);
 
-        column![settings, edit_button]
+        let optimisation_item: Element<'a, Message> = match optimisation {
+            Optimisation::Idle => {
+                settings::item::builder(fl!("optimise-parameters"))
+                    .description(fl!("optimise-parameters-description"))
+                    .control(button::standard(fl!("optimise")).on_press_maybe(
+                        edit_folder.id.is_some().then_some(Message::OptimiseFolder),
+                    ))
+                    .into()
+            }
+            Optimisation::Running => settings::item::builder(fl!("optimise-parameters"))
+                .description(fl!("optimise-parameters-description"))
+                .control(button::standard(fl!("optimising")))
+                .into(),
+            Optimisation::Finished(result) => settings::item::builder(fl!("optimisation-result"))
+                .description(fl!(
+                    "optimisation-log-loss",
+                    old = format!("{:.4}", result.old_log_loss),
+                    new = format!("{:.4}", result.new_log_loss),
+                    reviews = result.review_count
+                ))
+                .control(
+                    row![
+                        button::standard(fl!("discard")).on_press(Message::DiscardOptimisation),
+                        button::suggested(fl!("accept")).on_press(Message::AcceptOptimisation)
+                    ]
+                    .spacing(spacing.space_xxs),
+                )
+                .into(),
+        };
+
+        let fsrs_settings = settings::view_column(vec![
+            settings::section()
+                .title(fl!("fsrs-parameters"))
+                .add(
+                    settings::item::builder(if edit_folder.fsrs_parameters.is_some() {
+                        fl!("optimised-parameters")
+                    } else {
+                        fl!("default-parameters")
+                    })
+                    .control(
+                        button::destructive(fl!("reset-parameters")).on_press_maybe(
+                            edit_folder
+                                .fsrs_parameters
+                                .is_some()
+                                .then_some(Message::ResetFsrsParameters),
+                        ),
+                    ),
+                )
+                .add(optimisation_item)
+                .into(),
+        ]);
+
+        column![settings, edit_button, fsrs_settings]
             .spacing(spacing.space_xs)
             .into()
     }
@@ -290,11 +787,15 @@ impl FoldersScreen {
 // VIEWS
 //
 
-/// View of the header of this screen
-fn header_view<'a>(spacing: Spacing, folders: &'a [Folder]) -> Element<'a, Message> {
+/// View of the header of this screen, the study set can be dragged from it's handle (if given) onto the nav bar
+fn header_view<'a>(
+    spacing: Spacing,
+    folders: &'a [Folder],
+    dragged_set: Option<i32>,
+) -> Element<'a, Message> {
     let new_folder_button = button::icon(icons::get_handle("list-add-symbolic", 18))
         .class(theme::Button::Suggested)
-        .on_press(Message::OpenCreateFolderDialog);
+        .on_press(Message::OpenCreateFolderDialog(None));
 
     let title = if !folders.is_empty() {
         format!("{} ({})", fl!("folders"), &folders.len())
@@ -302,52 +803,131 @@ fn header_view<'a>(spacing: Spacing, folders: &'a [Folder]) -> Element<'a, Messa
         fl!("folders")
     };
 
-    cosmic::widget::row::with_capacity(2)
+    cosmic::widget::row::with_capacity(3)
         .align_y(Alignment::Center)
         .spacing(spacing.space_s)
         .padding([spacing.space_none, spacing.space_xxs])
+        .push_maybe(dragged_set.map(|set_id| {
+            dnd_source::<Message, DraggedStudySet>(icons::get_icon("list-drag-handle-symbolic", 18))
+                .drag_content(move || DraggedStudySet(set_id))
+        }))
         .push(text::title3(title).width(Length::Fill))
         .push(new_folder_button)
         .into()
 }
 
 /// View of the contents of this screen
-fn folders_view<'a>(spacing: &Spacing, folders: &'a [Folder]) -> Element<'a, Message> {
+fn folders_view<'a>(
+    spacing: &Spacing,
+    folders: &'a [Folder],
+    counts: &HashMap<i32, FolderCounts>,
+    collapsed: &HashSet<i32>,
+    draggable: bool,
+) -> Element<'a, Message> {
     let content: Element<'a, Message> = if folders.is_empty() {
         text(fl!("empty-folders-page")).into()
     } else {
         let mut folders_list = list::list_column().style(theme::Container::Card);
+        // Depth of the collapsed folder whose subfolders we are currently skipping
+        let mut hidden_below: Option<usize> = None;
+
+        for (depth, folder) in folder::folder_tree(folders) {
+            if let Some(hidden_depth) = hidden_below {
+                if depth > hidden_depth {
+                    continue;
+                }
+                hidden_below = None;
+            }
+
+            let folder_id = folder.id.unwrap_or_default();
+            let is_collapsed = collapsed.contains(&folder_id);
+            if is_collapsed {
+                hidden_below = Some(depth);
+            }
+
+            let expand_button: Element<'a, Message> =
+                if folders.iter().any(|f| f.parent_id == folder.id) {
+                    button::icon(icons::get_handle(
+                        if is_collapsed {
+                            "go-next-symbolic"
+                        } else {
+                            "go-down-symbolic"
+                        },
+                        18,
+                    ))
+                    .class(theme::Button::Text)
+                    .on_press(Message::ToggleFolderExpanded(folder_id))
+                    .into()
+                } else {
+                    cosmic::widget::space::horizontal()
+                        .width(Length::Fixed(36.0))
+                        .into()
+                };
+
+            let folder_counts = counts.get(&folder_id).copied().unwrap_or_default();
 
-        for folder in folders {
-            folders_list = folders_list.add(
+            let folder_row = row![
+                cosmic::widget::space::horizontal()
+                    .width(Length::Fixed(depth as f32 * f32::from(spacing.space_l))),
+                expand_button,
                 row![
+                    button::icon(icons::get_handle("folder-open-symbolic", 18))
+                        .class(theme::Button::Suggested)
+                        .width(Length::Shrink)
+                        .on_press(Message::OpenFolder(folder_id)),
+                    button::icon(icons::get_handle("edit-symbolic", 18))
+                        .class(theme::Button::Standard)
+                        .width(Length::Shrink)
+                        .on_press(Message::OpenContextPage(
+                            ContextPage::FolderSettings,
+                            folder.clone()
+                        ))
+                ]
+                .spacing(spacing.space_xxs),
+                text(folder.name.clone())
+                    .align_y(Vertical::Center)
+                    .align_x(Horizontal::Left)
+                    .width(Length::Fill)
+                    .wrapping(Wrapping::WordOrGlyph),
+                text::caption(fl!(
+                    "folder-counts",
+                    due = folder_counts.due,
+                    total = folder_counts.total
+                )),
+                button::icon(icons::get_handle("folder-new-symbolic", 18))
+                    .class(theme::Button::Standard)
+                    .on_press(Message::OpenCreateFolderDialog(Some(folder_id))),
+                button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                    .class(theme::Button::Destructive)
+                    .on_press(Message::DeleteFolder(folder_id))
+            ]
+            .width(Length::Fill)
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_s);
+
+            // Folders are reordered by dragging their handle onto another folder
+            let folder_row: Element<'a, Message> = if draggable {
+                dnd_destination_for_data(
                     row![
-                        button::icon(icons::get_handle("folder-open-symbolic", 18))
-                            .class(theme::Button::Suggested)
-                            .width(Length::Shrink)
-                            .on_press(Message::OpenFolder(folder.id.unwrap_or_default())),
-                        button::icon(icons::get_handle("edit-symbolic", 18))
-                            .class(theme::Button::Standard)
-                            .width(Length::Shrink)
-                            .on_press(Message::OpenContextPage(
-                                ContextPage::FolderSettings,
-                                folder.clone()
-                            ))
+                        dnd_source::<Message, DraggedFolder>(icons::get_icon(
+                            "list-drag-handle-symbolic",
+                            18
+                        ))
+                        .drag_content(move || DraggedFolder(folder_id)),
+                        folder_row
                     ]
+                    .align_y(Alignment::Center)
                     .spacing(spacing.space_xxs),
-                    text(folder.name.clone())
-                        .align_y(Vertical::Center)
-                        .align_x(Horizontal::Left)
-                        .width(Length::Fill)
-                        .wrapping(Wrapping::WordOrGlyph),
-                    button::icon(icons::get_handle("user-trash-full-symbolic", 18))
-                        .class(theme::Button::Destructive)
-                        .on_press(Message::DeleteFolder(folder.id.unwrap_or_default()))
-                ]
-                .width(Length::Fill)
-                .align_y(Alignment::Center)
-                .spacing(spacing.space_s),
-            );
+                    move |dragged: Option<DraggedFolder>, _action| {
+                        Message::FolderDropped(dragged, folder_id)
+                    },
+                )
+                .into()
+            } else {
+                folder_row.into()
+            };
+
+            folders_list = folders_list.add(folder_row);
         }
 
         folders_list.into()
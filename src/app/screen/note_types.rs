@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::Arc;
+
+use cosmic::cosmic_theme::Spacing;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::{Alignment, Length, Subscription};
+use cosmic::iced_core::text::Wrapping;
+use cosmic::iced_widget::{column, row};
+use cosmic::widget::{Row, button, container, list, scrollable, settings, text, text_input};
+use cosmic::{Element, Task, theme};
+use sqlx::{Pool, Sqlite};
+
+use crate::app::context_page::ContextPage;
+use crate::app::core::models::note::{CardTemplate, NoteType};
+use crate::app::core::utils::{Clock, OboeteToast};
+use crate::{fl, icons};
+
+/// Screen [`State`] holder
+pub struct NoteTypesScreen {
+    state: State,
+    clock: Arc<dyn Clock>,
+}
+
+/// The different states this screen can be in
+enum State {
+    Loading,
+    Ready {
+        edit_note_type: NoteType,
+        note_types: Vec<NoteType>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Show the user a toast
+    AddToast(OboeteToast),
+    /// Load the note types into state
+    LoadNoteTypes,
+    /// Callback after asking to load the note types into state
+    NoteTypesLoaded(Result<Vec<NoteType>, anywho::Error>),
+
+    /// Ask to open the given [`ContextPage`] for the given [`NoteType`]
+    OpenContextPage(ContextPage, NoteType),
+
+    /// Ask to add or edit the currently editing [`NoteType`] in the database
+    SaveNoteType,
+    /// Callback after some input has been modified for the currently editing note type
+    EditNoteTypeInput(EditNoteTypeInput),
+
+    /// Ask to delete a [`NoteType`] from the database
+    DeleteNoteType(i32),
+
+    /// Ask to go back to the folders of the current studyset
+    Back,
+}
+
+/// Allows us to talk with the parent screen
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    AddToast(OboeteToast),
+
+    OpenDeleteNoteTypeDialog(i32),
+    OpenContextPage(ContextPage),
+
+    Back,
+}
+
+/// Identifies the possible inputs of the edit note type [`ContextPage`]
+#[derive(Debug, Clone)]
+pub enum EditNoteTypeInput {
+    NameInput(String),
+
+    FieldInput(usize, String),
+    AddField,
+    RemoveField(usize),
+
+    TemplateNameInput(usize, String),
+    TemplateFrontInput(usize, String),
+    TemplateBackInput(usize, String),
+    AddTemplate,
+    RemoveTemplate(usize),
+}
+
+impl NoteTypesScreen {
+    /// Init the screen
+    pub fn new(database: &Arc<Pool<Sqlite>>, clock: Arc<dyn Clock>) -> (Self, Task<Message>) {
+        (
+            Self {
+                state: State::Loading,
+                clock,
+            },
+            Task::perform(
+                NoteType::get_all(Arc::clone(database)),
+                Message::NoteTypesLoaded,
+            ),
+        )
+    }
+
+    /// View of the screen
+    pub fn view(&self) -> Element<'_, Message> {
+        match &self.state {
+            State::Loading => container(text(fl!("loading"))).center(Length::Fill).into(),
+            State::Ready { note_types, .. } => {
+                let spacing = theme::active().cosmic().spacing;
+
+                let header = header_view(spacing, note_types);
+                let content = note_types_view(&spacing, note_types);
+
+                container(
+                    column![header, content]
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .spacing(spacing.space_s),
+                )
+                .center(Length::Fill)
+                .into()
+            }
+        }
+    }
+
+    /// Handles interactions for this screen
+    pub fn update(&mut self, message: Message, database: &Arc<Pool<Sqlite>>) -> Action {
+        match message {
+            Message::AddToast(toast) => Action::AddToast(toast),
+            Message::LoadNoteTypes => Action::Run(Task::perform(
+                NoteType::get_all(Arc::clone(database)),
+                Message::NoteTypesLoaded,
+            )),
+            Message::NoteTypesLoaded(res) => {
+                match res {
+                    Ok(note_types) => {
+                        if let State::Ready {
+                            note_types: current_note_types,
+                            ..
+                        } = &mut self.state
+                        {
+                            *current_note_types = note_types;
+                        } else {
+                            self.state = State::Ready {
+                                note_types,
+                                edit_note_type: new_note_type(),
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
+                    }
+                }
+                Action::None
+            }
+
+            Message::OpenContextPage(context_page, note_type) => {
+                let State::Ready { edit_note_type, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                *edit_note_type = note_type;
+
+                Action::OpenContextPage(context_page)
+            }
+
+            Message::SaveNoteType => {
+                let State::Ready { edit_note_type, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let note_type = edit_note_type.clone();
+                let task = if note_type.id.is_some() {
+                    Task::perform(
+                        NoteType::edit(Arc::clone(database), note_type, self.clock.now()),
+                        |res| match res {
+                            Ok(_) => Message::LoadNoteTypes,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                Message::AddToast(OboeteToast::new(e))
+                            }
+                        },
+                    )
+                } else {
+                    // Start again with an empty note type so it's not added twice
+                    *edit_note_type = new_note_type();
+
+                    Task::perform(
+                        NoteType::add(Arc::clone(database), note_type),
+                        |res| match res {
+                            Ok(_) => Message::LoadNoteTypes,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                Message::AddToast(OboeteToast::new(e))
+                            }
+                        },
+                    )
+                };
+
+                Action::Run(task)
+            }
+            Message::EditNoteTypeInput(input) => {
+                let State::Ready { edit_note_type, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                apply_note_type_input(input, edit_note_type);
+
+                Action::None
+            }
+
+            Message::DeleteNoteType(note_type_id) => Action::OpenDeleteNoteTypeDialog(note_type_id),
+
+            Message::Back => Action::Back,
+        }
+    }
+
+    /// Subscriptions of this screen
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    //
+    // CONTEXT PAGES
+    //
+
+    /// View of the edit note type [`ContextPage`] of the application
+    pub fn edit_contextpage<'a>(&'a self, spacing: Spacing) -> Element<'a, Message> {
+        let State::Ready { edit_note_type, .. } = &self.state else {
+            return text("Error").into(); // It's theoretically impossible to be here.
+        };
+
+        let save_button = Row::new().push(cosmic::widget::space::horizontal()).push(
+            button::text(if edit_note_type.id.is_some() {
+                fl!("edit")
+            } else {
+                fl!("create")
+            })
+            .on_press_maybe(edit_note_type.is_valid().then_some(Message::SaveNoteType))
+            .class(theme::Button::Suggested),
+        );
+
+        let fields = edit_note_type
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                row![
+                    text_input(fl!("field-name"), field).on_input(move |v| {
+                        Message::EditNoteTypeInput(EditNoteTypeInput::FieldInput(index, v))
+                    }),
+                    button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                        .class(theme::Button::Destructive)
+                        .on_press_maybe((edit_note_type.fields.len() > 1).then_some(
+                            Message::EditNoteTypeInput(EditNoteTypeInput::RemoveField(index))
+                        ))
+                ]
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_xxs)
+                .into()
+            })
+            .collect::<Vec<Element<Message>>>();
+
+        let templates = edit_note_type
+            .templates
+            .iter()
+            .enumerate()
+            .map(|(index, template)| {
+                cosmic::widget::column::with_children(vec![
+                    row![
+                        text_input(fl!("template-name"), &template.name).on_input(move |v| {
+                            Message::EditNoteTypeInput(EditNoteTypeInput::TemplateNameInput(
+                                index, v,
+                            ))
+                        }),
+                        button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                            .class(theme::Button::Destructive)
+                            .on_press_maybe((edit_note_type.templates.len() > 1).then_some(
+                                Message::EditNoteTypeInput(EditNoteTypeInput::RemoveTemplate(
+                                    index
+                                ))
+                            ))
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+                    .into(),
+                    text::caption(fl!("template-front")).into(),
+                    text_input("{{Front}}", &template.front)
+                        .on_input(move |v| {
+                            Message::EditNoteTypeInput(EditNoteTypeInput::TemplateFrontInput(
+                                index, v,
+                            ))
+                        })
+                        .into(),
+                    text::caption(fl!("template-back")).into(),
+                    text_input("{{Back}}", &template.back)
+                        .on_input(move |v| {
+                            Message::EditNoteTypeInput(EditNoteTypeInput::TemplateBackInput(
+                                index, v,
+                            ))
+                        })
+                        .into(),
+                ])
+                .spacing(spacing.space_xxs)
+                .into()
+            })
+            .collect::<Vec<Element<Message>>>();
+
+        let placeholders = edit_note_type
+            .fields
+            .iter()
+            .map(|field| format!("{{{{{field}}}}}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        column![
+            settings::view_column(vec![
+                settings::section()
+                    .title(fl!("note-type-details"))
+                    .add(
+                        cosmic::widget::column::with_children(vec![
+                            text::body(fl!("note-type-name")).into(),
+                            text_input(fl!("note-type-name"), &edit_note_type.name)
+                                .on_input(|v| {
+                                    Message::EditNoteTypeInput(EditNoteTypeInput::NameInput(v))
+                                })
+                                .into(),
+                        ])
+                        .spacing(spacing.space_xxs),
+                    )
+                    .into(),
+                settings::section()
+                    .title(fl!("note-type-fields"))
+                    .add(cosmic::widget::column::with_children(fields).spacing(spacing.space_xxs))
+                    .add(
+                        Row::new().push(cosmic::widget::space::horizontal()).push(
+                            button::text(fl!("add-field"))
+                                .on_press(Message::EditNoteTypeInput(EditNoteTypeInput::AddField))
+                                .class(theme::Button::Standard),
+                        ),
+                    )
+                    .into(),
+                settings::section()
+                    .title(fl!("card-templates"))
+                    .add(cosmic::widget::column::with_children(templates).spacing(spacing.space_s),)
+                    .add(text::caption(fl!(
+                        "card-templates-description",
+                        placeholders = placeholders
+                    )))
+                    .add(
+                        Row::new().push(cosmic::widget::space::horizontal()).push(
+                            button::text(fl!("add-template"))
+                                .on_press(Message::EditNoteTypeInput(
+                                    EditNoteTypeInput::AddTemplate
+                                ))
+                                .class(theme::Button::Standard),
+                        ),
+                    )
+                    .into(),
+            ]),
+            save_button
+        ]
+        .spacing(spacing.space_xs)
+        .into()
+    }
+}
+
+/// A new [`NoteType`] with the same fields and template as a plain flashcard
+fn new_note_type() -> NoteType {
+    let front = fl!("field-front");
+    let back = fl!("field-back");
+
+    NoteType {
+        id: None,
+        name: String::new(),
+        templates: vec![CardTemplate {
+            ord: 0,
+            name: fl!("card-template-default-name", number = 1),
+            front: format!("{{{{{front}}}}}"),
+            back: format!("{{{{{back}}}}}"),
+        }],
+        fields: vec![front, back],
+    }
+}
+
+/// Given the [`EditNoteTypeInput`] apply the appropiate state changes
+fn apply_note_type_input(input: EditNoteTypeInput, note_type: &mut NoteType) {
+    match input {
+        EditNoteTypeInput::NameInput(value) => note_type.name = value,
+
+        EditNoteTypeInput::FieldInput(index, value) => {
+            if let Some(field) = note_type.fields.get_mut(index) {
+                *field = value;
+            }
+        }
+        EditNoteTypeInput::AddField => note_type.fields.push(String::new()),
+        EditNoteTypeInput::RemoveField(index) => {
+            if index < note_type.fields.len() {
+                note_type.fields.remove(index);
+            }
+        }
+
+        EditNoteTypeInput::TemplateNameInput(index, value) => {
+            if let Some(template) = note_type.templates.get_mut(index) {
+                template.name = value;
+            }
+        }
+        EditNoteTypeInput::TemplateFrontInput(index, value) => {
+            if let Some(template) = note_type.templates.get_mut(index) {
+                template.front = value;
+            }
+        }
+        EditNoteTypeInput::TemplateBackInput(index, value) => {
+            if let Some(template) = note_type.templates.get_mut(index) {
+                template.back = value;
+            }
+        }
+        EditNoteTypeInput::AddTemplate => {
+            let ord = note_type.next_ord();
+            note_type.templates.push(CardTemplate {
+                ord,
+                name: fl!(
+                    "card-template-default-name",
+                    number = note_type.templates.len() + 1
+                ),
+                ..Default::default()
+            });
+        }
+        EditNoteTypeInput::RemoveTemplate(index) => {
+            if index < note_type.templates.len() {
+                note_type.templates.remove(index);
+            }
+        }
+    }
+}
+
+//
+// VIEWS
+//
+
+/// View of the header of this screen
+fn header_view<'a>(spacing: Spacing, note_types: &'a [NoteType]) -> Element<'a, Message> {
+    let back_button = button::icon(icons::get_handle("go-previous-symbolic", 18))
+        .class(theme::Button::Icon)
+        .on_press(Message::Back);
+
+    let new_note_type_button = button::icon(icons::get_handle("list-add-symbolic", 18))
+        .class(theme::Button::Suggested)
+        .on_press(Message::OpenContextPage(
+            ContextPage::EditNoteType,
+            new_note_type(),
+        ));
+
+    let title = if !note_types.is_empty() {
+        format!("{} ({})", fl!("note-types"), &note_types.len())
+    } else {
+        fl!("note-types")
+    };
+
+    cosmic::widget::row::with_capacity(3)
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_s)
+        .padding([spacing.space_none, spacing.space_xxs])
+        .push(back_button)
+        .push(text::title3(title).width(Length::Fill))
+        .push(new_note_type_button)
+        .into()
+}
+
+/// View of the contents of this screen
+fn note_types_view<'a>(spacing: &Spacing, note_types: &'a [NoteType]) -> Element<'a, Message> {
+    let content: Element<'a, Message> = if note_types.is_empty() {
+        text(fl!("empty-note-types-page")).into()
+    } else {
+        let mut note_types_list = list::list_column().style(theme::Container::Card);
+
+        for note_type in note_types {
+            note_types_list = note_types_list.add(
+                row![
+                    button::icon(icons::get_handle("edit-symbolic", 18))
+                        .class(theme::Button::Standard)
+                        .width(Length::Shrink)
+                        .on_press(Message::OpenContextPage(
+                            ContextPage::EditNoteType,
+                            note_type.clone()
+                        )),
+                    text(note_type.name.clone())
+                        .align_y(Vertical::Center)
+                        .align_x(Horizontal::Left)
+                        .width(Length::Fill)
+                        .wrapping(Wrapping::WordOrGlyph),
+                    text::caption(fl!(
+                        "note-type-summary",
+                        fields = note_type.fields.len(),
+                        templates = note_type.templates.len()
+                    )),
+                    button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                        .class(theme::Button::Destructive)
+                        .on_press(Message::DeleteNoteType(note_type.id.unwrap_or_default()))
+                ]
+                .width(Length::Fill)
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_s),
+            );
+        }
+
+        note_types_list.into()
+    };
+
+    scrollable(
+        container(content)
+            .align_x(Horizontal::Center)
+            .width(Length::Fill),
+    )
+    .into()
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::Arc;
+
+use cosmic::cosmic_theme::Spacing;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::{Alignment, Length, Subscription};
+use cosmic::iced_core::text::Wrapping;
+use cosmic::iced_widget::{column, row};
+use cosmic::widget::{button, container, list, scrollable, text};
+use cosmic::{Element, Task, theme};
+use sqlx::{Pool, Sqlite};
+
+use crate::app::core::models::flashcard::FlashcardField;
+use crate::app::core::models::search::SearchResult;
+use crate::app::core::utils::OboeteToast;
+use crate::{fl, icons};
+
+/// Screen [`State`] holder
+pub struct SearchScreen {
+    /// What the user searched for
+    query: String,
+    state: State,
+}
+
+/// The different states this screen can be in
+enum State {
+    Loading,
+    Ready { results: Vec<SearchResult> },
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Callback after asking to search the flashcards
+    ResultsLoaded(Result<Vec<SearchResult>, anywho::Error>),
+    /// Ask to open the given [`SearchResult`] on it's folder
+    OpenResult(SearchResult),
+}
+
+/// Allows us to talk with the parent screen
+pub enum Action {
+    None,
+    AddToast(OboeteToast),
+
+    /// Open the flashcard (flashcard_id) on it's folder (folder_id) of it's studyset (studyset_id)
+    OpenFlashcard {
+        studyset_id: i32,
+        folder_id: i32,
+        flashcard_id: i32,
+    },
+}
+
+impl SearchScreen {
+    /// Init the screen
+    pub fn new(database: &Arc<Pool<Sqlite>>, query: String) -> (Self, Task<Message>) {
+        (
+            Self {
+                query: query.clone(),
+                state: State::Loading,
+            },
+            Task::perform(
+                SearchResult::search(Arc::clone(database), query),
+                Message::ResultsLoaded,
+            ),
+        )
+    }
+
+    /// View of the screen
+    pub fn view(&self) -> Element<'_, Message> {
+        match &self.state {
+            State::Loading => container(text(fl!("loading"))).center(Length::Fill).into(),
+            State::Ready { results } => {
+                let spacing = theme::active().cosmic().spacing;
+
+                let header = header_view(spacing, &self.query, results.len());
+                let content = results_view(&spacing, results);
+
+                container(
+                    column![header, content]
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .spacing(spacing.space_s),
+                )
+                .center(Length::Fill)
+                .into()
+            }
+        }
+    }
+
+    /// Handles interactions for this screen
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::ResultsLoaded(res) => {
+                match res {
+                    Ok(results) => self.state = State::Ready { results },
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
+                    }
+                }
+                Action::None
+            }
+            Message::OpenResult(result) => Action::OpenFlashcard {
+                studyset_id: result.studyset_id,
+                folder_id: result.folder_id,
+                flashcard_id: result.flashcard_id,
+            },
+        }
+    }
+
+    /// Subscriptions of this screen
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+}
+
+//
+// VIEWS
+//
+
+/// View of the header of this screen
+fn header_view<'a>(spacing: Spacing, query: &str, results_count: usize) -> Element<'a, Message> {
+    cosmic::widget::row::with_capacity(1)
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_s)
+        .padding([spacing.space_none, spacing.space_xxs])
+        .push(
+            text::title3(fl!(
+                "search-results",
+                query = query.to_string(),
+                count = results_count
+            ))
+            .width(Length::Fill),
+        )
+        .into()
+}
+
+/// View of the results of the search, grouped by study set and folder
+fn results_view<'a>(spacing: &Spacing, results: &'a [SearchResult]) -> Element<'a, Message> {
+    let content: Element<'a, Message> = if results.is_empty() {
+        text(fl!("empty-search-page")).into()
+    } else {
+        let mut groups = cosmic::widget::column::with_capacity(results.len())
+            .spacing(spacing.space_xs)
+            .width(Length::Fill);
+
+        // Results come ordered by study set and folder, so a new group starts every time one of them changes
+        for (index, result) in results.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|i| &results[i]);
+
+            if previous.is_none_or(|p| p.studyset_id != result.studyset_id) {
+                groups = groups.push(text::title4(result.studyset_name.as_str()));
+            }
+
+            if previous.is_none_or(|p| p.folder_id != result.folder_id) {
+                let mut folder_list = list::list_column().style(theme::Container::Card);
+                for result in results[index..]
+                    .iter()
+                    .take_while(|r| r.folder_id == result.folder_id)
+                {
+                    folder_list = folder_list.add(result_view(spacing, result));
+                }
+
+                groups = groups.push(
+                    column![text::heading(result.folder_name.as_str()), folder_list]
+                        .spacing(spacing.space_xxs),
+                );
+            }
+        }
+
+        groups.into()
+    };
+
+    scrollable(
+        container(content)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Top)
+            .width(Length::Fill),
+    )
+    .into()
+}
+
+/// View of a single [`SearchResult`]
+fn result_view<'a>(spacing: &Spacing, result: &'a SearchResult) -> Element<'a, Message> {
+    let side_text = |field: &FlashcardField| match field {
+        FlashcardField::Unsupported(_) => fl!("unsupported-field"),
+        field => field.plain_text().to_string(),
+    };
+
+    row![
+        button::icon(icons::get_handle("edit-symbolic", 18))
+            .class(theme::Button::Standard)
+            .width(Length::Shrink)
+            .on_press(Message::OpenResult(result.clone())),
+        column![
+            text(side_text(&result.front)).wrapping(Wrapping::WordOrGlyph),
+            text::caption(side_text(&result.back)).wrapping(Wrapping::WordOrGlyph)
+        ]
+        .width(Length::Fill)
+    ]
+    .width(Length::Fill)
+    .align_y(Alignment::Center)
+    .spacing(spacing.space_s)
+    .into()
+}
@@ -7,20 +7,30 @@ use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::keyboard::key::Named;
 use cosmic::iced::keyboard::{self, Key};
 use cosmic::iced::{self, Color, ContentFit, Font, Length, Subscription, event, window};
-use cosmic::iced_core::text::Wrapping;
-use cosmic::iced_widget::{column, row, stack};
-use cosmic::widget::{button, container, image, mouse_area, text, tooltip};
+use cosmic::iced_core::text::{Span, Wrapping};
+use cosmic::iced_widget::{column, rich_text, row, span, stack};
+use cosmic::widget::{button, container, dropdown, image, mouse_area, scrollable, text, tooltip};
 use cosmic::{Element, Task, theme};
 use sqlx::{Pool, Sqlite};
 
-use crate::app::core::models::flashcard::{Flashcard, FlashcardField, FlashcardStatus};
+use crate::app::core::models::flashcard::{Flashcard, FlashcardField, FlashcardStatus, LEECH_TAG};
+use crate::app::core::models::folder::Folder;
+use crate::app::core::models::revlog::RevlogEntry;
+use crate::app::core::utils::cloze::{self, ClozePart};
 use crate::app::core::utils::fsrs_scheduler::FSRSScheduler;
-use crate::app::core::utils::{self, OboeteToast};
+use crate::app::core::utils::{self, Clock, OboeteToast, math};
+use crate::app::widgets::code::code;
+use crate::app::widgets::markdown::markdown;
 use crate::{fl, icons};
 
 /// Screen [`State`] holder
 pub struct StudyScreen {
     current_folder_id: i32,
+    /// Only study the flashcards with this tag
+    tag_filter: Option<String>,
+    clock: Arc<dyn Clock>,
+    /// Play the audio of a side as soon as it's shown
+    autoplay_audio: bool,
     state: State,
 }
 
@@ -34,6 +44,19 @@ enum State {
         current_index: usize, // Track position in due cards
         current_mode: PracticeMode,
         window_width: f32,
+        /// Options of the tag filter, the first one means no filter
+        tag_options: Vec<String>,
+        /// Leech detection options of the studying folder
+        leech_threshold: u32,
+        leech_suspend: bool,
+        /// Ratings given on this session that can be undone, the last one goes first
+        undo_history: Vec<UndoStep>,
+        /// Flashcard whose rating (or undo) is being saved, nothing else is rated or undone until it's finished
+        saving: Option<i32>,
+        /// Rating being saved, it's only added to the undo history once it's saved
+        pending_undo: Option<UndoStep>,
+        /// Every flashcard of the session has been studied, the last rating can still be undone
+        finished: bool,
     },
 }
 
@@ -49,6 +72,14 @@ struct StudyingFlashcard {
     flashcard_side: FlashcardSide,
 }
 
+/// A rating given on the current session
+struct UndoStep {
+    /// The rated flashcard as it was before the rating
+    flashcard: Flashcard,
+    /// Position of the rated flashcard on the session
+    index: usize,
+}
+
 /// Which flashcard side are we looking at?
 #[derive(Default)]
 enum FlashcardSide {
@@ -59,10 +90,14 @@ enum FlashcardSide {
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    /// Does nothing
+    None,
     /// Asks to go back a screen
     Back,
     /// Show the user a toast
     AddToast(OboeteToast),
+    /// Opens the given url on the browser
+    LaunchUrl(String),
     /// Hotkey (Subscription) pressed
     Hotkey(Hotkey),
     /// Callback when the app window is resized
@@ -71,14 +106,28 @@ pub enum Message {
     /// Load the flashcards into state
     LoadFlashcards,
     /// Callback after asking to load the flashcards into state
-    FlashcardsLoaded(Result<(Vec<Flashcard>, f32), anywho::Error>),
+    FlashcardsLoaded(Result<(Vec<Flashcard>, Folder), anywho::Error>),
+    /// Callback after selecting a tag (by it's index on the options) to filter the flashcards
+    TagFilterChanged(usize),
 
     /// Ask to swap the currently studying flashcard side
     SwapFlashcardSide,
+    /// Ask to play the audio of the currently visible side (if any)
+    PlayAudio,
     /// Update the currently styuding flashcard status
     UpdateFlashcardStatus(i32, FlashcardStatus),
-    /// Callback after updating the currently studying flashcard, select a new card...
-    FlashcardStatusUpdated,
+    /// Callback after updating the given flashcard, select a new card...
+    FlashcardStatusUpdated(i32, Result<(), anywho::Error>),
+    /// Hide the given flashcard until tomorrow
+    BuryFlashcard(i32),
+    /// Hide the given flashcard until it's unsuspended on the flashcards list
+    SuspendFlashcard(i32),
+    /// Callback after burying or suspending the currently studying flashcard, it's skipped
+    FlashcardSkipped,
+    /// Undo the last rating of this session, the rated flashcard is studied again
+    Undo,
+    /// Callback after undoing the last rating of the given flashcard
+    Undone(i32, Result<(), anywho::Error>),
 }
 
 /// Allows us to talk with the parent screen
@@ -91,14 +140,23 @@ pub enum Action {
 
 impl StudyScreen {
     /// Init the screen
-    pub fn new(database: &Arc<Pool<Sqlite>>, folder_id: i32) -> (Self, Task<Message>) {
+    pub fn new(
+        database: &Arc<Pool<Sqlite>>,
+        folder_id: i32,
+        tag_filter: Option<String>,
+        clock: Arc<dyn Clock>,
+        autoplay_audio: bool,
+    ) -> (Self, Task<Message>) {
         (
             Self {
                 current_folder_id: folder_id,
+                tag_filter,
+                clock,
+                autoplay_audio,
                 state: State::Loading,
             },
             Task::perform(
-                Flashcard::get_all_with_retention_rate(Arc::clone(database), folder_id),
+                Flashcard::get_all_with_folder(Arc::clone(database), folder_id),
                 Message::FlashcardsLoaded,
             ),
         )
@@ -114,9 +172,36 @@ impl StudyScreen {
                 current_index,
                 current_mode,
                 window_width,
+                tag_options,
+                undo_history,
+                saving,
+                finished,
                 ..
             } => {
                 let spacing = theme::active().cosmic().spacing;
+                let can_undo = !undo_history.is_empty() && saving.is_none();
+
+                if *finished {
+                    return finished_view(spacing, can_undo);
+                }
+
+                // The first option means no filter, so there's nothing to filter if it's the only one
+                let tag_filter = (tag_options.len() > 1).then(|| {
+                    let selected = self
+                        .tag_filter
+                        .as_ref()
+                        .and_then(|tag| {
+                            tag_options.iter().position(|t| t.eq_ignore_ascii_case(tag))
+                        })
+                        .unwrap_or(0);
+                    container(dropdown(
+                        tag_options,
+                        Some(selected),
+                        Message::TagFilterChanged,
+                    ))
+                    .align_x(Horizontal::Right)
+                    .width(Length::Fill)
+                });
 
                 let content = study_view(
                     studying_flashcard,
@@ -124,11 +209,15 @@ impl StudyScreen {
                     flashcards,
                     current_index,
                     window_width,
+                    can_undo,
                 );
                 let buttons = study_buttons_view(spacing, studying_flashcard);
 
                 container(
-                    column![content, buttons]
+                    cosmic::widget::column::with_capacity(3)
+                        .push_maybe(tag_filter)
+                        .push(content)
+                        .push(buttons)
                         .width(Length::Fill)
                         .height(Length::Fill)
                         .spacing(spacing.space_s),
@@ -150,8 +239,27 @@ impl StudyScreen {
 
                 Action::Back(self.current_folder_id)
             }
+            Message::None => Action::None,
             Message::AddToast(toast) => Action::AddToast(toast),
+            Message::LaunchUrl(url) => {
+                match open::that_detached(&url) {
+                    Ok(()) => {}
+                    Err(err) => {
+                        eprintln!("failed to open {url:?}: {err}");
+                    }
+                }
+                Action::None
+            }
             Message::Hotkey(hotkey) => {
+                // Once the session is finished the last rating can still be undone
+                if let State::Ready { finished: true, .. } = &self.state {
+                    return match hotkey {
+                        Hotkey::Undo => self.update(Message::Undo, database),
+                        Hotkey::Esc => self.update(Message::Back, database),
+                        _ => Action::None,
+                    };
+                }
+
                 if let State::Ready {
                     studying_flashcard, ..
                 } = &mut self.state
@@ -178,6 +286,11 @@ impl StudyScreen {
                                 database,
                             ),
                             Hotkey::Space => self.update(Message::SwapFlashcardSide, database),
+                            Hotkey::R => self.update(Message::PlayAudio, database),
+                            Hotkey::B => {
+                                self.update(Message::BuryFlashcard(flashcard_id), database)
+                            }
+                            Hotkey::Undo => self.update(Message::Undo, database),
                             Hotkey::Esc => self.update(Message::Back, database),
                         };
                     }
@@ -195,26 +308,55 @@ impl StudyScreen {
                 self.state = State::Loading;
 
                 Action::Run(Task::perform(
-                    Flashcard::get_all_with_retention_rate(
-                        Arc::clone(database),
-                        self.current_folder_id,
-                    ),
+                    Flashcard::get_all_with_folder(Arc::clone(database), self.current_folder_id),
                     Message::FlashcardsLoaded,
                 ))
             }
             Message::FlashcardsLoaded(res) => {
+                let mut scheduler_error = None;
+
                 match res {
-                    Ok((mut flashcards, retention_rate)) => {
+                    Ok((mut flashcards, folder)) => {
+                        let mut tag_options: Vec<String> = Vec::new();
+                        for tag in flashcards.iter().flat_map(|f| &f.tags) {
+                            if !tag_options.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                                tag_options.push(tag.clone());
+                            }
+                        }
+                        tag_options.sort_by_key(|t| t.to_lowercase());
+                        tag_options.insert(0, fl!("all-tags"));
+
+                        if let Some(tag) = &self.tag_filter {
+                            flashcards.retain(|f| f.has_tag(tag));
+                        }
+
                         if !flashcards.is_empty() {
                             if let Some((due_cards, current_mode)) =
-                                order_due_cards(&mut flashcards)
+                                order_due_cards(&mut flashcards, self.clock.as_ref())
                             {
                                 let flashcard = due_cards.first().unwrap().to_owned();
 
+                                // Invalid saved parameters must not stop the user from studying, the default ones are used instead
+                                let scheduler = match FSRSScheduler::new(&folder) {
+                                    Ok(scheduler) => scheduler,
+                                    Err(e) => {
+                                        eprintln!("{}", e);
+                                        scheduler_error = Some(e);
+                                        match FSRSScheduler::new(&Folder {
+                                            fsrs_parameters: None,
+                                            ..folder.clone()
+                                        }) {
+                                            Ok(scheduler) => scheduler,
+                                            Err(e) => {
+                                                eprintln!("{}", e);
+                                                return Action::AddToast(OboeteToast::new(e));
+                                            }
+                                        }
+                                    }
+                                };
+
                                 self.state = State::Ready {
-                                    scheduler: Box::from(
-                                        FSRSScheduler::new(retention_rate).unwrap(),
-                                    ),
+                                    scheduler: Box::from(scheduler),
                                     flashcards: due_cards,
                                     studying_flashcard: StudyingFlashcard {
                                         flashcard,
@@ -223,6 +365,13 @@ impl StudyScreen {
                                     current_index: 0,
                                     current_mode,
                                     window_width: 1200.,
+                                    tag_options,
+                                    leech_threshold: folder.leech_threshold,
+                                    leech_suspend: folder.leech_suspend,
+                                    undo_history: Vec::new(),
+                                    pending_undo: None,
+                                    saving: None,
+                                    finished: false,
                                 };
                             } else {
                                 return self.update(Message::Back, &Arc::clone(database));
@@ -236,7 +385,35 @@ impl StudyScreen {
                         return Action::AddToast(OboeteToast::new(e));
                     }
                 }
-                Action::None
+
+                let State::Ready { flashcards, .. } = &self.state else {
+                    return Action::None;
+                };
+                let mut render_task =
+                    render_math(flashcards.iter().flat_map(|f| [&f.front, &f.back]));
+                if let Some(e) = scheduler_error {
+                    render_task = Task::batch([
+                        render_task,
+                        Task::done(Message::AddToast(OboeteToast::new(e))),
+                    ]);
+                }
+
+                match self.autoplay() {
+                    Action::Run(task) => Action::Run(Task::batch([render_task, task])),
+                    _ => Action::Run(render_task),
+                }
+            }
+            Message::TagFilterChanged(index) => {
+                let State::Ready { tag_options, .. } = &self.state else {
+                    return Action::None;
+                };
+
+                // The first option means no filter
+                self.tag_filter = match index {
+                    0 => None,
+                    _ => tag_options.get(index).cloned(),
+                };
+                self.update(Message::LoadFlashcards, database)
             }
             Message::SwapFlashcardSide => {
                 let State::Ready {
@@ -251,22 +428,43 @@ impl StudyScreen {
                     FlashcardSide::Back => studying_flashcard.flashcard_side = FlashcardSide::Front,
                 }
 
-                Action::None
+                self.autoplay()
             }
+            Message::PlayAudio => self.play_audio(),
             Message::UpdateFlashcardStatus(flashcard_id, flashcard_status) => {
+                if self.is_busy() {
+                    return Action::None;
+                }
+
                 let State::Ready {
                     studying_flashcard,
                     scheduler,
+                    current_index,
+                    leech_threshold,
+                    leech_suspend,
+                    saving,
+                    pending_undo,
                     ..
-                } = &self.state
+                } = &mut self.state
                 else {
                     return Action::None;
                 };
 
-                let (new_memory_state, new_due_date) = match utils::update_fsrs_data(
+                *pending_undo = Some(UndoStep {
+                    flashcard: studying_flashcard.flashcard.clone(),
+                    index: *current_index,
+                });
+
+                // Forgetting a flashcard that was already learned (in review) is a lapse
+                let lapsed = flashcard_status == FlashcardStatus::Bad
+                    && studying_flashcard.flashcard.fsrs_state.is_some()
+                    && studying_flashcard.flashcard.learning_step.is_none();
+
+                let scheduled = match utils::update_fsrs_data(
                     &flashcard_status,
                     &studying_flashcard.flashcard,
                     scheduler,
+                    self.clock.as_ref(),
                 ) {
                     Some(data) => data,
                     None => {
@@ -275,16 +473,119 @@ impl StudyScreen {
                     }
                 };
 
+                let review = RevlogEntry {
+                    id: None,
+                    flashcard_id,
+                    reviewed_at: self.clock.now(),
+                    rating: flashcard_status,
+                    elapsed_days: scheduled.elapsed_days,
+                    previous_fsrs_state: studying_flashcard.flashcard.fsrs_state.clone(),
+                    new_fsrs_state: scheduled.memory_state.into(),
+                    interval: scheduled.interval,
+                };
+
+                // Keep the studying flashcard up to date, it may come back later in this session
+                let flashcard = &mut studying_flashcard.flashcard;
+                flashcard.status = flashcard_status;
+                flashcard.fsrs_state = Some(review.new_fsrs_state.clone());
+                flashcard.due_date = Some(scheduled.due_date);
+                flashcard.last_reviewed = Some(review.reviewed_at);
+                flashcard.learning_step = scheduled.learning_step;
+                flashcard.relearning = scheduled.relearning;
+
+                *saving = Some(flashcard_id);
+                let update_task = Task::perform(
+                    Flashcard::update_status(Arc::clone(database), review, scheduled, lapsed),
+                    move |res| Message::FlashcardStatusUpdated(flashcard_id, res),
+                );
+
+                if !lapsed {
+                    return Action::Run(update_task);
+                }
+                flashcard.lapses += 1;
+
+                // Flashcards that reach the leech threshold are tagged (and maybe suspended) only once
+                if *leech_threshold == 0
+                    || flashcard.lapses < *leech_threshold
+                    || flashcard.is_leech()
+                {
+                    return Action::Run(update_task);
+                }
+
+                flashcard.tags.push(String::from(LEECH_TAG));
+                flashcard.suspended = *leech_suspend;
+                let leech_message = if *leech_suspend {
+                    fl!("leech-suspended", lapses = flashcard.lapses)
+                } else {
+                    fl!("leech-detected", lapses = flashcard.lapses)
+                };
+
+                Action::Run(
+                    Task::perform(
+                        Flashcard::mark_leech(Arc::clone(database), flashcard_id, *leech_suspend),
+                        move |res| match res {
+                            Ok(_) => Message::AddToast(OboeteToast::new(leech_message)),
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                Message::AddToast(OboeteToast::new(e))
+                            }
+                        },
+                    )
+                    .chain(update_task),
+                )
+            }
+            Message::FlashcardStatusUpdated(flashcard_id, res) => {
+                let State::Ready {
+                    flashcards,
+                    studying_flashcard,
+                    undo_history,
+                    saving,
+                    pending_undo,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                // A rating that is no longer the one being saved can't move the session forward
+                if *saving != Some(flashcard_id) {
+                    return Action::None;
+                }
+                *saving = None;
+
+                let Some(step) = pending_undo.take() else {
+                    return Action::None;
+                };
+
+                // The rating wasn't saved, the flashcard goes back to how it was so it can be rated again
+                if let Err(e) = res {
+                    eprintln!("{}", e);
+                    studying_flashcard.flashcard = step.flashcard;
+                    return Action::AddToast(OboeteToast::new(e));
+                }
+                undo_history.push(step);
+
+                // Flashcards still on a short (re)learning step come back later in this session (unless suspended as leeches)
+                if let (Some(_), Some(due_date)) = (
+                    studying_flashcard.flashcard.learning_step,
+                    studying_flashcard.flashcard.due_date,
+                ) && !studying_flashcard.flashcard.suspended
+                    && self.clock.day_of(due_date) <= self.clock.today()
+                {
+                    flashcards.push(studying_flashcard.flashcard.clone());
+                }
+
+                self.next_flashcard()
+            }
+            Message::BuryFlashcard(flashcard_id) => {
+                if self.is_busy() {
+                    return Action::None;
+                }
+
                 Action::Run(Task::perform(
-                    Flashcard::update_status(
-                        Arc::clone(database),
-                        flashcard_status,
-                        flashcard_id,
-                        new_memory_state.into(),
-                        new_due_date,
-                    ),
+                    Flashcard::bury(Arc::clone(database), flashcard_id, self.clock.today() + 1),
                     |res| match res {
-                        Ok(_) => Message::FlashcardStatusUpdated,
+                        Ok(_) => Message::FlashcardSkipped,
                         Err(e) => {
                             eprintln!("{}", e);
                             Message::AddToast(OboeteToast::new(e))
@@ -292,39 +593,202 @@ impl StudyScreen {
                     },
                 ))
             }
-            Message::FlashcardStatusUpdated => {
+            Message::SuspendFlashcard(flashcard_id) => {
+                if self.is_busy() {
+                    return Action::None;
+                }
+
+                Action::Run(Task::perform(
+                    Flashcard::set_suspended(Arc::clone(database), flashcard_id, true),
+                    |res| match res {
+                        Ok(_) => Message::FlashcardSkipped,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            Message::AddToast(OboeteToast::new(e))
+                        }
+                    },
+                ))
+            }
+            Message::FlashcardSkipped => {
                 let State::Ready {
                     flashcards,
                     current_index,
-                    studying_flashcard,
+                    undo_history,
                     ..
                 } = &mut self.state
                 else {
                     return Action::None;
                 };
 
-                // Move to next card
-                let next_index = *current_index + 1;
+                // Skipping changes the order of the session, the previous ratings can't be undone anymore
+                undo_history.clear();
+
+                // The skipped flashcard may be on the session more than once (learning steps)
+                let skipped_id = flashcards.get(*current_index).and_then(|f| f.id);
+                let (studied, pending) = flashcards.split_at(*current_index + 1);
+                let pending: Vec<Flashcard> = pending
+                    .iter()
+                    .filter(|f| f.id != skipped_id)
+                    .cloned()
+                    .collect();
+                *flashcards = studied.iter().cloned().chain(pending).collect();
 
-                if next_index >= flashcards.len() {
-                    // No more cards to study, go back
-                    return self.update(Message::Back, database);
+                self.next_flashcard()
+            }
+            Message::Undo => {
+                let State::Ready {
+                    flashcards,
+                    current_index,
+                    studying_flashcard,
+                    undo_history,
+                    saving,
+                    finished,
+                    ..
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                // Undoing while the rating is still being saved would race with it
+                if saving.is_some() {
+                    return Action::None;
                 }
 
-                // Update to next card
-                *current_index = next_index;
-                let next_flashcard = flashcards[next_index].clone();
+                let Some(step) = undo_history.pop() else {
+                    return Action::None;
+                };
+                let flashcard_id = step.flashcard.id.unwrap_or_default();
+                *saving = Some(flashcard_id);
+                *finished = false;
+
+                // The rated flashcard may have been queued again (learning steps)
+                if let Some(position) = flashcards
+                    .iter()
+                    .skip(step.index + 1)
+                    .rposition(|f| f.id == step.flashcard.id)
+                {
+                    flashcards.remove(step.index + 1 + position);
+                }
 
+                if let Some(flashcard) = flashcards.get_mut(step.index) {
+                    *flashcard = step.flashcard.clone();
+                }
+                *current_index = step.index;
                 *studying_flashcard = StudyingFlashcard {
-                    flashcard: next_flashcard,
+                    flashcard: step.flashcard.clone(),
                     flashcard_side: FlashcardSide::default(),
                 };
 
-                Action::None
+                Action::Run(Task::perform(
+                    Flashcard::undo_review(Arc::clone(database), step.flashcard),
+                    move |res| Message::Undone(flashcard_id, res),
+                ))
+            }
+            Message::Undone(flashcard_id, res) => {
+                let State::Ready { saving, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                if *saving != Some(flashcard_id) {
+                    return Action::None;
+                }
+                *saving = None;
+
+                match res {
+                    Ok(_) => self.autoplay(),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        Action::AddToast(OboeteToast::new(e))
+                    }
+                }
             }
         }
     }
 
+    /// Returns true if a rating is being saved or the session is finished, the flashcard can't be rated or skipped then
+    fn is_busy(&self) -> bool {
+        matches!(
+            &self.state,
+            State::Ready { saving, finished, .. } if saving.is_some() || *finished
+        )
+    }
+
+    /// Moves to the next flashcard of the session, the session is finished if there are no more
+    fn next_flashcard(&mut self) -> Action {
+        let State::Ready {
+            flashcards,
+            current_index,
+            studying_flashcard,
+            finished,
+            ..
+        } = &mut self.state
+        else {
+            return Action::None;
+        };
+
+        let next_index = *current_index + 1;
+
+        if next_index >= flashcards.len() {
+            // No more cards to study, the last rating can still be undone from the finished view
+            *finished = true;
+            return Action::None;
+        }
+
+        // Update to next card
+        *current_index = next_index;
+        let next_flashcard = flashcards[next_index].clone();
+
+        *studying_flashcard = StudyingFlashcard {
+            flashcard: next_flashcard,
+            flashcard_side: FlashcardSide::default(),
+        };
+
+        self.autoplay()
+    }
+
+    /// Plays the audio of the visible side of the studying [`Flashcard`] (if any)
+    fn play_audio(&self) -> Action {
+        let State::Ready {
+            studying_flashcard, ..
+        } = &self.state
+        else {
+            return Action::None;
+        };
+
+        let field = match studying_flashcard.flashcard_side {
+            FlashcardSide::Front => &studying_flashcard.flashcard.front,
+            FlashcardSide::Back => &studying_flashcard.flashcard.back,
+        };
+
+        // Sides made of blocks play their first audio
+        let Some(path) = field.blocks().iter().find_map(|block| match block {
+            FlashcardField::Audio { path, .. } => Some(path),
+            _ => None,
+        }) else {
+            return Action::None;
+        };
+
+        Action::Run(Task::perform(
+            utils::play_audio(path.clone()),
+            |res| match res {
+                Ok(_) => Message::None,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    Message::AddToast(OboeteToast::new(e))
+                }
+            },
+        ))
+    }
+
+    /// Plays the audio of the visible side if autoplay is enabled
+    fn autoplay(&self) -> Action {
+        if self.autoplay_audio {
+            self.play_audio()
+        } else {
+            Action::None
+        }
+    }
+
     /// Subscriptions of this screen
     pub fn subscription(&self) -> Subscription<Message> {
         Subscription::batch([
@@ -341,17 +805,16 @@ fn study_view<'a>(
     flashcards: &'a [Flashcard],
     current_index: &usize,
     window_width: &'a f32,
+    can_undo: bool,
 ) -> Element<'a, Message> {
     // calculate text size based on window width
     let text_size = (window_width / 15.0).clamp(30.0, 75.0);
 
     let flashcard_content: Element<Message> = match studying_flashcard.flashcard_side {
         FlashcardSide::Front => match &studying_flashcard.flashcard.front {
-            FlashcardField::Text(t) => {
-                container(text(t).size(text_size).wrapping(Wrapping::WordOrGlyph))
-                    .center(Length::Fill)
-                    .into()
-            }
+            FlashcardField::Text(t) => container(markdown(t, text_size, Message::LaunchUrl))
+                .center(Length::Fill)
+                .into(),
             FlashcardField::Image { path, alt_text } => container(
                 container(tooltip(
                     container(image(path).content_fit(ContentFit::Contain)).padding(15),
@@ -365,26 +828,88 @@ fn study_view<'a>(
             )
             .center(Length::Fill)
             .into(),
-        },
-        FlashcardSide::Back => match &studying_flashcard.flashcard.back {
-            FlashcardField::Text(t) => {
-                container(text(t).size(text_size).wrapping(Wrapping::WordOrGlyph))
+            FlashcardField::Audio { transcript, .. } => {
+                container(audio_view(transcript, text_size))
                     .center(Length::Fill)
                     .into()
             }
-            FlashcardField::Image { path, alt_text } => container(
-                container(tooltip(
-                    container(image(path).content_fit(ContentFit::Contain)).padding(15),
-                    container(text(alt_text).size(15).wrapping(Wrapping::WordOrGlyph))
-                        .center(Length::Shrink)
-                        .padding(5),
-                    tooltip::Position::FollowCursor,
-                ))
+            FlashcardField::Cloze { text: t, index } => {
+                container(cloze_view(t, *index, false, text_size))
+                    .center(Length::Fill)
+                    .into()
+            }
+            FlashcardField::Code { language, source } => {
+                container(code(language, source, text_size / 2.0))
+                    .center(Length::Fill)
+                    .padding(20)
+                    .into()
+            }
+            FlashcardField::Blocks(blocks) => container(blocks_view(blocks, text_size))
                 .center(Length::Fill)
-                .padding(20),
-            )
-            .center(Length::Fill)
-            .into(),
+                .padding(20)
+                .into(),
+            FlashcardField::Unsupported(_) => container(text(fl!("unsupported-field")))
+                .center(Length::Fill)
+                .into(),
+        },
+        // The back of a cloze reveals the whole text, followed by the back extra
+        FlashcardSide::Back => match (
+            &studying_flashcard.flashcard.front,
+            &studying_flashcard.flashcard.back,
+        ) {
+            (FlashcardField::Cloze { text: t, index }, back) => {
+                let extra = back.plain_text();
+                container(
+                    column![cloze_view(t, *index, true, text_size)]
+                        .push_maybe((!extra.is_empty()).then(|| {
+                            text(extra.into_owned())
+                                .size(text_size / 2.0)
+                                .wrapping(Wrapping::WordOrGlyph)
+                        }))
+                        .align_x(Horizontal::Center)
+                        .spacing(20),
+                )
+                .center(Length::Fill)
+                .into()
+            }
+            (_, back) => match back {
+                FlashcardField::Text(t) => container(markdown(t, text_size, Message::LaunchUrl))
+                    .center(Length::Fill)
+                    .into(),
+                FlashcardField::Image { path, alt_text } => container(
+                    container(tooltip(
+                        container(image(path).content_fit(ContentFit::Contain)).padding(15),
+                        container(text(alt_text).size(15).wrapping(Wrapping::WordOrGlyph))
+                            .center(Length::Shrink)
+                            .padding(5),
+                        tooltip::Position::FollowCursor,
+                    ))
+                    .center(Length::Fill)
+                    .padding(20),
+                )
+                .center(Length::Fill)
+                .into(),
+                FlashcardField::Audio { transcript, .. } => {
+                    container(audio_view(transcript, text_size))
+                        .center(Length::Fill)
+                        .into()
+                }
+                FlashcardField::Code { language, source } => {
+                    container(code(language, source, text_size / 2.0))
+                        .center(Length::Fill)
+                        .padding(20)
+                        .into()
+                }
+                FlashcardField::Blocks(blocks) => container(blocks_view(blocks, text_size))
+                    .center(Length::Fill)
+                    .padding(20)
+                    .into(),
+                FlashcardField::Cloze { .. } | FlashcardField::Unsupported(_) => {
+                    container(text(fl!("unsupported-field")))
+                        .center(Length::Fill)
+                        .into()
+                }
+            },
         },
     };
 
@@ -448,11 +973,108 @@ fn study_view<'a>(
         .align_x(Horizontal::Left)
         .align_y(Vertical::Top)
         .width(Length::Fill)
+        .height(Length::Fill),
+        container(
+            row![
+                button::text(format!("{} [B]", fl!("bury-until-tomorrow")))
+                    .class(theme::Button::Text)
+                    .on_press(Message::BuryFlashcard(
+                        studying_flashcard.flashcard.id.unwrap_or_default()
+                    )),
+                button::text(fl!("suspend"))
+                    .class(theme::Button::Text)
+                    .on_press(Message::SuspendFlashcard(
+                        studying_flashcard.flashcard.id.unwrap_or_default()
+                    )),
+            ]
+            .push_maybe(can_undo.then(|| {
+                button::text(format!("{} [Ctrl+Z]", fl!("undo")))
+                    .class(theme::Button::Text)
+                    .on_press(Message::Undo)
+            }))
+            .spacing(5)
+        )
+        .padding(10)
+        .align_x(Horizontal::Right)
+        .align_y(Vertical::Top)
+        .width(Length::Fill)
         .height(Length::Fill)
     ])
     .into()
 }
 
+/// View shown once every flashcard of the session has been studied
+fn finished_view<'a>(spacing: Spacing, can_undo: bool) -> Element<'a, Message> {
+    container(
+        column![
+            text::title3(fl!("session-finished")),
+            row![]
+                .push_maybe(can_undo.then(|| {
+                    button::standard(format!("{} [Ctrl+Z]", fl!("undo"))).on_press(Message::Undo)
+                }))
+                .push(button::suggested(fl!("finish-session")).on_press(Message::Back))
+                .spacing(spacing.space_xs),
+        ]
+        .align_x(Horizontal::Center)
+        .spacing(spacing.space_s),
+    )
+    .center(Length::Fill)
+    .into()
+}
+
+/// View of an audio side, the audio can be replayed with the button (or the R key)
+fn audio_view<'a>(transcript: &'a str, text_size: f32) -> Element<'a, Message> {
+    column![
+        button::icon(icons::get_handle("media-playback-start-symbolic", 48))
+            .class(theme::Button::Suggested)
+            .on_press(Message::PlayAudio)
+            .padding(20),
+        text::caption(fl!("replay-audio-hint")),
+    ]
+    .push_maybe((!transcript.is_empty()).then(|| {
+        text(transcript)
+            .size(text_size)
+            .wrapping(Wrapping::WordOrGlyph)
+    }))
+    .align_x(Horizontal::Center)
+    .spacing(20)
+    .into()
+}
+
+/// View of a cloze text, the deletions of the given index are blanked (or revealed) and highlighted
+fn cloze_view<'a>(text: &str, index: u32, revealed: bool, text_size: f32) -> Element<'a, Message> {
+    let highlight = Color::from(theme::active().cosmic().accent_color());
+    let bold = Font {
+        weight: cosmic::iced::font::Weight::Bold,
+        ..Default::default()
+    };
+
+    let spans: Vec<Span<'a>> = cloze::parse(text)
+        .into_iter()
+        .map(|part| match part {
+            ClozePart::Deletion {
+                index: i,
+                answer,
+                hint,
+            } if i == index => {
+                let content = match (revealed, hint) {
+                    (true, _) => answer.to_string(),
+                    (false, Some(hint)) => format!("[{hint}]"),
+                    (false, None) => String::from("[...]"),
+                };
+                span(content).color(highlight).font(bold)
+            }
+            ClozePart::Deletion { answer, .. } => span(answer.to_string()),
+            ClozePart::Text(t) => span(t.to_string()),
+        })
+        .collect();
+
+    rich_text(spans)
+        .size(text_size)
+        .wrapping(Wrapping::WordOrGlyph)
+        .into()
+}
+
 /// View of the buttons of the study page
 fn study_buttons_view<'a>(
     spacing: Spacing,
@@ -528,23 +1150,33 @@ fn button_appearance(theme: &theme::Theme, status: FlashcardStatus) -> button::S
 }
 
 /// Orders the [`Flashcard`] to follow the FSRS algo if possible, if not offers ALL cards sorted by due date, also determines the page [`PracticeMode`]
-fn order_due_cards(flashcards: &mut [Flashcard]) -> Option<(Vec<Flashcard>, PracticeMode)> {
-    let current_day = utils::current_day();
+fn order_due_cards(
+    flashcards: &mut [Flashcard],
+    clock: &dyn Clock,
+) -> Option<(Vec<Flashcard>, PracticeMode)> {
+    let now = clock.now();
+
+    // Suspended and buried cards are not studied at all
+    let flashcards: Vec<Flashcard> = flashcards
+        .iter()
+        .filter(|card| !card.suspended && !card.is_buried(clock))
+        .cloned()
+        .collect();
 
     // Separate due and not-due cards
     let mut due_cards: Vec<Flashcard> = flashcards
         .iter()
-        .filter(|card| card.is_due())
+        .filter(|card| card.is_due(clock))
         .cloned()
         .collect();
 
     // Sort due cards by due date (study overdue cards first)
-    due_cards.sort_by_key(|card| card.due_date.unwrap_or(current_day));
+    due_cards.sort_by_key(|card| card.due_date.unwrap_or(now));
 
     // If no cards are due, offer ALL cards sorted by due date (earliest first)
     if due_cards.is_empty() && !flashcards.is_empty() {
         let mut all_cards = flashcards.to_vec();
-        all_cards.sort_by_key(|card| card.due_date.unwrap_or(current_day));
+        all_cards.sort_by_key(|card| card.due_date.unwrap_or(now));
         return Some((all_cards, PracticeMode::Study));
     }
 
@@ -566,20 +1198,30 @@ pub enum Hotkey {
     Three,
     Four,
     Space,
+    R,
+    B,
+    Undo,
     Esc,
 }
 
 fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
     match event {
-        event::Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => match key {
+        event::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => match key {
             Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
 
+            Key::Character(c) if modifiers.control() => match c.as_str() {
+                "z" | "Z" => Some(Message::Hotkey(Hotkey::Undo)),
+                _ => None,
+            },
+
             Key::Character(c) => match c.as_str() {
                 "1" => Some(Message::Hotkey(Hotkey::One)),
                 "2" => Some(Message::Hotkey(Hotkey::Two)),
                 "3" => Some(Message::Hotkey(Hotkey::Three)),
                 "4" => Some(Message::Hotkey(Hotkey::Four)),
                 " " => Some(Message::Hotkey(Hotkey::Space)),
+                "r" | "R" => Some(Message::Hotkey(Hotkey::R)),
+                "b" | "B" => Some(Message::Hotkey(Hotkey::B)),
                 _ => None,
             },
 
@@ -589,3 +1231,48 @@ fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> O
         _ => None,
     }
 }
+
+/// View of a side made of blocks, they are laid out vertically (the text is smaller so everything fits)
+fn blocks_view<'a>(blocks: &'a [FlashcardField], text_size: f32) -> Element<'a, Message> {
+    let text_size = text_size / 1.5;
+
+    scrollable(
+        column(blocks.iter().map(|block| {
+            match block {
+                FlashcardField::Text(t) => markdown(t, text_size, Message::LaunchUrl),
+                FlashcardField::Image { path, alt_text } => tooltip(
+                    container(image(path).content_fit(ContentFit::Contain)).max_height(400.),
+                    container(text(alt_text).size(15).wrapping(Wrapping::WordOrGlyph))
+                        .center(Length::Shrink)
+                        .padding(5),
+                    tooltip::Position::FollowCursor,
+                )
+                .into(),
+                FlashcardField::Audio { transcript, .. } => audio_view(transcript, text_size),
+                FlashcardField::Code { language, source } => {
+                    code(language, source, text_size / 2.0)
+                }
+                _ => text(fl!("unsupported-field")).into(),
+            }
+        }))
+        .align_x(Horizontal::Center)
+        .spacing(20),
+    )
+    .into()
+}
+
+// Helper function to render the math of the given fields, once it's done the screen gets redrawn showing it
+fn render_math<'a>(fields: impl Iterator<Item = &'a FlashcardField>) -> Task<Message> {
+    let formulas: Vec<(String, bool)> = fields.flat_map(|f| f.math_formulas()).collect();
+    if formulas.is_empty() {
+        return Task::none();
+    }
+
+    Task::perform(math::render_math(formulas), |res| match res {
+        Ok(_) => Message::None,
+        Err(e) => {
+            eprintln!("{}", e);
+            Message::AddToast(OboeteToast::new(e))
+        }
+    })
+}
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use cosmic::cosmic_theme::Spacing;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::{Alignment, Length, Subscription};
+use cosmic::iced_core::text::Wrapping;
+use cosmic::iced_widget::{column, row};
+use cosmic::widget::{button, container, list, scrollable, text};
+use cosmic::{Element, Task, theme};
+use sqlx::{Pool, Sqlite};
+
+use crate::app::core::models::trash::{TrashItem, TrashKind};
+use crate::app::core::utils::OboeteToast;
+use crate::{fl, icons};
+
+/// Screen [`State`] holder
+pub struct TrashScreen {
+    state: State,
+}
+
+/// The different states this screen can be in
+enum State {
+    Loading,
+    Ready { items: Vec<TrashItem> },
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Show the user a toast
+    AddToast(OboeteToast),
+    /// Load the items on the trash into state
+    LoadTrash,
+    /// Callback after asking to load the items on the trash into state
+    TrashLoaded(Result<Vec<TrashItem>, anywho::Error>),
+
+    /// Ask to take the given [`TrashItem`] out of the trash
+    Restore(TrashItem),
+    /// Ask to delete the given [`TrashItem`] for good
+    Purge(TrashItem),
+    /// Ask to delete everything on the trash for good
+    EmptyTrash,
+
+    /// Ask to go back to the folders of the current studyset
+    Back,
+}
+
+/// Allows us to talk with the parent screen
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    AddToast(OboeteToast),
+
+    OpenPurgeTrashItemDialog(TrashItem),
+    OpenEmptyTrashDialog,
+
+    Back,
+}
+
+impl TrashScreen {
+    /// Init the screen
+    pub fn new(database: &Arc<Pool<Sqlite>>) -> (Self, Task<Message>) {
+        (
+            Self {
+                state: State::Loading,
+            },
+            Task::perform(
+                TrashItem::get_all(Arc::clone(database)),
+                Message::TrashLoaded,
+            ),
+        )
+    }
+
+    /// View of the screen
+    pub fn view(&self) -> Element<'_, Message> {
+        match &self.state {
+            State::Loading => container(text(fl!("loading"))).center(Length::Fill).into(),
+            State::Ready { items } => {
+                let spacing = theme::active().cosmic().spacing;
+
+                let header = header_view(spacing, items);
+                let content = trash_view(&spacing, items);
+
+                container(
+                    column![header, content]
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .spacing(spacing.space_s),
+                )
+                .center(Length::Fill)
+                .into()
+            }
+        }
+    }
+
+    /// Handles interactions for this screen
+    pub fn update(&mut self, message: Message, database: &Arc<Pool<Sqlite>>) -> Action {
+        match message {
+            Message::AddToast(toast) => Action::AddToast(toast),
+            Message::LoadTrash => Action::Run(Task::perform(
+                TrashItem::get_all(Arc::clone(database)),
+                Message::TrashLoaded,
+            )),
+            Message::TrashLoaded(res) => {
+                match res {
+                    Ok(items) => self.state = State::Ready { items },
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Action::AddToast(OboeteToast::new(e));
+                    }
+                }
+                Action::None
+            }
+
+            Message::Restore(item) => Action::Run(Task::perform(
+                TrashItem::restore(Arc::clone(database), item),
+                |res| match res {
+                    Ok(_) => Message::LoadTrash,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        Message::AddToast(OboeteToast::new(e))
+                    }
+                },
+            )),
+            Message::Purge(item) => Action::OpenPurgeTrashItemDialog(item),
+            Message::EmptyTrash => Action::OpenEmptyTrashDialog,
+
+            Message::Back => Action::Back,
+        }
+    }
+
+    /// Subscriptions of this screen
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+}
+
+//
+// VIEWS
+//
+
+/// View of the header of this screen
+fn header_view<'a>(spacing: Spacing, items: &'a [TrashItem]) -> Element<'a, Message> {
+    let back_button = button::icon(icons::get_handle("go-previous-symbolic", 18))
+        .class(theme::Button::Icon)
+        .on_press(Message::Back);
+
+    let empty_trash_button = button::destructive(fl!("empty-trash"))
+        .on_press_maybe((!items.is_empty()).then_some(Message::EmptyTrash));
+
+    let title = if !items.is_empty() {
+        format!("{} ({})", fl!("trash"), items.len())
+    } else {
+        fl!("trash")
+    };
+
+    cosmic::widget::row::with_capacity(3)
+        .align_y(Alignment::Center)
+        .spacing(spacing.space_s)
+        .padding([spacing.space_none, spacing.space_xxs])
+        .push(back_button)
+        .push(text::title3(title).width(Length::Fill))
+        .push(empty_trash_button)
+        .into()
+}
+
+/// View of the contents of this screen
+fn trash_view<'a>(spacing: &Spacing, items: &'a [TrashItem]) -> Element<'a, Message> {
+    let content: Element<'a, Message> = if items.is_empty() {
+        text(fl!("empty-trash-page")).into()
+    } else {
+        let mut items_list = list::list_column().style(theme::Container::Card);
+
+        for item in items {
+            let icon = match item.kind {
+                TrashKind::StudySet => "x-office-document-symbolic",
+                TrashKind::Folder => "folder-symbolic",
+                TrashKind::Flashcard => "view-paged-symbolic",
+            };
+
+            let deleted_at = DateTime::from_timestamp(item.deleted_at, 0)
+                .map(|date| {
+                    date.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            let details = if item.location.is_empty() {
+                fl!("deleted-at", date = deleted_at)
+            } else {
+                format!(
+                    "{} · {}",
+                    item.location,
+                    fl!("deleted-at", date = deleted_at)
+                )
+            };
+
+            items_list = items_list.add(
+                row![
+                    icons::get_icon(icon, 18),
+                    column![
+                        text(item.name.clone()).wrapping(Wrapping::WordOrGlyph),
+                        text::caption(details)
+                    ]
+                    .width(Length::Fill),
+                    button::text(fl!("restore"))
+                        .class(theme::Button::Standard)
+                        .on_press(Message::Restore(item.clone())),
+                    button::icon(icons::get_handle("user-trash-full-symbolic", 18))
+                        .class(theme::Button::Destructive)
+                        .on_press(Message::Purge(item.clone()))
+                ]
+                .width(Length::Fill)
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_s),
+            );
+        }
+
+        items_list.into()
+    };
+
+    scrollable(
+        container(content)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Top)
+            .width(Length::Fill),
+    )
+    .into()
+}
@@ -1,3 +1,5 @@
 // SPDX-License-Identifier: GPL-3.0
 
+pub mod code;
+pub mod markdown;
 pub mod pill;
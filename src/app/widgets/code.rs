@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::LazyLock;
+
+use cosmic::{
+    Element,
+    iced::{Color, Font},
+    iced_core::text::{Span, Wrapping},
+    iced_widget::{rich_text, span},
+    theme,
+    widget::container,
+};
+use syntect::{
+    easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet, util::LinesWithEndings,
+};
+
+/// Languages that can be picked for a code field, all of them are highlighted
+pub const CODE_LANGUAGES: &[&str] = &[
+    "Plain Text",
+    "Bash",
+    "C",
+    "C++",
+    "C#",
+    "CSS",
+    "Go",
+    "Haskell",
+    "HTML",
+    "Java",
+    "JavaScript",
+    "JSON",
+    "Lua",
+    "Markdown",
+    "PHP",
+    "Python",
+    "Ruby",
+    "Rust",
+    "SQL",
+    "YAML",
+];
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Renders the given source code in a monospace font with syntax highlighting,
+/// the language can be a name or an extension (ej: "Rust" or "rs"), unknown languages are shown as plain text
+pub fn code<'a, Message: 'a>(language: &str, source: &str, text_size: f32) -> Element<'a, Message> {
+    let spacing = theme::active().cosmic().spacing;
+
+    container(
+        rich_text(highlight(language, source))
+            .font(Font::MONOSPACE)
+            .size(text_size)
+            .wrapping(Wrapping::WordOrGlyph),
+    )
+    .padding(spacing.space_xs)
+    .class(theme::Container::Card)
+    .into()
+}
+
+// Helper function to split the source into spans colored by the highlighting theme that matches the app theme
+fn highlight(language: &str, source: &str) -> Vec<Span<'static, String>> {
+    let syntax = SYNTAXES
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let highlighting_theme = if theme::active().cosmic().is_dark {
+        &THEMES.themes["base16-ocean.dark"]
+    } else {
+        &THEMES.themes["InspiredGitHub"]
+    };
+
+    let mut highlighter = HighlightLines::new(syntax, highlighting_theme);
+    let mut spans = Vec::new();
+
+    for line in LinesWithEndings::from(source) {
+        match highlighter.highlight_line(line, &SYNTAXES) {
+            Ok(ranges) => spans.extend(ranges.into_iter().map(|(style, text)| {
+                let color =
+                    Color::from_rgb8(style.foreground.r, style.foreground.g, style.foreground.b);
+                span(text.to_string()).color(color)
+            })),
+            // A line that can't be highlighted is kept as is
+            Err(_) => spans.push(span(line.to_string())),
+        }
+    }
+
+    spans
+}
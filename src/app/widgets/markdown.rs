@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::{
+    Element,
+    iced::{Alignment, Color, Font, Length, Padding, font},
+    iced_core::text::{Span, Wrapping},
+    iced_widget::{rich_text, row, span},
+    theme,
+    widget::{column, container, flex_row, svg, text},
+};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use crate::app::core::utils::math;
+use crate::app::widgets::code::code;
+
+/// A block of a markdown document
+struct Block {
+    kind: BlockKind,
+    /// Nesting level of the list the block belongs to (0 if none)
+    indent: usize,
+    /// List marker of the block (only for the first block of a list item)
+    marker: Option<String>,
+    inlines: Vec<Inline>,
+}
+
+enum BlockKind {
+    Paragraph,
+    Heading(HeadingLevel),
+    /// Code block, the language is the info string of fenced blocks (empty if none)
+    Code {
+        language: String,
+        source: String,
+    },
+    /// Display math (`$$...$$`)
+    Math(String),
+}
+
+/// Content of a paragraph or heading
+enum Inline {
+    Spans(Vec<Span<'static, String>>),
+    /// Inline math (`$...$`)
+    Math(String),
+}
+
+/// Renders the given markdown (bold, italics, lists, inline code, code blocks, headings, links and math) with COSMIC widgets,
+/// clicking a link calls `on_link` with it's url.
+/// Math formulas are shown as their source until they have been rendered with [`math::render_math`]
+pub fn markdown<'a, Message: Clone + 'a>(
+    content: &str,
+    text_size: f32,
+    on_link: impl Fn(String) -> Message + Clone + 'a,
+) -> Element<'a, Message> {
+    let accent = Color::from(theme::active().cosmic().accent_color());
+    let spacing = theme::active().cosmic().spacing;
+
+    let blocks = parse(content, accent);
+
+    column::with_children(
+        blocks
+            .into_iter()
+            .map(|block| {
+                let content: Element<'a, Message> = match block.kind {
+                    BlockKind::Code { language, source } => {
+                        code(&language, &source, text_size * 0.8)
+                    }
+                    BlockKind::Math(latex) => container(math_view(latex, true, text_size))
+                        .width(Length::Fill)
+                        .align_x(Alignment::Center)
+                        .into(),
+                    BlockKind::Heading(level) => {
+                        let scale = match level {
+                            HeadingLevel::H1 => 1.6,
+                            HeadingLevel::H2 => 1.4,
+                            HeadingLevel::H3 => 1.2,
+                            _ => 1.1,
+                        };
+                        inlines_view(
+                            block.inlines,
+                            text_size * scale,
+                            Font {
+                                weight: font::Weight::Bold,
+                                ..Default::default()
+                            },
+                            on_link.clone(),
+                        )
+                    }
+                    BlockKind::Paragraph => {
+                        inlines_view(block.inlines, text_size, Font::default(), on_link.clone())
+                    }
+                };
+
+                let marker = text(block.marker.unwrap_or_default())
+                    .size(text_size)
+                    .width(Length::Fixed(text_size * 1.5));
+
+                if block.indent == 0 {
+                    content
+                } else {
+                    row![marker, content]
+                        .padding(Padding {
+                            left: text_size * (block.indent - 1) as f32,
+                            ..Padding::ZERO
+                        })
+                        .align_y(Alignment::Start)
+                        .into()
+                }
+            })
+            .collect::<Vec<Element<'a, Message>>>(),
+    )
+    .spacing(spacing.space_xxs)
+    .into()
+}
+
+// Helper function to render the content of a paragraph or heading, text and inline math are laid out in a wrapping row
+fn inlines_view<'a, Message: Clone + 'a>(
+    mut inlines: Vec<Inline>,
+    text_size: f32,
+    font: Font,
+    on_link: impl Fn(String) -> Message + Clone + 'a,
+) -> Element<'a, Message> {
+    let spans_view = |spans: Vec<Span<'static, String>>| -> Element<'a, Message> {
+        rich_text(spans)
+            .size(text_size)
+            .font(font)
+            .wrapping(Wrapping::WordOrGlyph)
+            .on_link_click(on_link.clone())
+            .into()
+    };
+
+    // Most blocks have no math, those keep the text wrapping of a single rich text
+    if let [Inline::Spans(_)] = inlines.as_slice()
+        && let Some(Inline::Spans(spans)) = inlines.pop()
+    {
+        return spans_view(spans);
+    }
+
+    flex_row(
+        inlines
+            .into_iter()
+            .map(|inline| match inline {
+                Inline::Spans(spans) => spans_view(spans),
+                Inline::Math(latex) => math_view(latex, false, text_size),
+            })
+            .collect(),
+    )
+    .into()
+}
+
+// Helper function to show a math formula, if it has not been rendered yet it's source is shown instead
+fn math_view<'a, Message: 'a>(
+    latex: String,
+    display: bool,
+    text_size: f32,
+) -> Element<'a, Message> {
+    let Some(rendered) = math::cached_math(&latex, display) else {
+        let delimiter = if display { "$$" } else { "$" };
+        return text(format!("{delimiter}{latex}{delimiter}"))
+            .font(Font::MONOSPACE)
+            .size(text_size)
+            .into();
+    };
+
+    // The svg uses the current color for the glyphs, which needs to match the theme
+    let [r, g, b, _] = Color::from(theme::active().cosmic().on_bg_color()).into_rgba8();
+    let content = rendered
+        .svg
+        .replace("currentColor", &format!("#{r:02x}{g:02x}{b:02x}"));
+
+    // The size of the svg is relative to the font size
+    svg(svg::Handle::from_memory(content.into_bytes()))
+        .width(Length::Fixed(rendered.width * text_size))
+        .height(Length::Fixed(rendered.height * text_size))
+        .into()
+}
+
+// Helper function to parse the markdown into the blocks that get rendered
+fn parse(content: &str, accent: Color) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut inlines: Vec<Inline> = Vec::new();
+    let mut spans: Vec<Span<'static, String>> = Vec::new();
+
+    let mut strong = 0;
+    let mut emphasis = 0;
+    let mut link: Option<String> = None;
+    let mut heading: Option<HeadingLevel> = None;
+    let mut code_block: Option<String> = None;
+    let mut code_language = String::new();
+    // Next number of every nested list ([`None`] for bullet lists)
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut marker: Option<String> = None;
+
+    let flush = |blocks: &mut Vec<Block>,
+                 inlines: &mut Vec<Inline>,
+                 spans: &mut Vec<Span<'static, String>>,
+                 heading: &mut Option<HeadingLevel>,
+                 marker: &mut Option<String>,
+                 indent: usize| {
+        if !spans.is_empty() {
+            inlines.push(Inline::Spans(std::mem::take(spans)));
+        }
+        if inlines.is_empty() {
+            return;
+        }
+
+        blocks.push(Block {
+            kind: heading
+                .take()
+                .map(BlockKind::Heading)
+                .unwrap_or(BlockKind::Paragraph),
+            indent,
+            marker: marker.take(),
+            inlines: std::mem::take(inlines),
+        });
+    };
+
+    for event in Parser::new_ext(content, Options::ENABLE_MATH) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => heading = Some(level),
+            Event::Start(Tag::List(start)) => {
+                flush(
+                    &mut blocks,
+                    &mut inlines,
+                    &mut spans,
+                    &mut heading,
+                    &mut marker,
+                    lists.len(),
+                );
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                flush(
+                    &mut blocks,
+                    &mut inlines,
+                    &mut spans,
+                    &mut heading,
+                    &mut marker,
+                    lists.len(),
+                );
+                lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                flush(
+                    &mut blocks,
+                    &mut inlines,
+                    &mut spans,
+                    &mut heading,
+                    &mut marker,
+                    lists.len(),
+                );
+                marker = Some(match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => String::from("•"),
+                });
+            }
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item) => flush(
+                &mut blocks,
+                &mut inlines,
+                &mut spans,
+                &mut heading,
+                &mut marker,
+                lists.len(),
+            ),
+            Event::Start(Tag::Strong) => strong += 1,
+            Event::End(TagEnd::Strong) => strong -= 1,
+            Event::Start(Tag::Emphasis) => emphasis += 1,
+            Event::End(TagEnd::Emphasis) => emphasis -= 1,
+            Event::Start(Tag::Link { dest_url, .. }) => link = Some(dest_url.to_string()),
+            Event::End(TagEnd::Link) => link = None,
+            Event::Start(Tag::CodeBlock(kind)) => {
+                code_language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some(String::new());
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.push(Block {
+                kind: BlockKind::Code {
+                    language: std::mem::take(&mut code_language),
+                    source: code_block.take().unwrap_or_default().trim_end().to_string(),
+                },
+                indent: lists.len(),
+                marker: marker.take(),
+                inlines: Vec::new(),
+            }),
+            Event::InlineMath(latex) => {
+                if !spans.is_empty() {
+                    inlines.push(Inline::Spans(std::mem::take(&mut spans)));
+                }
+                inlines.push(Inline::Math(latex.to_string()));
+            }
+            Event::DisplayMath(latex) => {
+                flush(
+                    &mut blocks,
+                    &mut inlines,
+                    &mut spans,
+                    &mut heading,
+                    &mut marker,
+                    lists.len(),
+                );
+                blocks.push(Block {
+                    kind: BlockKind::Math(latex.to_string()),
+                    indent: lists.len(),
+                    marker: marker.take(),
+                    inlines: Vec::new(),
+                });
+            }
+            Event::Text(t) => match &mut code_block {
+                Some(code) => code.push_str(&t),
+                None => {
+                    let mut text_span = span(t.to_string());
+
+                    // Spans without a font use the one of the block (bold for headings)
+                    if strong > 0 || emphasis > 0 {
+                        let mut font = Font::default();
+                        if strong > 0 {
+                            font.weight = font::Weight::Bold;
+                        }
+                        if emphasis > 0 {
+                            font.style = font::Style::Italic;
+                        }
+                        text_span = text_span.font(font);
+                    }
+                    if let Some(url) = &link {
+                        text_span = text_span.link(url.clone()).color(accent).underline(true);
+                    }
+                    spans.push(text_span);
+                }
+            },
+            Event::Code(code) => spans.push(span(code.to_string()).font(Font::MONOSPACE)),
+            Event::SoftBreak => spans.push(span(" ")),
+            Event::HardBreak => spans.push(span("\n")),
+            _ => {}
+        }
+    }
+
+    flush(
+        &mut blocks,
+        &mut inlines,
+        &mut spans,
+        &mut heading,
+        &mut marker,
+        lists.len(),
+    );
+
+    blocks
+}
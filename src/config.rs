@@ -5,14 +5,48 @@ use cosmic::{
     theme,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::app::core::models::{
+    duplicates::DuplicateScope, list_options::FlashcardListOptions, ordering::OrderMode,
+};
 
 const APP_ID: &str = "dev.mariinkys.Oboete";
 const CONFIG_VERSION: u64 = 1;
 
-#[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
+#[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
 pub struct OboeteConfig {
     pub app_theme: AppTheme,
+    /// Local hour (0-23) at which a new study day starts
+    pub next_day_starts_at: u8,
+    /// Play the audio of a flashcard side as soon as it's shown while studying
+    pub autoplay_audio: bool,
+    /// Days after which the items on the trash are deleted for good
+    pub trash_retention_days: u32,
+    /// Where flashcards with the same front are considered duplicates
+    pub duplicate_scope: DuplicateScope,
+    /// Sort and filters of the flashcards list of each folder (by folder id)
+    pub flashcard_list_options: HashMap<i32, FlashcardListOptions>,
+    /// How the study sets are ordered on the nav bar
+    pub studyset_order: OrderMode,
+    /// How the folders of a study set are ordered
+    pub folder_order: OrderMode,
+}
+
+impl Default for OboeteConfig {
+    fn default() -> Self {
+        Self {
+            app_theme: AppTheme::default(),
+            next_day_starts_at: 4,
+            autoplay_audio: false,
+            trash_retention_days: 30,
+            duplicate_scope: DuplicateScope::default(),
+            flashcard_list_options: HashMap::new(),
+            studyset_order: OrderMode::default(),
+            folder_order: OrderMode::default(),
+        }
+    }
 }
 
 impl OboeteConfig {